@@ -2,15 +2,27 @@
 // cargo bench --features bench
 
 #![cfg(feature = "bench")]
-use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use criterion::measurement::WallTime;
+use criterion::{
+    black_box, criterion_group, criterion_main, BatchSize, BenchmarkGroup, Criterion, Throughput,
+};
+use fastasx::enums::MessageTypes;
+use fastasx::events::{Dispatcher, EventSink};
+use fastasx::ladder::LadderOrderBook;
+use fastasx::orderbook::{LimitOrderBook, OrderBook, OrderBookManager};
+use fastasx::orderflow::OrderFlow;
+use fastasx::shard::ShardedOrderBookManager;
+use fastasx::stockdirectory::StockDirectoryManager;
+use fastasx::tradetape::TradeTapeManager;
 use fastasx::types::{EnumTestHelpers, GenerateExampleMessage, Parse};
 use fastasx::{
     addordermessages, enums, helpers, modifyordermessages, noiimessages, stockmessages,
     systemmessages, trademessages, types,
 };
+use std::collections::VecDeque;
 
 fn bench_byte_to_bool(c: &mut Criterion) {
-    let valid_bytes = [b'Y', b'N'];
+    let valid_bytes = *b"YN";
     let byte = valid_bytes[fastrand::usize(..valid_bytes.len())];
 
     c.bench_function("byte_to_bool", |b| {
@@ -19,7 +31,7 @@ fn bench_byte_to_bool(c: &mut Criterion) {
 }
 
 fn bench_byte_to_bool_space(c: &mut Criterion) {
-    let valid_bytes = [b'Y', b'N', b' '];
+    let valid_bytes = *b"YN ";
     let byte = valid_bytes[fastrand::usize(..valid_bytes.len())];
 
     c.bench_function("byte_to_bool_space", |b| {
@@ -46,7 +58,7 @@ fn bench_stock_parsing(c: &mut Criterion) {
 
     c.bench_function("stock_parsing", |b| {
         b.iter(|| {
-            let _parsed: types::Stock = black_box(example_msg).try_into().unwrap();
+            let _parsed: types::Stock = black_box(&example_msg[..]).try_into().unwrap();
         });
     });
 }
@@ -155,6 +167,48 @@ fn bench_ipo_quoting_period_update(c: &mut Criterion) {
     });
 }
 
+fn bench_luld_auction_collar(c: &mut Criterion) {
+    let example_msg = stockmessages::LULDAuctionCollar::generate_binary_example();
+
+    c.bench_function("luld_auction_collar", |b| {
+        b.iter(|| {
+            let parsed = stockmessages::LULDAuctionCollar::parse(black_box(&example_msg));
+            assert!(
+                parsed.is_ok(),
+                "Parsing the luld auction collar message failed"
+            );
+        });
+    });
+}
+
+fn bench_operational_halt(c: &mut Criterion) {
+    let example_msg = stockmessages::OperationalHalt::generate_binary_example();
+
+    c.bench_function("operational_halt", |b| {
+        b.iter(|| {
+            let parsed = stockmessages::OperationalHalt::parse(black_box(&example_msg));
+            assert!(
+                parsed.is_ok(),
+                "Parsing the operational halt message failed"
+            );
+        });
+    });
+}
+
+fn bench_dlcr_price_discovery(c: &mut Criterion) {
+    let example_msg = stockmessages::DLCRPriceDiscovery::generate_binary_example();
+
+    c.bench_function("dlcr_price_discovery", |b| {
+        b.iter(|| {
+            let parsed = stockmessages::DLCRPriceDiscovery::parse(black_box(&example_msg));
+            assert!(
+                parsed.is_ok(),
+                "Parsing the dlcr price discovery message failed"
+            );
+        });
+    });
+}
+
 fn bench_non_crossing_trade(c: &mut Criterion) {
     let example_msg = trademessages::NonCrossingTrade::generate_binary_example();
 
@@ -275,6 +329,72 @@ fn bench_net_order_imbalance_indicator(c: &mut Criterion) {
     });
 }
 
+fn bench_order_book<B: OrderBook>(
+    group: &mut BenchmarkGroup<WallTime>,
+    name: &str,
+    messages: &[MessageTypes],
+) {
+    group.bench_function(name, |b| {
+        b.iter_batched_ref(
+            OrderBookManager::<B>::default,
+            |order_book_manager| {
+                let mut events = VecDeque::new();
+                for message in messages {
+                    order_book_manager.on_message(black_box(message), &mut events);
+                    events.clear();
+                }
+            },
+            BatchSize::LargeInput,
+        );
+    });
+}
+
+/// Book maintenance on a synthetic day of order flow, see `OrderFlow`. Reports messages per second.
+fn bench_order_books(c: &mut Criterion) {
+    let messages: Vec<_> = OrderFlow::new(42, 500, 1_000_000).collect();
+    let mut group = c.benchmark_group("order_book");
+    group.throughput(Throughput::Elements(messages.len() as u64));
+    group.sample_size(20);
+    bench_order_book::<LimitOrderBook>(&mut group, "btree", &messages);
+    bench_order_book::<LadderOrderBook>(&mut group, "ladder", &messages);
+
+    // The same books on 4 worker threads, including waiting for them to finish
+    group.bench_function("sharded", |b| {
+        b.iter_batched_ref(
+            || ShardedOrderBookManager::new(OrderBookManager::new(), 4),
+            |sharded| {
+                let mut events = VecDeque::new();
+                for message in &messages {
+                    sharded.on_message(black_box(message), &mut events);
+                    events.clear();
+                }
+                black_box(sharded.finish());
+            },
+            BatchSize::LargeInput,
+        );
+    });
+
+    // The sinks of a headless replay: the books, stock directory and trade tape
+    group.bench_function("dispatch", |b| {
+        b.iter_batched_ref(
+            || {
+                let mut dispatcher = Dispatcher::new();
+                dispatcher.add_sink(OrderBookManager::new());
+                dispatcher.add_sink(StockDirectoryManager::new());
+                dispatcher.add_sink(TradeTapeManager::new());
+                dispatcher
+            },
+            |dispatcher| {
+                for message in &messages {
+                    dispatcher.dispatch(black_box(message));
+                }
+            },
+            BatchSize::LargeInput,
+        );
+    });
+    group.finish();
+}
+
 criterion_group!(
     benches,
     bench_byte_to_bool,
@@ -289,6 +409,9 @@ criterion_group!(
     bench_mwcb_decline_level,
     bench_mwcb_status,
     bench_ipo_quoting_period_update,
+    bench_luld_auction_collar,
+    bench_operational_halt,
+    bench_dlcr_price_discovery,
     bench_non_crossing_trade,
     bench_crossing_trade,
     bench_broken_trade,
@@ -299,6 +422,7 @@ criterion_group!(
     bench_order_replace,
     bench_add_order,
     bench_net_order_imbalance_indicator,
+    bench_order_books,
 );
 
 criterion_main!(benches);
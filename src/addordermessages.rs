@@ -2,8 +2,8 @@ use crate::enums::BuySellIndicator;
 use crate::messageheader::MessageHeader;
 use crate::types::{PriceConversions, MPID};
 use crate::types::{
-    AltBinaryMessageLength, AltMessageHeaderType, BinaryMessageLength, MessageHeaderType, Parse,
-    ParseError, Price4, Stock,
+    AltBinaryMessageLength, AltMessageHeaderType, BinaryMessageLength, Encode, MessageHeaderType,
+    Parse, ParseError, Price4, Stock,
 };
 use byteorder::{BigEndian, ByteOrder};
 
@@ -63,6 +63,56 @@ impl AltMessageHeaderType for AddOrder {
     const ALT_MESSAGE_TYPE: u8 = b'F';
 }
 
+impl Encode for AddOrder {
+    fn message_type(&self) -> u8 {
+        match self.mpid {
+            Some(_) => Self::ALT_MESSAGE_TYPE,
+            None => Self::MESSAGE_TYPE,
+        }
+    }
+
+    fn encode(&self, output: &mut Vec<u8>) {
+        self.header.encode(output);
+        output.extend_from_slice(&self.order_reference_number.to_be_bytes());
+        output.push(u8::from(&self.buy_sell_indicator));
+        output.extend_from_slice(&self.shares.to_be_bytes());
+        output.extend_from_slice(&self.stock);
+        output.extend_from_slice(&self.price.value.to_be_bytes());
+        if let Some(mpid) = self.mpid {
+            output.extend_from_slice(&mpid);
+        }
+    }
+}
+
+#[cfg(any(test, feature = "bench"))]
+impl AddOrder {
+    /// A 100 share AAPL order without an MPID, for tests and benchmarks.
+    ///
+    /// # Arguments
+    /// * `stock_locate` - The stock locate code of the order.
+    /// * `timestamp` - Nanoseconds since midnight.
+    /// * `order_reference_number` - The order reference number.
+    /// * `buy_sell_indicator` - The side of the order.
+    /// * `price` - The raw price with four implied decimal places, e.g. `150_0000` for 150.0000.
+    pub fn example(
+        stock_locate: u16,
+        timestamp: u64,
+        order_reference_number: u64,
+        buy_sell_indicator: BuySellIndicator,
+        price: u32,
+    ) -> AddOrder {
+        AddOrder {
+            header: MessageHeader::example(stock_locate, timestamp),
+            order_reference_number,
+            buy_sell_indicator,
+            shares: 100,
+            stock: *b"AAPL    ",
+            price: Price4::new(price),
+            mpid: None,
+        }
+    }
+}
+
 #[cfg(any(test, feature = "bench"))]
 impl GenerateExampleMessage<{ Self::LENGTH }> for AddOrder {
     fn generate_binary_example() -> [u8; Self::LENGTH] {
@@ -78,7 +128,7 @@ impl GenerateExampleMessage<{ Self::LENGTH }> for AddOrder {
         let mut example = [0; Self::LENGTH];
         example[..10].copy_from_slice(&header);
         BigEndian::write_u64(&mut example[10..18], order_reference_number);
-        example[18] = buy_sell_indicator.into();
+        example[18] = buy_sell_indicator;
         BigEndian::write_u32(&mut example[19..23], shares);
         example[23..31].copy_from_slice(&stock);
         BigEndian::write_u32(&mut example[31..35], price);
@@ -0,0 +1,259 @@
+use crate::enums::{CrossType, ImbalanceDirection, MessageTypes};
+use crate::events::{Event, EventSink};
+use crate::noiimessages::NetOrderImbalanceIndicator;
+use crate::orderbook::StockLocateCode;
+use crate::trademessages::CrossingTrade;
+use crate::types::Price4;
+use std::collections::{HashMap, VecDeque};
+
+/// The state of an auction as published by a single Net Order Imbalance Indicator ('I') message.
+///
+/// # Fields
+/// * `timestamp` - Nanoseconds since midnight.
+/// * `paired_shares` - The shares that would be matched at the current reference price.
+/// * `imbalance_shares` - The shares that would not be matched at the current reference price.
+/// * `imbalance_direction` - The side of the imbalance.
+/// * `far_price` - The price the cross would happen at, considering cross orders only.
+/// * `near_price` - The price the cross would happen at, considering cross orders and continuous book orders.
+/// * `current_reference_price` - The price within the inside at which paired shares are maximized.
+/// * `price_variation_indicator` - How far the near price is from the reference price, as an ITCH code.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Imbalance {
+    pub timestamp: u64,
+    pub paired_shares: u64,
+    pub imbalance_shares: u64,
+    pub imbalance_direction: ImbalanceDirection,
+    pub far_price: Price4,
+    pub near_price: Price4,
+    pub current_reference_price: Price4,
+    pub price_variation_indicator: char,
+}
+
+impl From<&NetOrderImbalanceIndicator> for Imbalance {
+    fn from(noii: &NetOrderImbalanceIndicator) -> Self {
+        Self {
+            timestamp: noii.header.timestamp,
+            paired_shares: noii.paired_shares,
+            imbalance_shares: noii.imbalance_shares,
+            imbalance_direction: noii.imbalance_direction,
+            far_price: noii.far_price,
+            near_price: noii.near_price,
+            current_reference_price: noii.current_reference_price,
+            price_variation_indicator: noii.price_variation_indicator,
+        }
+    }
+}
+
+/// The print of a cross, from a Crossing Trade ('Q') message.
+///
+/// # Fields
+/// * `timestamp` - Nanoseconds since midnight.
+/// * `shares` - The shares matched in the cross.
+/// * `price` - The cross price.
+/// * `match_number` - The match number of the print.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cross {
+    pub timestamp: u64,
+    pub shares: u64,
+    pub price: Price4,
+    pub match_number: u64,
+}
+
+/// A single opening, closing, IPO/halt or intraday cross of a stock.
+///
+/// # Fields
+/// * `cross_type` - The type of cross.
+/// * `imbalances` - Every imbalance published ahead of the cross, in file order. Empty for crosses without NOII messages.
+/// * `cross` - The final print of the auction, `None` until it crosses.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Auction {
+    pub cross_type: CrossType,
+    pub imbalances: Vec<Imbalance>,
+    pub cross: Option<Cross>,
+}
+
+impl Auction {
+    fn new(cross_type: CrossType) -> Self {
+        Self {
+            cross_type,
+            imbalances: Vec::new(),
+            cross: None,
+        }
+    }
+
+    /// The latest imbalance published, `None` if there were none.
+    pub fn latest(&self) -> Option<&Imbalance> {
+        self.imbalances.last()
+    }
+
+    /// Returns true once the auction has crossed.
+    pub fn is_crossed(&self) -> bool {
+        self.cross.is_some()
+    }
+}
+
+/// Tracks the auctions of every stock from Net Order Imbalance Indicator ('I') messages, and ties each auction to its
+/// Crossing Trade ('Q') print.
+///
+/// An imbalance message opens an auction of its cross type, unless one is already waiting to cross, and the next
+/// cross print of that type closes it.
+///
+/// # Fields
+/// * `auctions` - The auctions of each stock, in the order they opened.
+#[derive(Debug, Default)]
+pub struct AuctionTracker {
+    pub auctions: HashMap<StockLocateCode, Vec<Auction>>,
+}
+
+impl AuctionTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The auctions of a stock, in the order they opened.
+    pub fn get_auctions(&self, stock_locate: StockLocateCode) -> &[Auction] {
+        self.auctions
+            .get(&stock_locate)
+            .map_or(&[], |auctions| auctions.as_slice())
+    }
+
+    /// The latest auction of a type for a stock, whether it has crossed or not.
+    ///
+    /// # Arguments
+    /// * `stock_locate` - The stock locate code of the stock.
+    /// * `cross_type` - The type of cross, e.g. `CrossType::ClosingCross`.
+    pub fn latest_auction(
+        &self,
+        stock_locate: StockLocateCode,
+        cross_type: CrossType,
+    ) -> Option<&Auction> {
+        self.get_auctions(stock_locate)
+            .iter()
+            .rev()
+            .find(|auction| auction.cross_type == cross_type)
+    }
+
+    /// The auction of a type that is waiting to cross, opening one if there is none.
+    fn open_auction(
+        &mut self,
+        stock_locate: StockLocateCode,
+        cross_type: CrossType,
+    ) -> &mut Auction {
+        let auctions = self.auctions.entry(stock_locate).or_default();
+        let open = auctions
+            .iter()
+            .rposition(|auction| auction.cross_type == cross_type && !auction.is_crossed());
+        match open {
+            Some(index) => &mut auctions[index],
+            None => {
+                auctions.push(Auction::new(cross_type));
+                auctions.last_mut().unwrap()
+            }
+        }
+    }
+
+    pub fn add_imbalance(&mut self, noii: &NetOrderImbalanceIndicator) {
+        self.open_auction(noii.header.stock_locate, noii.cross_type)
+            .imbalances
+            .push(Imbalance::from(noii));
+    }
+
+    pub fn add_cross(&mut self, trade: &CrossingTrade) {
+        self.open_auction(trade.header.stock_locate, trade.cross_type)
+            .cross = Some(Cross {
+            timestamp: trade.header.timestamp,
+            shares: trade.shares,
+            price: trade.cross_price,
+            match_number: trade.match_number,
+        });
+    }
+}
+
+impl EventSink for AuctionTracker {
+    fn on_message(&mut self, message: &MessageTypes, _events: &mut VecDeque<Event>) {
+        match message {
+            MessageTypes::NOII(noii) => self.add_imbalance(noii),
+            MessageTypes::CrossingTrade(trade) => self.add_cross(trade),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::messageheader::MessageHeader;
+    use crate::types::PriceConversions;
+
+    fn noii(timestamp: u64, cross_type: CrossType, paired_shares: u64) -> MessageTypes {
+        MessageTypes::NOII(NetOrderImbalanceIndicator {
+            header: MessageHeader::example(7, timestamp),
+            paired_shares,
+            imbalance_shares: 500,
+            imbalance_direction: ImbalanceDirection::BuyImbalance,
+            stock: *b"AAPL    ",
+            far_price: Price4::new(150_0000),
+            near_price: Price4::new(150_1000),
+            current_reference_price: Price4::new(150_0500),
+            cross_type,
+            price_variation_indicator: 'L',
+        })
+    }
+
+    fn cross(timestamp: u64, cross_type: CrossType, shares: u64) -> MessageTypes {
+        MessageTypes::CrossingTrade(CrossingTrade {
+            header: MessageHeader::example(7, timestamp),
+            shares,
+            stock: *b"AAPL    ",
+            cross_price: Price4::new(150_0500),
+            match_number: timestamp,
+            cross_type,
+        })
+    }
+
+    #[test]
+    fn test_auction_tracker() {
+        let mut tracker = AuctionTracker::new();
+        let mut events = VecDeque::new();
+        for message in [
+            noii(1, CrossType::OpeningCross, 1_000),
+            noii(2, CrossType::OpeningCross, 2_000),
+            cross(3, CrossType::OpeningCross, 2_000),
+            noii(4, CrossType::ClosingCross, 3_000),
+            noii(5, CrossType::ClosingCross, 4_000),
+        ] {
+            tracker.on_message(&message, &mut events);
+        }
+
+        let opening = tracker.latest_auction(7, CrossType::OpeningCross).unwrap();
+        assert_eq!(opening.imbalances.len(), 2);
+        assert_eq!(opening.cross.unwrap().shares, 2_000);
+
+        // The closing auction is still waiting to cross
+        let closing = tracker.latest_auction(7, CrossType::ClosingCross).unwrap();
+        assert!(!closing.is_crossed());
+        assert_eq!(closing.latest().unwrap().paired_shares, 4_000);
+
+        tracker.on_message(&cross(6, CrossType::ClosingCross, 4_000), &mut events);
+        let closing = tracker.latest_auction(7, CrossType::ClosingCross).unwrap();
+        assert_eq!(closing.imbalances.len(), 2);
+        assert_eq!(
+            closing.cross,
+            Some(Cross {
+                timestamp: 6,
+                shares: 4_000,
+                price: Price4::new(150_0500),
+                match_number: 6,
+            })
+        );
+
+        // A halt cross without imbalance messages is an auction of its own
+        tracker.on_message(
+            &cross(7, CrossType::IPOCrossOrHaltedSecurity, 100),
+            &mut events,
+        );
+        assert_eq!(tracker.get_auctions(7).len(), 3);
+        assert!(tracker.get_auctions(8).is_empty());
+        assert!(events.is_empty());
+    }
+}
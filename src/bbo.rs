@@ -0,0 +1,170 @@
+use crate::enums::MessageTypes;
+use crate::events::{Event, EventSink, TopOfBook};
+use crate::helpers::stock_to_str;
+use crate::orderbook::StockLocateCode;
+use crate::types::{Price4, Stock};
+use std::collections::{HashMap, VecDeque};
+use std::io::{self, Write};
+
+/// Writes a top of book (L1 quote) stream as CSV, one row per `TopOfBook` event.
+/// Rows are only written when the best bid, best ask or the shares at either change, see `OrderBookManager`.
+///
+/// # Fields
+/// * `writer` - Destination of the CSV rows.
+/// * `symbols` - Stocks to write, empty for every stock.
+/// * `stocks` - Stock locate code > stock, from 'R' messages. Locate codes are only valid for a single day.
+/// * `error` - The first write error, returned by `flush`. Nothing is written once set.
+pub struct BboWriter<W: Write> {
+    writer: W,
+    symbols: Vec<Stock>,
+    stocks: HashMap<StockLocateCode, Stock>,
+    error: Option<io::Error>,
+}
+
+impl<W: Write> BboWriter<W> {
+    /// Create a BBO writer, writing the CSV header immediately.
+    ///
+    /// # Arguments
+    /// * `writer` - Destination of the CSV rows.
+    /// * `symbols` - Stocks to write, empty for every stock.
+    pub fn new(mut writer: W, symbols: Vec<Stock>) -> Result<Self, io::Error> {
+        writeln!(
+            writer,
+            "timestamp,stock,stock_locate,bid,bid_size,ask,ask_size"
+        )?;
+        Ok(Self {
+            writer,
+            symbols,
+            stocks: HashMap::new(),
+            error: None,
+        })
+    }
+
+    /// Forget the stock directory, before replaying the next day.
+    pub fn reset(&mut self) {
+        self.stocks.clear();
+    }
+
+    /// Flush the writer.
+    ///
+    /// # Returns
+    /// * `Ok(())` - If every row was written.
+    /// * `Err(io::Error)` - The first error hit while writing or flushing.
+    pub fn flush(&mut self) -> Result<(), io::Error> {
+        match self.error.take() {
+            Some(e) => Err(e),
+            None => self.writer.flush(),
+        }
+    }
+
+    fn write_row(&mut self, top_of_book: &TopOfBook) -> Result<(), io::Error> {
+        let Some(stock) = self.stocks.get(&top_of_book.stock_locate) else {
+            log::debug!(
+                "Skipping BBO of stock locate {} as it is not in the stock directory",
+                top_of_book.stock_locate
+            );
+            return Ok(());
+        };
+        if !self.symbols.is_empty() && !self.symbols.contains(stock) {
+            return Ok(());
+        }
+        let format_price =
+            |price: Option<Price4>| price.map(|price| price.to_string()).unwrap_or_default();
+        writeln!(
+            self.writer,
+            "{},{},{},{},{},{},{}",
+            top_of_book.timestamp,
+            stock_to_str(stock),
+            top_of_book.stock_locate,
+            format_price(top_of_book.best_bid),
+            top_of_book.bid_size,
+            format_price(top_of_book.best_ask),
+            top_of_book.ask_size,
+        )
+    }
+}
+
+impl<W: Write> EventSink for BboWriter<W> {
+    fn on_message(&mut self, message: &MessageTypes, _events: &mut VecDeque<Event>) {
+        if let MessageTypes::StockDirectory(directory) = message {
+            self.stocks
+                .insert(directory.header.stock_locate, directory.stock);
+        }
+    }
+
+    fn on_event(&mut self, event: &Event, _events: &mut VecDeque<Event>) {
+        if self.error.is_some() {
+            return;
+        }
+        if let Event::TopOfBook(top_of_book) = event {
+            if let Err(e) = self.write_row(top_of_book) {
+                log::error!("Failed to write BBO: {e}");
+                self.error = Some(e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::addordermessages::AddOrder;
+    use crate::enums::BuySellIndicator;
+    use crate::events::Dispatcher;
+    use crate::messageheader::MessageHeader;
+    use crate::modifyordermessages::OrderExecuted;
+    use crate::orderbook::OrderBookManager;
+    use crate::stockmessages::StockDirectory;
+    use std::sync::{Arc, RwLock};
+
+    fn add_order(
+        order_reference_number: u64,
+        buy_sell_indicator: BuySellIndicator,
+        shares: u32,
+        price: u32,
+    ) -> MessageTypes {
+        MessageTypes::AddOrder(AddOrder {
+            shares,
+            ..AddOrder::example(
+                1,
+                order_reference_number,
+                order_reference_number,
+                buy_sell_indicator,
+                price,
+            )
+        })
+    }
+
+    #[test]
+    fn test_bbo_writer() {
+        let directory = StockDirectory::example(1, *b"AAPL    ");
+
+        let bbo_writer = Arc::new(RwLock::new(BboWriter::new(Vec::new(), Vec::new()).unwrap()));
+        let mut dispatcher = Dispatcher::new();
+        dispatcher.add_sink(OrderBookManager::new());
+        dispatcher.add_sink(Arc::clone(&bbo_writer));
+
+        dispatcher.dispatch(&MessageTypes::StockDirectory(directory));
+        dispatcher.dispatch(&add_order(1, BuySellIndicator::Buy, 100, 150_0000));
+        dispatcher.dispatch(&add_order(2, BuySellIndicator::Buy, 100, 149_0000)); // Behind the best bid, no change
+        dispatcher.dispatch(&add_order(3, BuySellIndicator::Sell, 50, 151_0000));
+        dispatcher.dispatch(&add_order(4, BuySellIndicator::Buy, 20, 150_0000)); // Size change only
+        dispatcher.dispatch(&MessageTypes::OrderExecuted(OrderExecuted {
+            header: MessageHeader::example(1, 5),
+            order_reference_number: 3,
+            executed_shares: 50,
+            match_number: 1,
+        })); // Ask side emptied
+
+        let mut bbo_writer = bbo_writer.write().unwrap();
+        bbo_writer.flush().unwrap();
+        assert_eq!(
+            String::from_utf8(bbo_writer.writer.clone()).unwrap(),
+            "timestamp,stock,stock_locate,bid,bid_size,ask,ask_size\n\
+             1,AAPL,1,150.0000,100,,0\n\
+             3,AAPL,1,150.0000,100,151.0000,50\n\
+             4,AAPL,1,150.0000,120,151.0000,50\n\
+             5,AAPL,1,150.0000,120,,0\n"
+        );
+    }
+}
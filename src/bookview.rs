@@ -0,0 +1,230 @@
+use crate::enums::MessageTypes;
+use crate::events::{Event, EventSink};
+use crate::orderbook::{order_message_header, Depth, OrderBookManager, StockLocateCode};
+use crate::types::Stock;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, RwLock, TryLockError};
+use std::time::{Duration, Instant};
+
+/// A copy of a single book, published by a `BookPublisher` for readers on other threads, e.g. the TUI.
+///
+/// # Fields
+/// * `version` - Incremented on every publish, so readers can tell whether the book changed since they last looked.
+/// * `timestamp` - Nanoseconds since midnight, of the last message applied to the book.
+/// * `stock_locate` - The stock locate code of the stock, `None` until its 'R' (stock directory) message arrives.
+/// * `depth` - Every price level of the book, best first.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BookView {
+    pub version: u64,
+    pub timestamp: u64,
+    pub stock_locate: Option<StockLocateCode>,
+    pub depth: Depth,
+}
+
+/// The latest `BookView` of a stock, shared between the replay and its readers.
+///
+/// The slot only holds a pointer to an immutable view, so the lock is held for a reference count increment and never
+/// while a book is copied or drawn. The replay only ever tries the lock: if a reader holds it, the view is published
+/// on a later message instead, so readers can not slow down ingestion.
+#[derive(Debug, Default)]
+pub struct PublishedBook {
+    slot: Mutex<Arc<BookView>>,
+}
+
+impl PublishedBook {
+    /// Returns the latest view. Readers keep it for as long as they like, without holding up the replay.
+    pub fn load(&self) -> Arc<BookView> {
+        Arc::clone(&self.slot.lock().unwrap())
+    }
+
+    /// Replace the view unless a reader is loading it.
+    ///
+    /// # Returns
+    /// * `bool` - True if the view was replaced.
+    fn try_store(&self, view: BookView) -> bool {
+        match self.slot.try_lock() {
+            Ok(mut slot) => {
+                *slot = Arc::new(view);
+                true
+            }
+            Err(TryLockError::WouldBlock) => false,
+            Err(TryLockError::Poisoned(e)) => panic!("published book poisoned: {e}"),
+        }
+    }
+}
+
+/// Publishes the book of a single stock to a `PublishedBook`, so readers never lock the `OrderBookManager`.
+/// Must be registered after the order book manager, so each message has been applied before the book is copied.
+///
+/// Copying a book is much slower than applying a message to it, so a changed book is published at most once per
+/// `interval` of wall clock time. Call `flush` once the replay is done, to publish the final book.
+///
+/// # Fields
+/// * `order_book_manager` - The books being replayed.
+/// * `stock` - The stock to publish.
+/// * `stock_locate` - The stock locate code of `stock`, from its 'R' message. Locate codes are only valid for a single day.
+/// * `published` - Where views are published.
+/// * `interval` - The minimum wall clock time between publishes.
+/// * `last_published` - When the book was last published, `None` before the first publish.
+/// * `timestamp` - The timestamp of the last message applied to the book.
+/// * `version` - The version of the last view published.
+/// * `dirty` - True if the book changed since it was last published.
+pub struct BookPublisher {
+    order_book_manager: Arc<RwLock<OrderBookManager>>,
+    stock: Stock,
+    stock_locate: Option<StockLocateCode>,
+    published: Arc<PublishedBook>,
+    interval: Duration,
+    last_published: Option<Instant>,
+    timestamp: u64,
+    version: u64,
+    dirty: bool,
+}
+
+impl BookPublisher {
+    /// # Arguments
+    /// * `order_book_manager` - The books being replayed.
+    /// * `stock` - The stock to publish.
+    /// * `interval` - The minimum wall clock time between publishes, e.g. the refresh rate of the reader.
+    pub fn new(
+        order_book_manager: Arc<RwLock<OrderBookManager>>,
+        stock: Stock,
+        interval: Duration,
+    ) -> Self {
+        Self {
+            order_book_manager,
+            stock,
+            stock_locate: None,
+            published: Arc::default(),
+            interval,
+            last_published: None,
+            timestamp: 0,
+            version: 0,
+            dirty: false,
+        }
+    }
+
+    /// Returns the views published, to hand to readers.
+    pub fn published(&self) -> Arc<PublishedBook> {
+        Arc::clone(&self.published)
+    }
+
+    /// Publish the book if it changed since it was last published, waiting for readers if need be.
+    pub fn flush(&mut self) {
+        if self.dirty {
+            let view = self.view();
+            *self.published.slot.lock().unwrap() = Arc::new(view);
+            self.published_now();
+        }
+    }
+
+    fn view(&self) -> BookView {
+        let depth = self.stock_locate.and_then(|stock_locate| {
+            let order_book_manager = self.order_book_manager.read().unwrap();
+            order_book_manager
+                .order_books
+                .get(&stock_locate)
+                .map(|book| book.get_depth(usize::MAX))
+        });
+        BookView {
+            version: self.version + 1,
+            timestamp: self.timestamp,
+            stock_locate: self.stock_locate,
+            depth: depth.unwrap_or_default(),
+        }
+    }
+
+    fn published_now(&mut self) {
+        self.version += 1;
+        self.dirty = false;
+        self.last_published = Some(Instant::now());
+    }
+}
+
+impl EventSink for BookPublisher {
+    fn on_message(&mut self, message: &MessageTypes, _events: &mut VecDeque<Event>) {
+        match message {
+            MessageTypes::StockDirectory(directory) if directory.stock == self.stock => {
+                self.stock_locate = Some(directory.header.stock_locate);
+                self.timestamp = directory.header.timestamp;
+                self.dirty = true;
+            }
+            _ => match order_message_header(message) {
+                Some(header) if Some(header.stock_locate) == self.stock_locate => {
+                    self.timestamp = header.timestamp;
+                    self.dirty = true;
+                }
+                _ => return,
+            },
+        }
+        let due = self
+            .last_published
+            .is_none_or(|last_published| last_published.elapsed() >= self.interval);
+        if due && self.published.try_store(self.view()) {
+            self.published_now();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::addordermessages::AddOrder;
+    use crate::enums::BuySellIndicator;
+    use crate::stockmessages::StockDirectory;
+
+    fn add_order(order_reference_number: u64, timestamp: u64) -> MessageTypes {
+        MessageTypes::AddOrder(AddOrder::example(
+            1,
+            timestamp,
+            order_reference_number,
+            BuySellIndicator::Buy,
+            150_0000,
+        ))
+    }
+
+    #[test]
+    fn test_book_publisher() {
+        let order_book_manager = Arc::new(RwLock::new(OrderBookManager::new()));
+        let mut publisher = BookPublisher::new(
+            Arc::clone(&order_book_manager),
+            *b"AAPL    ",
+            Duration::from_secs(3600),
+        );
+        let published = publisher.published();
+        let mut events = VecDeque::new();
+        let mut dispatch = |publisher: &mut BookPublisher, message: MessageTypes| {
+            order_book_manager
+                .write()
+                .unwrap()
+                .on_message(&message, &mut events);
+            publisher.on_message(&message, &mut events);
+        };
+        assert_eq!(published.load().stock_locate, None);
+
+        let directory = StockDirectory::example(1, *b"AAPL    ");
+        dispatch(&mut publisher, MessageTypes::StockDirectory(directory));
+        let view = published.load();
+        assert_eq!((view.version, view.stock_locate), (1, Some(1)));
+
+        // Changes within the interval wait for the next publish
+        dispatch(&mut publisher, add_order(1, 2));
+        dispatch(&mut publisher, add_order(2, 3));
+        assert_eq!(published.load().version, 1);
+
+        // Readers holding the slot never block the replay
+        let reader = published.slot.lock().unwrap();
+        publisher.last_published = None;
+        dispatch(&mut publisher, add_order(3, 4));
+        drop(reader);
+        assert_eq!(view.version, 1);
+        assert_eq!(published.load().version, 1);
+
+        publisher.flush();
+        let view = published.load();
+        assert_eq!((view.version, view.timestamp), (2, 4));
+        assert_eq!(view.depth.bids.len(), 1);
+        assert_eq!(view.depth.bids[0].share_quantity, 300);
+        assert!(view.depth.asks.is_empty());
+    }
+}
@@ -0,0 +1,224 @@
+use clap::error::ErrorKind;
+use clap::{CommandFactory, Parser, ValueEnum};
+use fastasx::depth::SnapshotSchedule;
+use fastasx::filter::SymbolFilter;
+use fastasx::helpers::{parse_time_of_day, str_to_stock};
+use fastasx::types::Stock;
+use std::path::PathBuf;
+
+/// Command line interface for fastasx_bin.
+#[derive(Parser, Debug)]
+#[command(
+    name = "fastasx_bin",
+    version,
+    about = "Parses ITCH 5.0 files and builds per-stock order books"
+)]
+pub struct Cli {
+    /// ITCH 5.0 files to process. Each file is replayed independently, in the order given.
+    #[arg(required = true, value_name = "FILE")]
+    pub inputs: Vec<PathBuf>,
+
+    /// What to do with the parsed messages.
+    #[arg(short, long, value_enum, default_value_t = OutputMode::Tui)]
+    pub mode: OutputMode,
+
+    /// Comma separated tickers to report on, e.g. `AAPL,MSFT`. The first ticker is shown in the TUI.
+    #[arg(short, long, value_delimiter = ',', value_parser = parse_symbol)]
+    pub symbols: Vec<Stock>,
+
+    /// Only build the books of `--symbols`, skipping the messages of every other stock before they are parsed.
+    #[arg(long, requires = "symbols")]
+    pub filter: bool,
+
+    /// Comma separated stock locate codes to build the books of, skipping the messages of every other stock.
+    /// Combined with `--filter`, stocks matching either are kept.
+    #[arg(long, value_name = "CODES", value_delimiter = ',', value_parser = clap::value_parser!(u16).range(1..))]
+    pub stock_locates: Vec<u16>,
+
+    /// Destination for `--mode export`. Defaults to stdout.
+    #[arg(short, long, value_name = "PATH")]
+    pub output: Option<PathBuf>,
+
+    /// Log level (off, error, warn, info, debug, trace). Falls back to `RUST_LOG` when not set.
+    #[arg(short, long, value_name = "LEVEL")]
+    pub log_level: Option<log::LevelFilter>,
+
+    /// Memory map the input files and parse messages in place, instead of streaming them through a ring buffer.
+    #[arg(long)]
+    pub mmap: bool,
+
+    /// Write the top of book (BBO) of every (filtered) stock to a CSV file, one row per change of the best prices or
+    /// their sizes. Works with every mode.
+    #[arg(long, value_name = "PATH")]
+    pub bbo: Option<PathBuf>,
+
+    /// Write market by price depth snapshots of every (filtered) stock to a CSV file.
+    /// Snapshots are taken after every book update unless `--depth-interval` or `--depth-updates` is given.
+    #[arg(long, value_name = "PATH")]
+    pub depth: Option<PathBuf>,
+
+    /// Price levels per side in each depth snapshot.
+    #[arg(long, value_name = "N", default_value_t = 5, requires = "depth")]
+    pub depth_levels: usize,
+
+    /// Snapshot every book at this interval of exchange time, in milliseconds. Rows are stamped with the interval boundary.
+    #[arg(long, value_name = "MS", requires = "depth", conflicts_with = "depth_updates", value_parser = clap::value_parser!(u64).range(1..))]
+    pub depth_interval: Option<u64>,
+
+    /// Snapshot a book after every N messages that update it.
+    #[arg(long, value_name = "N", requires = "depth", value_parser = clap::value_parser!(u64).range(1..))]
+    pub depth_updates: Option<u64>,
+
+    /// Write a market by order (L3) snapshot of every book, as it stands at `--snapshot-at`, to a file.
+    /// Only supported with a single input file.
+    #[arg(long, value_name = "PATH", requires = "snapshot_at")]
+    pub snapshot: Option<PathBuf>,
+
+    /// Time of day to take the snapshot at, e.g. `09:30` or `10:00:00.5`, or nanoseconds since midnight.
+    #[arg(long, value_name = "TIME", requires = "snapshot", value_parser = parse_time)]
+    pub snapshot_at: Option<u64>,
+
+    /// Only report on messages at or after this time of day, e.g. `09:30`. Earlier messages still build the books.
+    #[arg(long, value_name = "TIME", value_parser = parse_time)]
+    pub start: Option<u64>,
+
+    /// Stop replaying at the first message at or after this time of day, e.g. `10:00`.
+    #[arg(long, value_name = "TIME", value_parser = parse_time)]
+    pub end: Option<u64>,
+
+    /// Restore the books from a `--snapshot` file instead of building them from the start of the file.
+    /// Order messages before the snapshot was taken are skipped. `--start` defaults to the snapshot time.
+    /// Only supported with a single input file.
+    #[arg(long, value_name = "PATH")]
+    pub restore: Option<PathBuf>,
+
+    /// Replay at this multiple of real time, e.g. `1` for wall clock speed or `10` for 10x, using the gaps between
+    /// message timestamps. Messages before `--start` are not paced.
+    #[arg(long, value_name = "MULTIPLIER", value_parser = parse_speed)]
+    pub speed: Option<f64>,
+
+    /// Build the order books on this many worker threads, each owning the books of a subset of stocks.
+    /// Events such as executions and BBO changes are still emitted in order for each stock, but are interleaved
+    /// across stocks. Not supported with the TUI, `--depth` or `--snapshot`, which read the books as they are built,
+    /// or `--bbo`, which is written in file order. The time each MPID spends at the inside is not tracked.
+    #[arg(long, value_name = "N", conflicts_with_all = ["bbo", "depth", "snapshot"], value_parser = clap::value_parser!(u16).range(1..))]
+    pub shards: Option<u16>,
+
+    /// Resynchronise on corrupt framing by scanning forward to the next valid message header.
+    /// A frame is corrupt if its length is zero or does not match its known message type. Unknown message types are
+    /// still skipped by their declared length.
+    #[arg(long)]
+    pub resync: bool,
+}
+
+/// Output modes supported by fastasx_bin.
+///
+/// # Variants
+/// * `Tui` - Render the order book of the first symbol in a terminal UI.
+/// * `Headless` - No UI, print processing statistics once each file is done. Suitable for batch jobs.
+/// * `Export` - No UI, write a CSV summary of every (filtered) order book once each file is done.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputMode {
+    Tui,
+    Headless,
+    Export,
+}
+
+impl Cli {
+    /// Parses the command line, exiting with a usage error if the arguments are inconsistent.
+    pub fn parse_and_validate() -> Self {
+        let cli = Cli::parse();
+        if cli.mode == OutputMode::Tui && cli.inputs.len() > 1 {
+            Cli::command()
+                .error(
+                    ErrorKind::ArgumentConflict,
+                    "the TUI can only display a single input file, use --mode headless or --mode export for several",
+                )
+                .exit();
+        }
+        if cli.snapshot.is_some() && cli.inputs.len() > 1 {
+            Cli::command()
+                .error(
+                    ErrorKind::ArgumentConflict,
+                    "--snapshot can only be taken of a single input file",
+                )
+                .exit();
+        }
+        if cli.restore.is_some() && cli.inputs.len() > 1 {
+            Cli::command()
+                .error(
+                    ErrorKind::ArgumentConflict,
+                    "--restore can only be used with a single input file",
+                )
+                .exit();
+        }
+        if let (Some(start), Some(end)) = (cli.start, cli.end) {
+            if start >= end {
+                Cli::command()
+                    .error(ErrorKind::ValueValidation, "--start must be before --end")
+                    .exit();
+            }
+        }
+        if cli.mode == OutputMode::Tui && cli.shards.is_some() {
+            Cli::command()
+                .error(
+                    ErrorKind::ArgumentConflict,
+                    "--shards is not supported by the TUI, use --mode headless or --mode export",
+                )
+                .exit();
+        }
+        if cli.mode != OutputMode::Export && cli.output.is_some() {
+            Cli::command()
+                .error(
+                    ErrorKind::ArgumentConflict,
+                    "--output is only used with --mode export",
+                )
+                .exit();
+        }
+        cli
+    }
+
+    /// The depth snapshot schedule given by `--depth-interval` or `--depth-updates`, every update by default.
+    pub fn depth_schedule(&self) -> SnapshotSchedule {
+        match (self.depth_interval, self.depth_updates) {
+            (Some(interval), _) => SnapshotSchedule::Interval(interval * 1_000_000),
+            (None, updates) => SnapshotSchedule::Updates(updates.unwrap_or(1)),
+        }
+    }
+
+    /// The allowlist given by `--filter` and `--stock-locates`, or `None` to keep every stock.
+    pub fn symbol_filter(&self) -> Option<SymbolFilter> {
+        if !self.filter && self.stock_locates.is_empty() {
+            return None;
+        }
+        let symbols = if self.filter {
+            self.symbols.clone()
+        } else {
+            Vec::new()
+        };
+        Some(SymbolFilter::new(symbols, &self.stock_locates))
+    }
+
+    /// Returns true if `stock` passes the symbol filter. An empty filter lets every stock through.
+    pub fn wants_stock(&self, stock: &Stock) -> bool {
+        self.symbols.is_empty() || self.symbols.contains(stock)
+    }
+}
+
+fn parse_symbol(symbol: &str) -> Result<Stock, String> {
+    str_to_stock(symbol)
+        .ok_or_else(|| format!("'{symbol}' is not a valid ticker (1-8 ASCII characters)"))
+}
+
+fn parse_time(time: &str) -> Result<u64, String> {
+    parse_time_of_day(time).ok_or_else(|| {
+        format!("'{time}' is not a valid time of day (HH:MM[:SS[.fraction]] or nanoseconds since midnight)")
+    })
+}
+
+fn parse_speed(speed: &str) -> Result<f64, String> {
+    match speed.parse::<f64>() {
+        Ok(speed) if speed.is_finite() && speed > 0.0 => Ok(speed),
+        _ => Err(format!("'{speed}' is not a positive number")),
+    }
+}
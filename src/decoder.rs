@@ -0,0 +1,419 @@
+use crate::addordermessages::AddOrder;
+use crate::enums::MessageTypes;
+use crate::modifyordermessages::{
+    OrderCancel, OrderDelete, OrderExecuted, OrderExecutedWithPrice, OrderReplace,
+};
+use crate::noiimessages::{NetOrderImbalanceIndicator, RetailPriceImprovementIndicator};
+use crate::stockmessages::{
+    DLCRPriceDiscovery, IPOQuotingPeriodUpdate, LULDAuctionCollar, MWCBDeclineLevel, MWCBStatus,
+    MarketParticipantPosition, OperationalHalt, RegSHOShortSalePriceTestRestriction,
+    StockDirectory, StockTradingAction,
+};
+use crate::systemmessages::SystemEventMessage;
+use crate::trademessages::{BrokenTrade, CrossingTrade, NonCrossingTrade};
+use crate::types::{
+    AltBinaryMessageLength, AltMessageHeaderType, BinaryMessageLength, MessageHeaderType, Parse,
+    ParseError,
+};
+use byteorder::{BigEndian, ByteOrder};
+use std::io::{BufRead, BufReader, Read};
+
+/// Size of the big endian length prefix in front of every message in an ITCH file.
+pub const LENGTH_PREFIX_SIZE: usize = 2;
+
+/// Parses a message body of exactly `T::LENGTH` bytes.
+fn parse_body<T>(body: &[u8]) -> Result<T, ParseError>
+where
+    T: Parse + BinaryMessageLength,
+{
+    if body.len() != T::LENGTH {
+        return Err(ParseError::IncompleteMessage {
+            expected: T::LENGTH,
+        });
+    }
+    T::parse(body)
+}
+
+/// The body length (excluding the message type byte) of a known message type.
+///
+/// # Arguments
+/// * `message_type` - The message type byte.
+///
+/// # Returns
+/// * `Some(usize)` - The body length of the message type.
+/// * `None` - If the message type is unknown.
+pub fn message_length(message_type: u8) -> Option<usize> {
+    let length = match message_type {
+        SystemEventMessage::MESSAGE_TYPE => SystemEventMessage::LENGTH,
+        StockDirectory::MESSAGE_TYPE => StockDirectory::LENGTH,
+        StockTradingAction::MESSAGE_TYPE => StockTradingAction::LENGTH,
+        RegSHOShortSalePriceTestRestriction::MESSAGE_TYPE => {
+            RegSHOShortSalePriceTestRestriction::LENGTH
+        }
+        MarketParticipantPosition::MESSAGE_TYPE => MarketParticipantPosition::LENGTH,
+        MWCBDeclineLevel::MESSAGE_TYPE => MWCBDeclineLevel::LENGTH,
+        MWCBStatus::MESSAGE_TYPE => MWCBStatus::LENGTH,
+        IPOQuotingPeriodUpdate::MESSAGE_TYPE => IPOQuotingPeriodUpdate::LENGTH,
+        LULDAuctionCollar::MESSAGE_TYPE => LULDAuctionCollar::LENGTH,
+        OperationalHalt::MESSAGE_TYPE => OperationalHalt::LENGTH,
+        DLCRPriceDiscovery::MESSAGE_TYPE => DLCRPriceDiscovery::LENGTH,
+        AddOrder::MESSAGE_TYPE => AddOrder::LENGTH,
+        AddOrder::ALT_MESSAGE_TYPE => AddOrder::ALT_LENGTH,
+        OrderExecuted::MESSAGE_TYPE => OrderExecuted::LENGTH,
+        OrderExecutedWithPrice::MESSAGE_TYPE => OrderExecutedWithPrice::LENGTH,
+        OrderCancel::MESSAGE_TYPE => OrderCancel::LENGTH,
+        OrderDelete::MESSAGE_TYPE => OrderDelete::LENGTH,
+        OrderReplace::MESSAGE_TYPE => OrderReplace::LENGTH,
+        NonCrossingTrade::MESSAGE_TYPE => NonCrossingTrade::LENGTH,
+        CrossingTrade::MESSAGE_TYPE => CrossingTrade::LENGTH,
+        BrokenTrade::MESSAGE_TYPE => BrokenTrade::LENGTH,
+        NetOrderImbalanceIndicator::MESSAGE_TYPE => NetOrderImbalanceIndicator::LENGTH,
+        RetailPriceImprovementIndicator::MESSAGE_TYPE => RetailPriceImprovementIndicator::LENGTH,
+        _ => return None,
+    };
+    Some(length)
+}
+
+/// Checks whether a frame header is valid, i.e. its message type is known and its length matches that type.
+/// Used to find the next frame once corrupt framing is detected, see `is_plausible_frame_header`.
+///
+/// # Arguments
+/// * `length` - The length prefix of the frame, including the message type byte.
+/// * `message_type` - The message type byte.
+pub fn is_valid_frame_header(length: u16, message_type: u8) -> bool {
+    message_length(message_type).is_some_and(|expected| usize::from(length) == expected + 1)
+}
+
+/// Checks whether a frame header is plausible, i.e. its length is not zero and, if its message type is known, matches
+/// that type. Frames of unknown message types are trusted and skipped by their length prefix, so only an implausible
+/// header is treated as corrupt framing.
+///
+/// # Arguments
+/// * `length` - The length prefix of the frame, including the message type byte.
+/// * `message_type` - The message type byte.
+pub fn is_plausible_frame_header(length: u16, message_type: u8) -> bool {
+    length != 0
+        && message_length(message_type).is_none_or(|expected| usize::from(length) == expected + 1)
+}
+
+/// Parses a single message into `MessageTypes`.
+///
+/// # Arguments
+/// * `message_type` - The message type byte.
+/// * `body` - The bytes following the message type byte, i.e. `length - 1` bytes of the frame.
+///
+/// # Returns
+/// * `Ok(MessageTypes)` - The parsed message.
+/// * `Err(ParseError)` - If the message type is unknown or the body is not parseable.
+pub fn parse_message(message_type: u8, body: &[u8]) -> Result<MessageTypes, ParseError> {
+    let message = match message_type {
+        SystemEventMessage::MESSAGE_TYPE => MessageTypes::SystemEvent(parse_body(body)?),
+        StockDirectory::MESSAGE_TYPE => MessageTypes::StockDirectory(parse_body(body)?),
+        StockTradingAction::MESSAGE_TYPE => MessageTypes::StockTradingAction(parse_body(body)?),
+        RegSHOShortSalePriceTestRestriction::MESSAGE_TYPE => {
+            MessageTypes::RegSHO(parse_body(body)?)
+        }
+        MarketParticipantPosition::MESSAGE_TYPE => {
+            MessageTypes::MarketParticipantPosition(parse_body(body)?)
+        }
+        MWCBDeclineLevel::MESSAGE_TYPE => MessageTypes::MWCBDeclineLevel(parse_body(body)?),
+        MWCBStatus::MESSAGE_TYPE => MessageTypes::MWCBStatus(parse_body(body)?),
+        IPOQuotingPeriodUpdate::MESSAGE_TYPE => {
+            MessageTypes::IPOQuotingPeriodUpdate(parse_body(body)?)
+        }
+        LULDAuctionCollar::MESSAGE_TYPE => MessageTypes::LULDAuctionCollar(parse_body(body)?),
+        OperationalHalt::MESSAGE_TYPE => MessageTypes::OperationalHalt(parse_body(body)?),
+        DLCRPriceDiscovery::MESSAGE_TYPE => MessageTypes::DLCRPriceDiscovery(parse_body(body)?),
+        AddOrder::MESSAGE_TYPE => MessageTypes::AddOrder(parse_body(body)?),
+        AddOrder::ALT_MESSAGE_TYPE => {
+            if body.len() != AddOrder::ALT_LENGTH {
+                return Err(ParseError::IncompleteMessage {
+                    expected: AddOrder::ALT_LENGTH,
+                });
+            }
+            MessageTypes::AddOrder(AddOrder::parse(body)?)
+        }
+        OrderExecuted::MESSAGE_TYPE => MessageTypes::OrderExecuted(parse_body(body)?),
+        OrderExecutedWithPrice::MESSAGE_TYPE => {
+            MessageTypes::OrderExecutedWithPrice(parse_body(body)?)
+        }
+        OrderCancel::MESSAGE_TYPE => MessageTypes::OrderCancel(parse_body(body)?),
+        OrderDelete::MESSAGE_TYPE => MessageTypes::OrderDelete(parse_body(body)?),
+        OrderReplace::MESSAGE_TYPE => MessageTypes::OrderReplace(parse_body(body)?),
+        NonCrossingTrade::MESSAGE_TYPE => MessageTypes::NonCrossingTrade(parse_body(body)?),
+        CrossingTrade::MESSAGE_TYPE => MessageTypes::CrossingTrade(parse_body(body)?),
+        BrokenTrade::MESSAGE_TYPE => MessageTypes::BrokenTrade(parse_body(body)?),
+        NetOrderImbalanceIndicator::MESSAGE_TYPE => MessageTypes::NOII(parse_body(body)?),
+        RetailPriceImprovementIndicator::MESSAGE_TYPE => MessageTypes::RPII(parse_body(body)?),
+        _ => return Err(ParseError::UnknownMessageType { message_type }),
+    };
+    Ok(message)
+}
+
+/// Streaming decoder over any `Read`, e.g. a `File` or a socket.
+/// Reads are buffered internally, so there is no need to wrap the reader in a `BufReader`.
+///
+/// Each item is a parsed message, or the error for that message. Framing is driven by the length prefix,
+/// so an unknown or unparseable message does not affect the messages after it.
+pub struct Decoder<R: Read> {
+    reader: BufReader<R>,
+    body: Vec<u8>,
+}
+
+impl<R: Read> Decoder<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader: BufReader::new(reader),
+            body: Vec::with_capacity(u16::MAX as usize),
+        }
+    }
+
+    /// Read the next frame, leaving its body in `self.body`.
+    ///
+    /// # Returns
+    /// * `Ok(Some(u8))` - The message type of the frame.
+    /// * `Ok(None)` - If the reader is exhausted.
+    /// * `Err(ParseError)` - If the frame could not be read.
+    fn read_frame(&mut self) -> Result<Option<u8>, ParseError> {
+        if self.reader.fill_buf()?.is_empty() {
+            return Ok(None);
+        }
+
+        let mut length_prefix = [0u8; LENGTH_PREFIX_SIZE];
+        self.reader.read_exact(&mut length_prefix)?;
+        let length = BigEndian::read_u16(&length_prefix);
+        if length == 0 {
+            return Err(ParseError::InvalidMessageLength { length });
+        }
+
+        let mut message_type = [0u8; 1];
+        self.reader.read_exact(&mut message_type)?;
+        self.body.resize(length as usize - 1, 0);
+        self.reader.read_exact(&mut self.body)?;
+        Ok(Some(message_type[0]))
+    }
+}
+
+impl<R: Read> Iterator for Decoder<R> {
+    type Item = Result<MessageTypes, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.read_frame() {
+            Ok(Some(message_type)) => Some(parse_message(message_type, &self.body)),
+            Ok(None) => None,
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+/// Decoder over ITCH bytes that are already in memory. Frames are parsed in place, without copying.
+pub struct SliceDecoder<'a> {
+    input: &'a [u8],
+    position: usize,
+}
+
+impl<'a> SliceDecoder<'a> {
+    pub fn new(input: &'a [u8]) -> Self {
+        Self { input, position: 0 }
+    }
+
+    /// Byte offset of the next frame.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Used to resynchronise on corrupt input. Does nothing if the frame at the current position is plausible, see
+    /// `is_plausible_frame_header`, and ends within the input, so frames of unknown message types are kept. Otherwise
+    /// skips forward to the next valid frame header, see `is_valid_frame_header`, whose frame ends within the input.
+    ///
+    /// # Returns
+    /// * `usize` - The number of bytes skipped.
+    pub fn resync(&mut self) -> usize {
+        if self.frame_fits(is_plausible_frame_header) {
+            return 0;
+        }
+        let start = self.position;
+        while self.position + LENGTH_PREFIX_SIZE < self.input.len()
+            && !self.frame_fits(is_valid_frame_header)
+        {
+            self.position += 1;
+        }
+        self.position - start
+    }
+
+    /// Checks the frame header at the current position, and that the frame ends within the input.
+    fn frame_fits(&self, check: fn(u16, u8) -> bool) -> bool {
+        let Some(header) = self
+            .input
+            .get(self.position..self.position + LENGTH_PREFIX_SIZE + 1)
+        else {
+            return false;
+        };
+        let length = BigEndian::read_u16(header);
+        check(length, header[LENGTH_PREFIX_SIZE])
+            && self.position + LENGTH_PREFIX_SIZE + usize::from(length) <= self.input.len()
+    }
+
+    /// Return the next frame without parsing it.
+    ///
+    /// # Returns
+    /// * `Some(Ok((u8, &[u8])))` - The message type and body of the frame.
+    /// * `Some(Err(ParseError))` - If the frame is truncated or has a zero length. A truncated frame ends decoding.
+    /// * `None` - If the input is exhausted.
+    pub fn next_frame(&mut self) -> Option<Result<(u8, &'a [u8]), ParseError>> {
+        let remaining = &self.input[self.position..];
+        if remaining.is_empty() {
+            return None;
+        }
+        if remaining.len() < LENGTH_PREFIX_SIZE {
+            self.position = self.input.len();
+            return Some(Err(ParseError::IncompleteMessage {
+                expected: LENGTH_PREFIX_SIZE,
+            }));
+        }
+
+        let length = BigEndian::read_u16(remaining) as usize;
+        if length == 0 {
+            self.position += LENGTH_PREFIX_SIZE;
+            return Some(Err(ParseError::InvalidMessageLength { length: 0 }));
+        }
+        if remaining.len() < LENGTH_PREFIX_SIZE + length {
+            self.position = self.input.len();
+            return Some(Err(ParseError::IncompleteMessage {
+                expected: LENGTH_PREFIX_SIZE + length,
+            }));
+        }
+
+        self.position += LENGTH_PREFIX_SIZE + length;
+        let message_type = remaining[LENGTH_PREFIX_SIZE];
+        let body = &remaining[LENGTH_PREFIX_SIZE + 1..LENGTH_PREFIX_SIZE + length];
+        Some(Ok((message_type, body)))
+    }
+}
+
+impl Iterator for SliceDecoder<'_> {
+    type Item = Result<MessageTypes, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let frame = self.next_frame()?;
+        Some(frame.and_then(|(message_type, body)| parse_message(message_type, body)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::GenerateExampleMessage;
+
+    fn frame(message_type: u8, body: &[u8]) -> Vec<u8> {
+        let mut frame = ((body.len() + 1) as u16).to_be_bytes().to_vec();
+        frame.push(message_type);
+        frame.extend_from_slice(body);
+        frame
+    }
+
+    fn example_stream() -> Vec<u8> {
+        let mut stream = Vec::new();
+        stream.extend(frame(
+            SystemEventMessage::MESSAGE_TYPE,
+            &SystemEventMessage::generate_binary_example(),
+        ));
+        stream.extend(frame(
+            AddOrder::MESSAGE_TYPE,
+            &AddOrder::generate_binary_example(),
+        ));
+        stream.extend(frame(b'z', &[0u8; 40])); // Unknown message type
+        stream.extend(frame(
+            OrderExecuted::MESSAGE_TYPE,
+            &OrderExecuted::generate_binary_example(),
+        ));
+        stream.extend(frame(
+            OrderDelete::MESSAGE_TYPE,
+            &OrderDelete::generate_binary_example(),
+        ));
+        stream
+    }
+
+    fn assert_example_messages(messages: Vec<Result<MessageTypes, ParseError>>) {
+        assert_eq!(messages.len(), 5);
+        assert!(matches!(messages[0], Ok(MessageTypes::SystemEvent(_))));
+        assert!(matches!(messages[1], Ok(MessageTypes::AddOrder(_))));
+        assert!(matches!(
+            messages[2],
+            Err(ParseError::UnknownMessageType { message_type: b'z' })
+        ));
+        assert!(matches!(messages[3], Ok(MessageTypes::OrderExecuted(_))));
+        assert!(matches!(messages[4], Ok(MessageTypes::OrderDelete(_))));
+    }
+
+    #[test]
+    fn test_decoder_reader() {
+        let stream = example_stream();
+        assert_example_messages(Decoder::new(stream.as_slice()).collect());
+    }
+
+    #[test]
+    fn test_decoder_slice() {
+        let stream = example_stream();
+        let mut decoder = SliceDecoder::new(&stream);
+        assert_example_messages(decoder.by_ref().collect());
+        assert_eq!(decoder.position(), stream.len());
+    }
+
+    #[test]
+    fn test_decoder_truncated_message() {
+        let mut stream = example_stream();
+        stream.truncate(stream.len() - 5);
+
+        let messages: Vec<_> = SliceDecoder::new(&stream).collect();
+        assert_eq!(messages.len(), 5);
+        assert!(matches!(
+            messages[4],
+            Err(ParseError::IncompleteMessage { .. })
+        ));
+
+        let messages: Vec<_> = Decoder::new(stream.as_slice()).collect();
+        assert_eq!(messages.len(), 5);
+        assert!(matches!(messages[4], Err(ParseError::Io(_))));
+    }
+
+    #[test]
+    fn test_decoder_resync() {
+        let mut stream = vec![0xde, 0xad, 0xbe, 0xef, 0x00];
+        stream.extend(example_stream());
+
+        let mut decoder = SliceDecoder::new(&stream);
+        assert_eq!(decoder.resync(), 5);
+        assert_eq!(decoder.resync(), 0);
+        assert_example_messages(decoder.collect());
+    }
+
+    #[test]
+    fn test_decoder_resync_trusts_unknown_message_types() {
+        let stream = example_stream();
+        let mut decoder = SliceDecoder::new(&stream);
+        let mut messages = Vec::new();
+        while decoder.position() < stream.len() {
+            assert_eq!(decoder.resync(), 0);
+            messages.push(decoder.next().unwrap());
+        }
+        assert_example_messages(messages);
+
+        // A known message type with the wrong length, or a frame that runs past the end of the input, is corrupt
+        assert!(is_plausible_frame_header(41, b'z'));
+        assert!(!is_plausible_frame_header(5, AddOrder::MESSAGE_TYPE));
+        assert!(!is_plausible_frame_header(0, b'z'));
+        let mut stream = frame(b'z', &[0u8; 40]);
+        stream.truncate(20);
+        assert_eq!(SliceDecoder::new(&stream).resync(), stream.len() - 2);
+    }
+
+    #[test]
+    fn test_parse_message_wrong_length() {
+        let body = AddOrder::generate_binary_example();
+        assert!(matches!(
+            parse_message(AddOrder::ALT_MESSAGE_TYPE, &body),
+            Err(ParseError::IncompleteMessage { expected: 39 })
+        ));
+    }
+}
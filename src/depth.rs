@@ -0,0 +1,307 @@
+use crate::enums::MessageTypes;
+use crate::events::{Event, EventSink};
+use crate::helpers::stock_to_str;
+use crate::orderbook::{order_message_header, LimitOrderBook, OrderBookManager, StockLocateCode};
+use crate::types::Stock;
+use std::collections::{HashMap, VecDeque};
+use std::io::{self, Write};
+use std::sync::{Arc, RwLock};
+
+/// When a `DepthWriter` takes snapshots.
+///
+/// # Variants
+/// * `Interval` - Snapshot every book at a fixed interval of exchange time, in nanoseconds. Each snapshot is stamped
+///   with an interval boundary and holds the books as they stood at it. Intervals without order messages are skipped.
+/// * `Updates` - Snapshot a book after every N order messages that update it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapshotSchedule {
+    Interval(u64),
+    Updates(u64),
+}
+
+/// Writes market by price depth snapshots as CSV, one row per book per snapshot.
+/// Each row has the price, shares and order count of the top `levels` levels of each side, best first.
+/// Missing levels have an empty price and no shares.
+/// Must be registered before the order book manager, so a snapshot can be taken before a message is applied.
+///
+/// # Fields
+/// * `writer` - Destination of the CSV rows.
+/// * `levels` - Price levels per side.
+/// * `schedule` - When snapshots are taken.
+/// * `symbols` - Stocks to write, empty for every stock.
+/// * `order_book_manager` - The books being replayed, set by `reset`.
+/// * `stocks` - Stock locate code > stock, from 'R' messages. Locate codes are only valid for a single day.
+/// * `next_snapshot` - The next interval boundary, 0 until the first order message. Only used by `SnapshotSchedule::Interval`.
+/// * `updates` - Stock locate code > order messages seen. Only used by `SnapshotSchedule::Updates`.
+/// * `pending` - The timestamp and stock locate code of a snapshot due once the book has applied the message that made
+///   it due. Only used by `SnapshotSchedule::Updates`.
+/// * `error` - The first write error, returned by `flush`. Nothing is written once set.
+pub struct DepthWriter<W: Write> {
+    writer: W,
+    levels: usize,
+    schedule: SnapshotSchedule,
+    symbols: Vec<Stock>,
+    order_book_manager: Arc<RwLock<OrderBookManager>>,
+    stocks: HashMap<StockLocateCode, Stock>,
+    next_snapshot: u64,
+    updates: HashMap<StockLocateCode, u64>,
+    pending: Option<(u64, StockLocateCode)>,
+    error: Option<io::Error>,
+}
+
+impl<W: Write> DepthWriter<W> {
+    /// Create a depth writer, writing the CSV header immediately.
+    /// Call `reset` with the order book manager being replayed before dispatching any messages.
+    ///
+    /// # Arguments
+    /// * `writer` - Destination of the CSV rows.
+    /// * `levels` - Price levels per side.
+    /// * `schedule` - When snapshots are taken.
+    /// * `symbols` - Stocks to write, empty for every stock.
+    pub fn new(
+        mut writer: W,
+        levels: usize,
+        schedule: SnapshotSchedule,
+        symbols: Vec<Stock>,
+    ) -> Result<Self, io::Error> {
+        write!(writer, "timestamp,stock,stock_locate")?;
+        for side in ["bid", "ask"] {
+            for level in 1..=levels {
+                write!(
+                    writer,
+                    ",{side}_price_{level},{side}_size_{level},{side}_orders_{level}"
+                )?;
+            }
+        }
+        writeln!(writer)?;
+        Ok(Self {
+            writer,
+            levels,
+            schedule,
+            symbols,
+            order_book_manager: Arc::default(),
+            stocks: HashMap::new(),
+            next_snapshot: 0,
+            updates: HashMap::new(),
+            pending: None,
+            error: None,
+        })
+    }
+
+    /// Start snapshotting the books of the next day.
+    ///
+    /// # Arguments
+    /// * `order_book_manager` - The order book manager the day is replayed into.
+    pub fn reset(&mut self, order_book_manager: Arc<RwLock<OrderBookManager>>) {
+        self.write_pending();
+        self.order_book_manager = order_book_manager;
+        self.stocks.clear();
+        self.next_snapshot = 0;
+        self.updates.clear();
+    }
+
+    /// Flush the writer.
+    ///
+    /// # Returns
+    /// * `Ok(())` - If every row was written.
+    /// * `Err(io::Error)` - The first error hit while writing or flushing.
+    pub fn flush(&mut self) -> Result<(), io::Error> {
+        self.write_pending();
+        match self.error.take() {
+            Some(e) => Err(e),
+            None => self.writer.flush(),
+        }
+    }
+
+    /// Write the pending snapshot, if any. The books must not have applied a message since it became due.
+    fn write_pending(&mut self) {
+        if let Some((timestamp, stock_locate)) = self.pending.take() {
+            self.write_snapshot(timestamp, Some(stock_locate));
+        }
+    }
+
+    /// Write a snapshot, keeping the first error.
+    fn write_snapshot(&mut self, timestamp: u64, stock_locate: Option<StockLocateCode>) {
+        if self.error.is_some() {
+            return;
+        }
+        if let Err(e) = self.snapshot(timestamp, stock_locate) {
+            log::error!("Failed to write depth snapshot: {e}");
+            self.error = Some(e);
+        }
+    }
+
+    /// Write a snapshot of a single book, or of every book if `stock_locate` is `None`.
+    fn snapshot(
+        &mut self,
+        timestamp: u64,
+        stock_locate: Option<StockLocateCode>,
+    ) -> Result<(), io::Error> {
+        let order_book_manager = Arc::clone(&self.order_book_manager);
+        let order_book_manager = order_book_manager.read().unwrap();
+        match stock_locate {
+            None => {
+                // Every book, in stock locate order so snapshots are comparable
+                let mut stock_locates: Vec<_> =
+                    order_book_manager.order_books.keys().copied().collect();
+                stock_locates.sort_unstable();
+                for stock_locate in stock_locates {
+                    let book = &order_book_manager.order_books[&stock_locate];
+                    self.write_row(timestamp, stock_locate, book)?;
+                }
+            }
+            Some(stock_locate) => {
+                if let Some(book) = order_book_manager.order_books.get(&stock_locate) {
+                    self.write_row(timestamp, stock_locate, book)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn write_row(
+        &mut self,
+        timestamp: u64,
+        stock_locate: StockLocateCode,
+        book: &LimitOrderBook,
+    ) -> Result<(), io::Error> {
+        let Some(stock) = self.stocks.get(&stock_locate) else {
+            return Ok(());
+        };
+        if !self.symbols.is_empty() && !self.symbols.contains(stock) {
+            return Ok(());
+        }
+        write!(
+            self.writer,
+            "{},{},{}",
+            timestamp,
+            stock_to_str(stock),
+            stock_locate
+        )?;
+        let depth = book.get_depth(self.levels);
+        for side in [&depth.bids, &depth.asks] {
+            for level in 0..self.levels {
+                match side.get(level) {
+                    Some(level) => write!(
+                        self.writer,
+                        ",{},{},{}",
+                        level.price, level.share_quantity, level.order_count
+                    )?,
+                    None => write!(self.writer, ",,0,0")?,
+                }
+            }
+        }
+        writeln!(self.writer)
+    }
+}
+
+impl<W: Write> EventSink for DepthWriter<W> {
+    fn on_message(&mut self, message: &MessageTypes, _events: &mut VecDeque<Event>) {
+        if let MessageTypes::StockDirectory(directory) = message {
+            self.stocks
+                .insert(directory.header.stock_locate, directory.stock);
+            return;
+        }
+        let Some(header) = order_message_header(message) else {
+            return;
+        };
+
+        // The book applies this message after us, so it still stands as it did after the last one
+        self.write_pending();
+        match self.schedule {
+            SnapshotSchedule::Interval(interval) => {
+                let boundary = self.next_snapshot;
+                let due = boundary != 0 && header.timestamp >= boundary;
+                if boundary == 0 || due {
+                    // Quiet intervals are skipped, rather than repeating the same snapshot
+                    self.next_snapshot = (header.timestamp / interval + 1) * interval;
+                }
+                if due {
+                    self.write_snapshot(boundary, None);
+                }
+            }
+            SnapshotSchedule::Updates(updates) => {
+                let count = self.updates.entry(header.stock_locate).or_default();
+                *count += 1;
+                if (*count).is_multiple_of(updates) {
+                    self.pending = Some((header.timestamp, header.stock_locate));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::addordermessages::AddOrder;
+    use crate::enums::BuySellIndicator;
+    use crate::events::Dispatcher;
+    use crate::stockmessages::StockDirectory;
+
+    fn add_order(
+        timestamp: u64,
+        order_reference_number: u64,
+        buy_sell_indicator: BuySellIndicator,
+        price: u32,
+    ) -> MessageTypes {
+        MessageTypes::AddOrder(AddOrder::example(
+            1,
+            timestamp,
+            order_reference_number,
+            buy_sell_indicator,
+            price,
+        ))
+    }
+
+    /// Replays a stock directory message and four orders, returning the CSV written.
+    fn replay(schedule: SnapshotSchedule) -> String {
+        let directory = StockDirectory::example(1, *b"AAPL    ");
+
+        let order_book_manager = Arc::new(RwLock::new(OrderBookManager::new()));
+        let depth_writer = Arc::new(RwLock::new(
+            DepthWriter::new(Vec::new(), 2, schedule, Vec::new()).unwrap(),
+        ));
+        depth_writer
+            .write()
+            .unwrap()
+            .reset(Arc::clone(&order_book_manager));
+
+        let mut dispatcher = Dispatcher::new();
+        dispatcher.add_sink(Arc::clone(&depth_writer));
+        dispatcher.add_sink(Arc::clone(&order_book_manager));
+        dispatcher.dispatch(&MessageTypes::StockDirectory(directory));
+        dispatcher.dispatch(&add_order(100, 1, BuySellIndicator::Buy, 150_0000));
+        dispatcher.dispatch(&add_order(900, 2, BuySellIndicator::Buy, 149_0000));
+        dispatcher.dispatch(&add_order(1500, 3, BuySellIndicator::Sell, 151_0000));
+        dispatcher.dispatch(&add_order(3100, 4, BuySellIndicator::Buy, 148_0000));
+
+        let mut depth_writer = depth_writer.write().unwrap();
+        depth_writer.flush().unwrap();
+        String::from_utf8(depth_writer.writer.clone()).unwrap()
+    }
+
+    #[test]
+    fn test_depth_writer_interval() {
+        assert_eq!(
+            replay(SnapshotSchedule::Interval(1000)),
+            "timestamp,stock,stock_locate,bid_price_1,bid_size_1,bid_orders_1,bid_price_2,bid_size_2,bid_orders_2,\
+             ask_price_1,ask_size_1,ask_orders_1,ask_price_2,ask_size_2,ask_orders_2\n\
+             1000,AAPL,1,150.0000,100,1,149.0000,100,1,,0,0,,0,0\n\
+             2000,AAPL,1,150.0000,100,1,149.0000,100,1,151.0000,100,1,,0,0\n"
+        );
+    }
+
+    #[test]
+    fn test_depth_writer_updates() {
+        let csv = replay(SnapshotSchedule::Updates(2));
+        let rows: Vec<_> = csv.lines().skip(1).collect();
+        assert_eq!(
+            rows,
+            [
+                "900,AAPL,1,150.0000,100,1,149.0000,100,1,,0,0,,0,0",
+                "3100,AAPL,1,150.0000,100,1,149.0000,100,1,151.0000,100,1,,0,0",
+            ]
+        );
+    }
+}
@@ -0,0 +1,210 @@
+use crate::decoder::LENGTH_PREFIX_SIZE;
+use crate::types::Encode;
+use std::io::{self, BufWriter, Write};
+
+/// Append a message to `output` as a frame of an ITCH file: the big endian length prefix, the message type byte
+/// and the body. The inverse of reading a single frame with a `decoder::Decoder`.
+///
+/// # Arguments
+/// * `message` - The message to frame, a `MessageTypes` or any single message struct.
+/// * `output` - Where the frame is appended.
+pub fn encode_frame<M: Encode + ?Sized>(message: &M, output: &mut Vec<u8>) {
+    let start = output.len();
+    output.extend_from_slice(&[0; LENGTH_PREFIX_SIZE]);
+    output.push(message.message_type());
+    message.encode(output);
+    let length = (output.len() - start - LENGTH_PREFIX_SIZE) as u16;
+    output[start..start + LENGTH_PREFIX_SIZE].copy_from_slice(&length.to_be_bytes());
+}
+
+/// Streaming encoder over any `Write`, e.g. a `File`, writing messages in the ITCH 5.0 file format.
+/// The inverse of `decoder::Decoder`, for building fixture files or filtered subsets of real files.
+///
+/// Writes are buffered internally, so call `flush` or `into_inner` once the last message is written.
+pub struct Encoder<W: Write> {
+    writer: BufWriter<W>,
+    frame: Vec<u8>,
+}
+
+impl<W: Write> Encoder<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer: BufWriter::new(writer),
+            frame: Vec::with_capacity(u16::MAX as usize),
+        }
+    }
+
+    /// Write a message as a length prefixed frame.
+    ///
+    /// # Arguments
+    /// * `message` - The message to write, a `MessageTypes` or any single message struct.
+    pub fn write<M: Encode + ?Sized>(&mut self, message: &M) -> Result<(), io::Error> {
+        self.frame.clear();
+        encode_frame(message, &mut self.frame);
+        self.writer.write_all(&self.frame)
+    }
+
+    pub fn flush(&mut self) -> Result<(), io::Error> {
+        self.writer.flush()
+    }
+
+    /// Flush the buffered frames and return the underlying writer.
+    pub fn into_inner(self) -> Result<W, io::Error> {
+        self.writer.into_inner().map_err(|e| e.into_error())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::addordermessages::AddOrder;
+    use crate::decoder::{parse_message, Decoder};
+    use crate::enums::MessageTypes;
+    use crate::modifyordermessages::{
+        OrderCancel, OrderDelete, OrderExecuted, OrderExecutedWithPrice, OrderReplace,
+    };
+    use crate::noiimessages::{NetOrderImbalanceIndicator, RetailPriceImprovementIndicator};
+    use crate::orderflow::OrderFlow;
+    use crate::stockmessages::{
+        DLCRPriceDiscovery, IPOQuotingPeriodUpdate, LULDAuctionCollar, MWCBDeclineLevel,
+        MWCBStatus, MarketParticipantPosition, OperationalHalt,
+        RegSHOShortSalePriceTestRestriction, StockDirectory, StockTradingAction,
+    };
+    use crate::systemmessages::SystemEventMessage;
+    use crate::trademessages::{BrokenTrade, CrossingTrade, NonCrossingTrade};
+    use crate::types::{
+        AltMessageHeaderType, BinaryMessageLength, GenerateExampleMessage, MessageHeaderType,
+    };
+
+    fn example_bodies() -> Vec<(u8, Vec<u8>)> {
+        let mut add_order_mpid = AddOrder::generate_binary_example().to_vec();
+        add_order_mpid.extend_from_slice(b"GSCO");
+        vec![
+            (
+                SystemEventMessage::MESSAGE_TYPE,
+                SystemEventMessage::generate_binary_example().to_vec(),
+            ),
+            (
+                StockDirectory::MESSAGE_TYPE,
+                StockDirectory::generate_binary_example().to_vec(),
+            ),
+            (
+                StockTradingAction::MESSAGE_TYPE,
+                StockTradingAction::generate_binary_example().to_vec(),
+            ),
+            (
+                RegSHOShortSalePriceTestRestriction::MESSAGE_TYPE,
+                RegSHOShortSalePriceTestRestriction::generate_binary_example().to_vec(),
+            ),
+            (
+                MarketParticipantPosition::MESSAGE_TYPE,
+                MarketParticipantPosition::generate_binary_example().to_vec(),
+            ),
+            (
+                MWCBDeclineLevel::MESSAGE_TYPE,
+                MWCBDeclineLevel::generate_binary_example().to_vec(),
+            ),
+            (
+                MWCBStatus::MESSAGE_TYPE,
+                MWCBStatus::generate_binary_example().to_vec(),
+            ),
+            (
+                IPOQuotingPeriodUpdate::MESSAGE_TYPE,
+                IPOQuotingPeriodUpdate::generate_binary_example().to_vec(),
+            ),
+            (
+                LULDAuctionCollar::MESSAGE_TYPE,
+                LULDAuctionCollar::generate_binary_example().to_vec(),
+            ),
+            (
+                OperationalHalt::MESSAGE_TYPE,
+                OperationalHalt::generate_binary_example().to_vec(),
+            ),
+            (
+                DLCRPriceDiscovery::MESSAGE_TYPE,
+                DLCRPriceDiscovery::generate_binary_example().to_vec(),
+            ),
+            (
+                AddOrder::MESSAGE_TYPE,
+                AddOrder::generate_binary_example().to_vec(),
+            ),
+            (AddOrder::ALT_MESSAGE_TYPE, add_order_mpid),
+            (
+                OrderExecuted::MESSAGE_TYPE,
+                OrderExecuted::generate_binary_example().to_vec(),
+            ),
+            (
+                OrderExecutedWithPrice::MESSAGE_TYPE,
+                OrderExecutedWithPrice::generate_binary_example().to_vec(),
+            ),
+            (
+                OrderCancel::MESSAGE_TYPE,
+                OrderCancel::generate_binary_example().to_vec(),
+            ),
+            (
+                OrderDelete::MESSAGE_TYPE,
+                OrderDelete::generate_binary_example().to_vec(),
+            ),
+            (
+                OrderReplace::MESSAGE_TYPE,
+                OrderReplace::generate_binary_example().to_vec(),
+            ),
+            (
+                NonCrossingTrade::MESSAGE_TYPE,
+                NonCrossingTrade::generate_binary_example().to_vec(),
+            ),
+            (
+                CrossingTrade::MESSAGE_TYPE,
+                CrossingTrade::generate_binary_example().to_vec(),
+            ),
+            (
+                BrokenTrade::MESSAGE_TYPE,
+                BrokenTrade::generate_binary_example().to_vec(),
+            ),
+            (
+                NetOrderImbalanceIndicator::MESSAGE_TYPE,
+                NetOrderImbalanceIndicator::generate_binary_example().to_vec(),
+            ),
+            (
+                RetailPriceImprovementIndicator::MESSAGE_TYPE,
+                RetailPriceImprovementIndicator::generate_binary_example().to_vec(),
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_encode_round_trip() {
+        for (message_type, body) in example_bodies() {
+            let message = parse_message(message_type, &body).unwrap();
+            let mut encoded = Vec::new();
+            message.encode(&mut encoded);
+            assert_eq!(message.message_type(), message_type);
+            assert_eq!(encoded, body, "message type {}", message_type as char);
+        }
+    }
+
+    #[test]
+    fn test_encode_frame() {
+        let body = OrderDelete::generate_binary_example();
+        let message = parse_message(OrderDelete::MESSAGE_TYPE, &body).unwrap();
+        let mut frame = vec![0xFF];
+        encode_frame(&message, &mut frame);
+        assert_eq!(frame[..4], [0xFF, 0, OrderDelete::LENGTH as u8 + 1, b'D']);
+        assert_eq!(frame[4..], body);
+    }
+
+    #[test]
+    fn test_encoder_decoder_round_trip() {
+        let messages: Vec<MessageTypes> = OrderFlow::new(7, 20, 5_000).collect();
+        let mut encoder = Encoder::new(Vec::new());
+        for message in &messages {
+            encoder.write(message).unwrap();
+        }
+        let file = encoder.into_inner().unwrap();
+
+        let decoded: Vec<MessageTypes> = Decoder::new(file.as_slice())
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(decoded, messages);
+    }
+}
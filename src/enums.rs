@@ -1,4 +1,5 @@
-use crate::types::ParseError;
+use crate::messageheader::MessageHeader;
+use crate::types::{Encode, ParseError};
 use crate::{
     addordermessages, modifyordermessages, noiimessages, stockmessages, systemmessages,
     trademessages,
@@ -13,6 +14,16 @@ pub enum BoolOrUnavailable {
     Str(&'static str),
 }
 
+impl From<&BoolOrUnavailable> for u8 {
+    fn from(value: &BoolOrUnavailable) -> Self {
+        match value {
+            BoolOrUnavailable::Bool(true) => b'Y',
+            BoolOrUnavailable::Bool(false) => b'N',
+            BoolOrUnavailable::Str(_) => b' ',
+        }
+    }
+}
+
 #[derive(Debug, PartialEq)]
 pub enum MessageTypes {
     SystemEvent(systemmessages::SystemEventMessage),
@@ -23,6 +34,9 @@ pub enum MessageTypes {
     MWCBDeclineLevel(stockmessages::MWCBDeclineLevel),
     MWCBStatus(stockmessages::MWCBStatus),
     IPOQuotingPeriodUpdate(stockmessages::IPOQuotingPeriodUpdate),
+    LULDAuctionCollar(stockmessages::LULDAuctionCollar),
+    OperationalHalt(stockmessages::OperationalHalt),
+    DLCRPriceDiscovery(stockmessages::DLCRPriceDiscovery),
     AddOrder(addordermessages::AddOrder),
     // AddOrderMPID(addordermessages::AddOrderMPID),
     OrderExecuted(modifyordermessages::OrderExecuted),
@@ -34,9 +48,96 @@ pub enum MessageTypes {
     CrossingTrade(trademessages::CrossingTrade),
     BrokenTrade(trademessages::BrokenTrade),
     NOII(noiimessages::NetOrderImbalanceIndicator),
+    RPII(noiimessages::RetailPriceImprovementIndicator),
 }
 
-#[derive(Debug, PartialEq)]
+impl MessageTypes {
+    /// The header of the message, for the timestamp and stock locate code of any message type.
+    pub fn header(&self) -> &MessageHeader {
+        match self {
+            MessageTypes::SystemEvent(message) => &message.header,
+            MessageTypes::StockDirectory(message) => &message.header,
+            MessageTypes::StockTradingAction(message) => &message.header,
+            MessageTypes::RegSHO(message) => &message.header,
+            MessageTypes::MarketParticipantPosition(message) => &message.header,
+            MessageTypes::MWCBDeclineLevel(message) => &message.header,
+            MessageTypes::MWCBStatus(message) => &message.header,
+            MessageTypes::IPOQuotingPeriodUpdate(message) => &message.header,
+            MessageTypes::LULDAuctionCollar(message) => &message.header,
+            MessageTypes::OperationalHalt(message) => &message.header,
+            MessageTypes::DLCRPriceDiscovery(message) => &message.header,
+            MessageTypes::AddOrder(message) => &message.header,
+            MessageTypes::OrderExecuted(message) => &message.header,
+            MessageTypes::OrderExecutedWithPrice(message) => &message.order_executed_message.header,
+            MessageTypes::OrderCancel(message) => &message.header,
+            MessageTypes::OrderDelete(message) => &message.header,
+            MessageTypes::OrderReplace(message) => &message.header,
+            MessageTypes::NonCrossingTrade(message) => &message.header,
+            MessageTypes::CrossingTrade(message) => &message.header,
+            MessageTypes::BrokenTrade(message) => &message.header,
+            MessageTypes::NOII(message) => &message.header,
+            MessageTypes::RPII(message) => &message.header,
+        }
+    }
+}
+
+impl Encode for MessageTypes {
+    fn message_type(&self) -> u8 {
+        match self {
+            MessageTypes::SystemEvent(message) => message.message_type(),
+            MessageTypes::StockDirectory(message) => message.message_type(),
+            MessageTypes::StockTradingAction(message) => message.message_type(),
+            MessageTypes::RegSHO(message) => message.message_type(),
+            MessageTypes::MarketParticipantPosition(message) => message.message_type(),
+            MessageTypes::MWCBDeclineLevel(message) => message.message_type(),
+            MessageTypes::MWCBStatus(message) => message.message_type(),
+            MessageTypes::IPOQuotingPeriodUpdate(message) => message.message_type(),
+            MessageTypes::LULDAuctionCollar(message) => message.message_type(),
+            MessageTypes::OperationalHalt(message) => message.message_type(),
+            MessageTypes::DLCRPriceDiscovery(message) => message.message_type(),
+            MessageTypes::AddOrder(message) => message.message_type(),
+            MessageTypes::OrderExecuted(message) => message.message_type(),
+            MessageTypes::OrderExecutedWithPrice(message) => message.message_type(),
+            MessageTypes::OrderCancel(message) => message.message_type(),
+            MessageTypes::OrderDelete(message) => message.message_type(),
+            MessageTypes::OrderReplace(message) => message.message_type(),
+            MessageTypes::NonCrossingTrade(message) => message.message_type(),
+            MessageTypes::CrossingTrade(message) => message.message_type(),
+            MessageTypes::BrokenTrade(message) => message.message_type(),
+            MessageTypes::NOII(message) => message.message_type(),
+            MessageTypes::RPII(message) => message.message_type(),
+        }
+    }
+
+    fn encode(&self, output: &mut Vec<u8>) {
+        match self {
+            MessageTypes::SystemEvent(message) => message.encode(output),
+            MessageTypes::StockDirectory(message) => message.encode(output),
+            MessageTypes::StockTradingAction(message) => message.encode(output),
+            MessageTypes::RegSHO(message) => message.encode(output),
+            MessageTypes::MarketParticipantPosition(message) => message.encode(output),
+            MessageTypes::MWCBDeclineLevel(message) => message.encode(output),
+            MessageTypes::MWCBStatus(message) => message.encode(output),
+            MessageTypes::IPOQuotingPeriodUpdate(message) => message.encode(output),
+            MessageTypes::LULDAuctionCollar(message) => message.encode(output),
+            MessageTypes::OperationalHalt(message) => message.encode(output),
+            MessageTypes::DLCRPriceDiscovery(message) => message.encode(output),
+            MessageTypes::AddOrder(message) => message.encode(output),
+            MessageTypes::OrderExecuted(message) => message.encode(output),
+            MessageTypes::OrderExecutedWithPrice(message) => message.encode(output),
+            MessageTypes::OrderCancel(message) => message.encode(output),
+            MessageTypes::OrderDelete(message) => message.encode(output),
+            MessageTypes::OrderReplace(message) => message.encode(output),
+            MessageTypes::NonCrossingTrade(message) => message.encode(output),
+            MessageTypes::CrossingTrade(message) => message.encode(output),
+            MessageTypes::BrokenTrade(message) => message.encode(output),
+            MessageTypes::NOII(message) => message.encode(output),
+            MessageTypes::RPII(message) => message.encode(output),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub enum SystemEventCode {
     StartOfMessages,
     StartOfSystemHours,
@@ -64,9 +165,22 @@ impl TryFrom<u8> for SystemEventCode {
     }
 }
 
+impl From<&SystemEventCode> for u8 {
+    fn from(value: &SystemEventCode) -> Self {
+        match value {
+            SystemEventCode::StartOfMessages => b'O',
+            SystemEventCode::StartOfSystemHours => b'S',
+            SystemEventCode::StartOfMarketHours => b'Q',
+            SystemEventCode::EndOfMarketHours => b'M',
+            SystemEventCode::EndOfSystemHours => b'E',
+            SystemEventCode::EndOfMessages => b'C',
+        }
+    }
+}
+
 #[cfg(any(test, feature = "bench"))]
 impl EnumTestHelpers<6> for SystemEventCode {
-    const VALID_CODES: [u8; 6] = [b'O', b'S', b'Q', b'M', b'E', b'C'];
+    const VALID_CODES: [u8; 6] = *b"OSQMEC";
 
     fn generate_example_code() -> u8 {
         let i = fastrand::usize(..Self::VALID_CODES.len());
@@ -108,9 +222,25 @@ impl TryFrom<u8> for MarketCategory {
     }
 }
 
+impl From<&MarketCategory> for u8 {
+    fn from(value: &MarketCategory) -> Self {
+        match value {
+            MarketCategory::NASDAQGlobalSelectMarket => b'Q',
+            MarketCategory::NASDAQGlobalMarket => b'G',
+            MarketCategory::NASDAQCapitalMarket => b'S',
+            MarketCategory::NYSE => b'N',
+            MarketCategory::NYSEArca => b'A',
+            MarketCategory::NYSEMKT => b'P',
+            MarketCategory::InvestorsExchange => b'V',
+            MarketCategory::BATS => b'Z',
+            MarketCategory::Unavailable => b' ',
+        }
+    }
+}
+
 #[cfg(any(test, feature = "bench"))]
 impl EnumTestHelpers<8> for MarketCategory {
-    const VALID_CODES: [u8; 8] = [b'Q', b'G', b'S', b'N', b'A', b'P', b'Z', b' '];
+    const VALID_CODES: [u8; 8] = *b"QGSNAPZ ";
 
     fn generate_example_code() -> u8 {
         let i = fastrand::usize(..Self::VALID_CODES.len());
@@ -138,9 +268,18 @@ impl TryFrom<u8> for Authenticity {
     }
 }
 
+impl From<&Authenticity> for u8 {
+    fn from(value: &Authenticity) -> Self {
+        match value {
+            Authenticity::Production => b'P',
+            Authenticity::Test => b'T',
+        }
+    }
+}
+
 #[cfg(any(test, feature = "bench"))]
 impl EnumTestHelpers<2> for Authenticity {
-    const VALID_CODES: [u8; 2] = [b'P', b'T'];
+    const VALID_CODES: [u8; 2] = *b"PT";
 
     fn generate_example_code() -> u8 {
         let i = fastrand::usize(..Self::VALID_CODES.len());
@@ -170,9 +309,19 @@ impl TryFrom<u8> for ShortSaleThresholdIndicator {
     }
 }
 
+impl From<&ShortSaleThresholdIndicator> for u8 {
+    fn from(value: &ShortSaleThresholdIndicator) -> Self {
+        match value {
+            ShortSaleThresholdIndicator::Restricted => b'Y',
+            ShortSaleThresholdIndicator::NotRestricted => b'N',
+            ShortSaleThresholdIndicator::NotAvailable => b' ',
+        }
+    }
+}
+
 #[cfg(any(test, feature = "bench"))]
 impl EnumTestHelpers<3> for ShortSaleThresholdIndicator {
-    const VALID_CODES: [u8; 3] = [b'Y', b'N', b' '];
+    const VALID_CODES: [u8; 3] = *b"YN ";
 
     fn generate_example_code() -> u8 {
         let i = fastrand::usize(..Self::VALID_CODES.len());
@@ -202,9 +351,19 @@ impl TryFrom<u8> for LuldReferencePriceTier {
     }
 }
 
+impl From<&LuldReferencePriceTier> for u8 {
+    fn from(value: &LuldReferencePriceTier) -> Self {
+        match value {
+            LuldReferencePriceTier::Tier1 => b'1',
+            LuldReferencePriceTier::Tier2 => b'2',
+            LuldReferencePriceTier::NotAvailable => b' ',
+        }
+    }
+}
+
 #[cfg(any(test, feature = "bench"))]
 impl EnumTestHelpers<3> for LuldReferencePriceTier {
-    const VALID_CODES: [u8; 3] = [b'1', b'2', b' '];
+    const VALID_CODES: [u8; 3] = *b"12 ";
 
     fn generate_example_code() -> u8 {
         let i = fastrand::usize(..Self::VALID_CODES.len());
@@ -250,11 +409,27 @@ impl TryFrom<u8> for FinancialStatusIndicator {
     }
 }
 
+impl From<&FinancialStatusIndicator> for u8 {
+    fn from(value: &FinancialStatusIndicator) -> Self {
+        match value {
+            FinancialStatusIndicator::Deficient => b'D',
+            FinancialStatusIndicator::Delinquent => b'E',
+            FinancialStatusIndicator::Bankrupt => b'Q',
+            FinancialStatusIndicator::Suspended => b'S',
+            FinancialStatusIndicator::DeficientAndBankrupt => b'G',
+            FinancialStatusIndicator::DeficientAndDelinquent => b'H',
+            FinancialStatusIndicator::DelinquentAndBankrupt => b'J',
+            FinancialStatusIndicator::DeficientDelinquentAndBankrupt => b'K',
+            FinancialStatusIndicator::CreationsAndRedemptionsSuspended => b'C',
+            FinancialStatusIndicator::Normal => b'N',
+            FinancialStatusIndicator::NotAvailable => b' ',
+        }
+    }
+}
+
 #[cfg(any(test, feature = "bench"))]
 impl EnumTestHelpers<11> for FinancialStatusIndicator {
-    const VALID_CODES: [u8; 11] = [
-        b'D', b'E', b'Q', b'S', b'G', b'H', b'J', b'K', b'C', b'N', b' ',
-    ];
+    const VALID_CODES: [u8; 11] = *b"DEQSGHJKCN ";
 
     fn generate_example_code() -> u8 {
         let i = fastrand::usize(..Self::VALID_CODES.len());
@@ -262,7 +437,7 @@ impl EnumTestHelpers<11> for FinancialStatusIndicator {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub enum TradingState {
     Halted,
     Paused,
@@ -286,9 +461,20 @@ impl TryFrom<u8> for TradingState {
     }
 }
 
+impl From<&TradingState> for u8 {
+    fn from(value: &TradingState) -> Self {
+        match value {
+            TradingState::Halted => b'H',
+            TradingState::Paused => b'P',
+            TradingState::QuotationOnly => b'Q',
+            TradingState::Trading => b'T',
+        }
+    }
+}
+
 #[cfg(any(test, feature = "bench"))]
 impl EnumTestHelpers<4> for TradingState {
-    const VALID_CODES: [u8; 4] = [b'H', b'P', b'Q', b'T'];
+    const VALID_CODES: [u8; 4] = *b"HPQT";
 
     fn generate_example_code() -> u8 {
         let i = fastrand::usize(..Self::VALID_CODES.len());
@@ -296,7 +482,7 @@ impl EnumTestHelpers<4> for TradingState {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub enum RegSHOAction {
     NoPriceTestInEffect,
     RegSHOShortSalePriceTestRestriction,
@@ -318,9 +504,19 @@ impl TryFrom<u8> for RegSHOAction {
     }
 }
 
+impl From<&RegSHOAction> for u8 {
+    fn from(value: &RegSHOAction) -> Self {
+        match value {
+            RegSHOAction::NoPriceTestInEffect => b'0',
+            RegSHOAction::RegSHOShortSalePriceTestRestriction => b'1',
+            RegSHOAction::TestRestrictionRemains => b'2',
+        }
+    }
+}
+
 #[cfg(any(test, feature = "bench"))]
 impl EnumTestHelpers<3> for RegSHOAction {
-    const VALID_CODES: [u8; 3] = [b'0', b'1', b'2'];
+    const VALID_CODES: [u8; 3] = *b"012";
 
     fn generate_example_code() -> u8 {
         let i = fastrand::usize(..Self::VALID_CODES.len());
@@ -328,7 +524,7 @@ impl EnumTestHelpers<3> for RegSHOAction {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub enum TradingReasonCodes {
     Halt(TradingHaltReasonCodes),
     Resumption(TradingResumptionReasonCodes),
@@ -338,7 +534,8 @@ impl TryFrom<&[u8]> for TradingReasonCodes {
     type Error = ParseError;
 
     fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
-        match value {
+        // Codes are left justified and space padded to 4 bytes
+        match value.trim_ascii_end() {
             b"T1" => Ok(TradingReasonCodes::Halt(
                 TradingHaltReasonCodes::HaltNewsPending,
             )),
@@ -433,17 +630,82 @@ impl TryFrom<&[u8]> for TradingReasonCodes {
             b"IPOE" => Ok(TradingReasonCodes::Resumption(
                 TradingResumptionReasonCodes::IPOPositioningWindowExtension,
             )),
-            b"    " => Ok(TradingReasonCodes::Halt(
+            b"" => Ok(TradingReasonCodes::Halt(
                 TradingHaltReasonCodes::NotAvailable,
             )),
-            _ => return Err(ParseError::InvalidTradingReasonCode),
+            _ => Err(ParseError::InvalidTradingReasonCode),
         }
     }
 }
 
-// TODO: Implement tests for TradingReasonCodes
+impl From<&TradingReasonCodes> for [u8; 4] {
+    fn from(value: &TradingReasonCodes) -> Self {
+        // Left justified and space padded, the inverse of `TryFrom<&[u8]>`
+        let code: &[u8] = match value {
+            TradingReasonCodes::Halt(TradingHaltReasonCodes::HaltNewsPending) => b"T1",
+            TradingReasonCodes::Halt(TradingHaltReasonCodes::HaltNewsDisseminated) => b"T2",
+            TradingReasonCodes::Halt(TradingHaltReasonCodes::SingleSecurityTradingPause) => b"T5",
+            TradingReasonCodes::Halt(TradingHaltReasonCodes::ExtraordinaryMarketActivity) => b"T6",
+            TradingReasonCodes::Halt(TradingHaltReasonCodes::ETFHalt) => b"T8",
+            TradingReasonCodes::Halt(TradingHaltReasonCodes::InformationRequested) => b"T12",
+            TradingReasonCodes::Halt(TradingHaltReasonCodes::NonCompliance) => b"H4",
+            TradingReasonCodes::Halt(TradingHaltReasonCodes::NonCurrentFilings) => b"H9",
+            TradingReasonCodes::Halt(TradingHaltReasonCodes::SECTradingSuspension) => b"H10",
+            TradingReasonCodes::Halt(TradingHaltReasonCodes::RegulatoryConcern) => b"H11",
+            TradingReasonCodes::Halt(TradingHaltReasonCodes::OperationsHalt) => b"O1",
+            TradingReasonCodes::Halt(TradingHaltReasonCodes::VolatilityPause) => b"LUDP",
+            TradingReasonCodes::Halt(TradingHaltReasonCodes::VolatilityPauseStraddle) => b"LUDS",
+            TradingReasonCodes::Halt(TradingHaltReasonCodes::Level1CircuitBreaker) => b"MWC1",
+            TradingReasonCodes::Halt(TradingHaltReasonCodes::Level2CircuitBreaker) => b"MWC2",
+            TradingReasonCodes::Halt(TradingHaltReasonCodes::Level3CircuitBreaker) => b"MWC3",
+            TradingReasonCodes::Halt(TradingHaltReasonCodes::CarryOverCircuitBreaker) => b"MWC0",
+            TradingReasonCodes::Halt(TradingHaltReasonCodes::IPONotYetTrading) => b"IPO1",
+            TradingReasonCodes::Halt(TradingHaltReasonCodes::CorporateAction) => b"M1",
+            TradingReasonCodes::Halt(TradingHaltReasonCodes::QuotationUnavailable) => b"M2",
+            TradingReasonCodes::Resumption(TradingResumptionReasonCodes::NewsAndResumptionTime) => {
+                b"T3"
+            }
+            TradingReasonCodes::Resumption(
+                TradingResumptionReasonCodes::SingleSecurityPauseOrQuoteOnlyPeriod,
+            ) => b"T7",
+            TradingReasonCodes::Resumption(
+                TradingResumptionReasonCodes::QualificationIssuesResolved,
+            ) => b"R4",
+            TradingReasonCodes::Resumption(TradingResumptionReasonCodes::FilingIssuesResolved) => {
+                b"R9"
+            }
+            TradingReasonCodes::Resumption(
+                TradingResumptionReasonCodes::IssuerNewNotForthcoming,
+            ) => b"C3",
+            TradingReasonCodes::Resumption(
+                TradingResumptionReasonCodes::QualificationsHaltEndedMaintenanceMet,
+            ) => b"C4",
+            TradingReasonCodes::Resumption(
+                TradingResumptionReasonCodes::QualificationsHaltConcludedFilingsMet,
+            ) => b"C9",
+            TradingReasonCodes::Resumption(TradingResumptionReasonCodes::TradeHaltConcluded) => {
+                b"C11"
+            }
+            TradingReasonCodes::Resumption(
+                TradingResumptionReasonCodes::CircuitBreakerResumption,
+            ) => b"MWCQ",
+            TradingReasonCodes::Resumption(TradingResumptionReasonCodes::IssueAvailable) => b"R1", // R2 is parsed the same
+            TradingReasonCodes::Resumption(
+                TradingResumptionReasonCodes::IPOSecurityQuotationReleased,
+            ) => b"IPOQ",
+            TradingReasonCodes::Resumption(
+                TradingResumptionReasonCodes::IPOPositioningWindowExtension,
+            ) => b"IPOE",
+            TradingReasonCodes::Halt(TradingHaltReasonCodes::NotAvailable) => b"",
+            TradingReasonCodes::Resumption(TradingResumptionReasonCodes::NotAvailable) => b"",
+        };
+        let mut bytes = [b' '; 4];
+        bytes[..code.len()].copy_from_slice(code);
+        bytes
+    }
+}
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub enum TradingHaltReasonCodes {
     HaltNewsPending,
     HaltNewsDisseminated,
@@ -468,7 +730,7 @@ pub enum TradingHaltReasonCodes {
     NotAvailable,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub enum TradingResumptionReasonCodes {
     NewsAndResumptionTime,
     SingleSecurityPauseOrQuoteOnlyPeriod,
@@ -533,12 +795,32 @@ impl TryFrom<u8> for IssueClassificationCodes {
     }
 }
 
+impl From<&IssueClassificationCodes> for u8 {
+    fn from(value: &IssueClassificationCodes) -> Self {
+        match value {
+            IssueClassificationCodes::AmericanDepositaryShare => b'A',
+            IssueClassificationCodes::Bond => b'B',
+            IssueClassificationCodes::CommonStock => b'C',
+            IssueClassificationCodes::DepositoryReceipt => b'F',
+            IssueClassificationCodes::UnregisteredSecurity => b'I',
+            IssueClassificationCodes::LimitedPartnership => b'L',
+            IssueClassificationCodes::Notes => b'N',
+            IssueClassificationCodes::OrdinaryShare => b'O',
+            IssueClassificationCodes::PreferredStock => b'P',
+            IssueClassificationCodes::OtherSecurity => b'Q',
+            IssueClassificationCodes::Right => b'R',
+            IssueClassificationCodes::ShareOfBeneficialInterest => b'S',
+            IssueClassificationCodes::ConvertibleDebenture => b'T',
+            IssueClassificationCodes::Unit => b'U',
+            IssueClassificationCodes::UnitBenifInt => b'V',
+            IssueClassificationCodes::Warrant => b'W',
+        }
+    }
+}
+
 #[cfg(any(test, feature = "bench"))]
 impl EnumTestHelpers<16> for IssueClassificationCodes {
-    const VALID_CODES: [u8; 16] = [
-        b'A', b'B', b'C', b'F', b'I', b'L', b'N', b'O', b'P', b'Q', b'R', b'S', b'T', b'U', b'V',
-        b'W',
-    ];
+    const VALID_CODES: [u8; 16] = *b"ABCFILNOPQRSTUVW";
 
     fn generate_example_code() -> u8 {
         let i = fastrand::usize(..Self::VALID_CODES.len());
@@ -546,7 +828,7 @@ impl EnumTestHelpers<16> for IssueClassificationCodes {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub enum MarketMakerMode {
     Normal,
     Passive,
@@ -572,9 +854,21 @@ impl TryFrom<u8> for MarketMakerMode {
     }
 }
 
+impl From<&MarketMakerMode> for u8 {
+    fn from(value: &MarketMakerMode) -> Self {
+        match value {
+            MarketMakerMode::Normal => b'N',
+            MarketMakerMode::Passive => b'P',
+            MarketMakerMode::Syndicate => b'S',
+            MarketMakerMode::PreSyndicate => b'R',
+            MarketMakerMode::Penalty => b'L',
+        }
+    }
+}
+
 #[cfg(any(test, feature = "bench"))]
 impl EnumTestHelpers<5> for MarketMakerMode {
-    const VALID_CODES: [u8; 5] = [b'N', b'P', b'S', b'R', b'L'];
+    const VALID_CODES: [u8; 5] = *b"NPSRL";
 
     fn generate_example_code() -> u8 {
         let i = fastrand::usize(..Self::VALID_CODES.len());
@@ -582,7 +876,7 @@ impl EnumTestHelpers<5> for MarketMakerMode {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub enum MarketParticipantState {
     Active,
     ExcusedWithdrawn,
@@ -608,9 +902,21 @@ impl TryFrom<u8> for MarketParticipantState {
     }
 }
 
+impl From<&MarketParticipantState> for u8 {
+    fn from(value: &MarketParticipantState) -> Self {
+        match value {
+            MarketParticipantState::Active => b'A',
+            MarketParticipantState::ExcusedWithdrawn => b'E',
+            MarketParticipantState::Withdrawn => b'W',
+            MarketParticipantState::Suspended => b'S',
+            MarketParticipantState::Deleted => b'D',
+        }
+    }
+}
+
 #[cfg(any(test, feature = "bench"))]
 impl EnumTestHelpers<5> for MarketParticipantState {
-    const VALID_CODES: [u8; 5] = [b'A', b'E', b'W', b'S', b'D'];
+    const VALID_CODES: [u8; 5] = *b"AEWSD";
 
     fn generate_example_code() -> u8 {
         let i = fastrand::usize(..Self::VALID_CODES.len());
@@ -618,7 +924,7 @@ impl EnumTestHelpers<5> for MarketParticipantState {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub enum MWCBLevel {
     Level1,
     Level2,
@@ -640,9 +946,19 @@ impl TryFrom<u8> for MWCBLevel {
     }
 }
 
+impl From<&MWCBLevel> for u8 {
+    fn from(value: &MWCBLevel) -> Self {
+        match value {
+            MWCBLevel::Level1 => b'1',
+            MWCBLevel::Level2 => b'2',
+            MWCBLevel::Level3 => b'3',
+        }
+    }
+}
+
 #[cfg(any(test, feature = "bench"))]
 impl EnumTestHelpers<3> for MWCBLevel {
-    const VALID_CODES: [u8; 3] = [b'1', b'2', b'3'];
+    const VALID_CODES: [u8; 3] = *b"123";
 
     fn generate_example_code() -> u8 {
         let i = fastrand::usize(..Self::VALID_CODES.len());
@@ -650,7 +966,7 @@ impl EnumTestHelpers<3> for MWCBLevel {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub enum IPOReleaseQualifier {
     Anticipated,
     Postponed,
@@ -670,9 +986,138 @@ impl TryFrom<u8> for IPOReleaseQualifier {
     }
 }
 
+impl From<&IPOReleaseQualifier> for u8 {
+    fn from(value: &IPOReleaseQualifier) -> Self {
+        match value {
+            IPOReleaseQualifier::Anticipated => b'A',
+            IPOReleaseQualifier::Postponed => b'C',
+        }
+    }
+}
+
 #[cfg(any(test, feature = "bench"))]
 impl EnumTestHelpers<2> for IPOReleaseQualifier {
-    const VALID_CODES: [u8; 2] = [b'A', b'C'];
+    const VALID_CODES: [u8; 2] = *b"AC";
+
+    fn generate_example_code() -> u8 {
+        let i = fastrand::usize(..Self::VALID_CODES.len());
+        Self::VALID_CODES[i]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MarketCode {
+    Nasdaq,
+    Bx,
+    Psx,
+}
+
+impl TryFrom<u8> for MarketCode {
+    type Error = ParseError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            b'Q' => Ok(MarketCode::Nasdaq),
+            b'B' => Ok(MarketCode::Bx),
+            b'X' => Ok(MarketCode::Psx),
+            _ => Err(ParseError::InvalidMarketCode {
+                invalid_byte: value,
+            }),
+        }
+    }
+}
+
+impl From<&MarketCode> for u8 {
+    fn from(value: &MarketCode) -> Self {
+        match value {
+            MarketCode::Nasdaq => b'Q',
+            MarketCode::Bx => b'B',
+            MarketCode::Psx => b'X',
+        }
+    }
+}
+
+#[cfg(any(test, feature = "bench"))]
+impl EnumTestHelpers<3> for MarketCode {
+    const VALID_CODES: [u8; 3] = *b"QBX";
+
+    fn generate_example_code() -> u8 {
+        let i = fastrand::usize(..Self::VALID_CODES.len());
+        Self::VALID_CODES[i]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OperationalHaltAction {
+    Halted,
+    Resumed,
+}
+
+impl TryFrom<u8> for OperationalHaltAction {
+    type Error = ParseError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            b'H' => Ok(OperationalHaltAction::Halted),
+            b'T' => Ok(OperationalHaltAction::Resumed),
+            _ => Err(ParseError::InvalidOperationalHaltAction {
+                invalid_byte: value,
+            }),
+        }
+    }
+}
+
+impl From<&OperationalHaltAction> for u8 {
+    fn from(value: &OperationalHaltAction) -> Self {
+        match value {
+            OperationalHaltAction::Halted => b'H',
+            OperationalHaltAction::Resumed => b'T',
+        }
+    }
+}
+
+#[cfg(any(test, feature = "bench"))]
+impl EnumTestHelpers<2> for OperationalHaltAction {
+    const VALID_CODES: [u8; 2] = *b"HT";
+
+    fn generate_example_code() -> u8 {
+        let i = fastrand::usize(..Self::VALID_CODES.len());
+        Self::VALID_CODES[i]
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum OpenEligibilityStatus {
+    NotEligible,
+    Eligible,
+}
+
+impl TryFrom<u8> for OpenEligibilityStatus {
+    type Error = ParseError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            b'N' => Ok(OpenEligibilityStatus::NotEligible),
+            b'Y' => Ok(OpenEligibilityStatus::Eligible),
+            _ => Err(ParseError::InvalidOpenEligibilityStatus {
+                invalid_byte: value,
+            }),
+        }
+    }
+}
+
+impl From<&OpenEligibilityStatus> for u8 {
+    fn from(value: &OpenEligibilityStatus) -> Self {
+        match value {
+            OpenEligibilityStatus::NotEligible => b'N',
+            OpenEligibilityStatus::Eligible => b'Y',
+        }
+    }
+}
+
+#[cfg(any(test, feature = "bench"))]
+impl EnumTestHelpers<2> for OpenEligibilityStatus {
+    const VALID_CODES: [u8; 2] = *b"NY";
 
     fn generate_example_code() -> u8 {
         let i = fastrand::usize(..Self::VALID_CODES.len());
@@ -700,9 +1145,18 @@ impl TryFrom<u8> for BuySellIndicator {
     }
 }
 
+impl From<&BuySellIndicator> for u8 {
+    fn from(value: &BuySellIndicator) -> Self {
+        match value {
+            BuySellIndicator::Sell => b'S',
+            BuySellIndicator::Buy => b'B',
+        }
+    }
+}
+
 #[cfg(any(test, feature = "bench"))]
 impl EnumTestHelpers<2> for BuySellIndicator {
-    const VALID_CODES: [u8; 2] = [b'S', b'B'];
+    const VALID_CODES: [u8; 2] = *b"SB";
 
     fn generate_example_code() -> u8 {
         let i = fastrand::usize(..Self::VALID_CODES.len());
@@ -710,7 +1164,7 @@ impl EnumTestHelpers<2> for BuySellIndicator {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub enum CrossType {
     OpeningCross,
     ClosingCross,
@@ -734,9 +1188,20 @@ impl TryFrom<u8> for CrossType {
     }
 }
 
+impl From<&CrossType> for u8 {
+    fn from(value: &CrossType) -> Self {
+        match value {
+            CrossType::OpeningCross => b'O',
+            CrossType::ClosingCross => b'C',
+            CrossType::IPOCrossOrHaltedSecurity => b'H',
+            CrossType::IntradayOrPostCloseCross => b'I',
+        }
+    }
+}
+
 #[cfg(any(test, feature = "bench"))]
 impl EnumTestHelpers<4> for CrossType {
-    const VALID_CODES: [u8; 4] = [b'O', b'C', b'H', b'I'];
+    const VALID_CODES: [u8; 4] = *b"OCHI";
 
     fn generate_example_code() -> u8 {
         let i = fastrand::usize(..Self::VALID_CODES.len());
@@ -744,7 +1209,7 @@ impl EnumTestHelpers<4> for CrossType {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub enum ImbalanceDirection {
     BuyImbalance,
     SellImbalance,
@@ -768,9 +1233,20 @@ impl TryFrom<u8> for ImbalanceDirection {
     }
 }
 
+impl From<&ImbalanceDirection> for u8 {
+    fn from(value: &ImbalanceDirection) -> Self {
+        match value {
+            ImbalanceDirection::BuyImbalance => b'B',
+            ImbalanceDirection::SellImbalance => b'S',
+            ImbalanceDirection::NoImbalance => b'N',
+            ImbalanceDirection::InsufficnetOrdersToCalculate => b'O',
+        }
+    }
+}
+
 #[cfg(any(test, feature = "bench"))]
 impl EnumTestHelpers<4> for ImbalanceDirection {
-    const VALID_CODES: [u8; 4] = [b'B', b'S', b'N', b'O'];
+    const VALID_CODES: [u8; 4] = *b"BSNO";
 
     fn generate_example_code() -> u8 {
         let i = fastrand::usize(..Self::VALID_CODES.len());
@@ -0,0 +1,292 @@
+use crate::enums::{MessageTypes, OperationalHaltAction};
+use crate::orderbook::{Execution, StockLocateCode};
+use crate::session::SessionChange;
+use crate::tradetape::Trade;
+use crate::tradingstate;
+use crate::types::Price4;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, RwLock};
+
+/// Events derived from the message stream, as opposed to the messages themselves.
+///
+/// # Variants
+/// * `TopOfBook` - The best bid or best ask of a stock, or the shares at them, changed.
+/// * `Execution` - Shares of a resting order were executed.
+/// * `Trade` - A print was added to the trade tape.
+/// * `TradeBroken` - A print was retracted from the trade tape.
+/// * `Halt` - A stock was halted or resumed.
+/// * `Session` - The market session changed, e.g. regular hours started.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    TopOfBook(TopOfBook),
+    Execution(Execution),
+    Trade(StockLocateCode, Trade),
+    TradeBroken(StockLocateCode, Trade),
+    Halt(Halt),
+    Session(SessionChange),
+}
+
+/// The best bid and offer (BBO) of a stock after a change.
+///
+/// # Fields
+/// * `timestamp` - Nanoseconds since midnight, of the message that caused the change.
+/// * `stock_locate` - The stock locate code of the book.
+/// * `best_bid` - The highest bid, `None` if the bid side is empty.
+/// * `bid_size` - The shares at the highest bid, 0 if the bid side is empty.
+/// * `best_ask` - The lowest ask, `None` if the ask side is empty.
+/// * `ask_size` - The shares at the lowest ask, 0 if the ask side is empty.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TopOfBook {
+    pub timestamp: u64,
+    pub stock_locate: StockLocateCode,
+    pub best_bid: Option<Price4>,
+    pub bid_size: u32,
+    pub best_ask: Option<Price4>,
+    pub ask_size: u32,
+}
+
+/// A stock was halted or resumed, by a `StockTradingAction` ('H') or `OperationalHalt` ('h') message.
+///
+/// # Fields
+/// * `timestamp` - Nanoseconds since midnight.
+/// * `stock_locate` - The stock locate code of the stock.
+/// * `halted` - True if the stock is now halted or paused, false if it resumed.
+/// * `operational` - True if the halt is an operational halt, which only applies to a single market.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Halt {
+    pub timestamp: u64,
+    pub stock_locate: StockLocateCode,
+    pub halted: bool,
+    pub operational: bool,
+}
+
+/// A handler of parsed messages and derived events, registered with a `Dispatcher`.
+///
+/// Both methods have empty default implementations, so a sink only implements what it needs.
+/// Derived events are emitted by pushing them onto `events`, and are delivered to every sink once all sinks have
+/// seen the current message. A sink must not emit an event in response to the same kind of event, otherwise dispatch never ends.
+pub trait EventSink {
+    /// Called for every parsed message, in file order.
+    ///
+    /// # Arguments
+    /// * `message` - The parsed message.
+    /// * `events` - Queue for derived events.
+    fn on_message(&mut self, _message: &MessageTypes, _events: &mut VecDeque<Event>) {}
+
+    /// Called for every derived event, in the order they were emitted.
+    ///
+    /// # Arguments
+    /// * `event` - The derived event.
+    /// * `events` - Queue for further derived events.
+    fn on_event(&mut self, _event: &Event, _events: &mut VecDeque<Event>) {}
+}
+
+/// Shared sinks, e.g. an `OrderBookManager` that is also read by the TUI. The lock is held for the duration of each call.
+impl<T: EventSink> EventSink for Arc<RwLock<T>> {
+    fn on_message(&mut self, message: &MessageTypes, events: &mut VecDeque<Event>) {
+        self.write().unwrap().on_message(message, events);
+    }
+
+    fn on_event(&mut self, event: &Event, events: &mut VecDeque<Event>) {
+        self.write().unwrap().on_event(event, events);
+    }
+}
+
+/// Delivers messages, and the events derived from them, to every registered sink.
+/// Sinks are called in the order they were registered.
+pub struct Dispatcher {
+    sinks: Vec<Box<dyn EventSink>>,
+    events: VecDeque<Event>,
+}
+
+impl Default for Dispatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Dispatcher {
+    pub fn new() -> Self {
+        Self {
+            sinks: Vec::new(),
+            events: VecDeque::new(),
+        }
+    }
+
+    /// Register a sink. Sinks that emit events other sinks depend on, e.g. the order book, should be registered first.
+    pub fn add_sink<S: EventSink + 'static>(&mut self, sink: S) {
+        self.sinks.push(Box::new(sink));
+    }
+
+    /// Deliver a message to every sink, followed by any events derived from it.
+    ///
+    /// # Arguments
+    /// * `message` - The parsed message.
+    pub fn dispatch(&mut self, message: &MessageTypes) {
+        for sink in &mut self.sinks {
+            sink.on_message(message, &mut self.events);
+        }
+        self.deliver_events();
+    }
+
+    /// Deliver an event that was derived outside of `dispatch` to every sink, followed by any events derived from it.
+    /// Used for the events of a `ShardedOrderBookManager` that are still in flight once the last message is dispatched.
+    ///
+    /// # Arguments
+    /// * `event` - The derived event.
+    pub fn dispatch_event(&mut self, event: Event) {
+        self.events.push_back(event);
+        self.deliver_events();
+    }
+
+    fn deliver_events(&mut self) {
+        while let Some(event) = self.events.pop_front() {
+            for sink in &mut self.sinks {
+                sink.on_event(&event, &mut self.events);
+            }
+        }
+    }
+}
+
+/// Emits a `Halt` event whenever a stock is halted or resumed.
+/// Stocks are assumed to be trading until a message says otherwise, so the start of day trading actions emit nothing.
+#[derive(Default)]
+pub struct HaltDetector {
+    halted: HashMap<(StockLocateCode, bool), bool>, // (stock locate code, operational) > halted
+}
+
+impl HaltDetector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn update(
+        &mut self,
+        timestamp: u64,
+        stock_locate: StockLocateCode,
+        halted: bool,
+        operational: bool,
+        events: &mut VecDeque<Event>,
+    ) {
+        let previous = self
+            .halted
+            .insert((stock_locate, operational), halted)
+            .unwrap_or(false);
+        if previous != halted {
+            events.push_back(Event::Halt(Halt {
+                timestamp,
+                stock_locate,
+                halted,
+                operational,
+            }));
+        }
+    }
+}
+
+impl EventSink for HaltDetector {
+    fn on_message(&mut self, message: &MessageTypes, events: &mut VecDeque<Event>) {
+        match message {
+            MessageTypes::StockTradingAction(action) => {
+                let halted = tradingstate::is_halted(action.trading_state);
+                self.update(
+                    action.header.timestamp,
+                    action.header.stock_locate,
+                    halted,
+                    false,
+                    events,
+                );
+            }
+            MessageTypes::OperationalHalt(halt) => {
+                let halted = halt.operational_halt_action == OperationalHaltAction::Halted;
+                self.update(
+                    halt.header.timestamp,
+                    halt.header.stock_locate,
+                    halted,
+                    true,
+                    events,
+                );
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enums::{TradingReasonCodes, TradingState};
+    use crate::messageheader::MessageHeader;
+    use crate::stockmessages::StockTradingAction;
+
+    /// Records everything it sees, emitting a halt event for every message.
+    #[derive(Default)]
+    struct Recorder {
+        messages: usize,
+        events: Vec<Event>,
+    }
+
+    impl EventSink for Recorder {
+        fn on_message(&mut self, _message: &MessageTypes, events: &mut VecDeque<Event>) {
+            self.messages += 1;
+            events.push_back(Event::Halt(Halt {
+                timestamp: 0,
+                stock_locate: 1,
+                halted: true,
+                operational: false,
+            }));
+        }
+
+        fn on_event(&mut self, event: &Event, _events: &mut VecDeque<Event>) {
+            self.events.push(event.clone());
+        }
+    }
+
+    fn trading_action(trading_state: TradingState) -> MessageTypes {
+        MessageTypes::StockTradingAction(StockTradingAction {
+            header: MessageHeader::example(7, 0),
+            stock: *b"AAPL    ",
+            trading_state,
+            reserved: 0,
+            reason: TradingReasonCodes::try_from(&b"T1  "[..]).unwrap(),
+        })
+    }
+
+    #[test]
+    fn test_dispatcher() {
+        let first = Arc::new(RwLock::new(Recorder::default()));
+        let second = Arc::new(RwLock::new(Recorder::default()));
+
+        let mut dispatcher = Dispatcher::new();
+        dispatcher.add_sink(Arc::clone(&first));
+        dispatcher.add_sink(Arc::clone(&second));
+        dispatcher.dispatch(&trading_action(TradingState::Trading));
+
+        // Every sink sees the message, and the events emitted by every sink
+        for recorder in [first, second] {
+            let recorder = recorder.read().unwrap();
+            assert_eq!(recorder.messages, 1);
+            assert_eq!(recorder.events.len(), 2);
+        }
+    }
+
+    #[test]
+    fn test_halt_detector() {
+        let mut detector = HaltDetector::new();
+        let mut events = VecDeque::new();
+
+        detector.on_message(&trading_action(TradingState::Trading), &mut events);
+        assert!(events.is_empty());
+
+        detector.on_message(&trading_action(TradingState::Halted), &mut events);
+        detector.on_message(&trading_action(TradingState::Paused), &mut events);
+        detector.on_message(&trading_action(TradingState::Trading), &mut events);
+        let halts: Vec<_> = events
+            .iter()
+            .map(|event| match event {
+                Event::Halt(halt) => halt.halted,
+                _ => panic!("Expected a halt event"),
+            })
+            .collect();
+        assert_eq!(halts, [true, false]);
+    }
+}
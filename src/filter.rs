@@ -0,0 +1,89 @@
+use crate::orderbook::StockLocateCode;
+use crate::stockdirectory::StockDirectoryManager;
+use crate::stockmessages::StockDirectory;
+use crate::types::{MessageHeaderType, Stock};
+use byteorder::{BigEndian, ByteOrder};
+
+/// An allowlist of stocks, checked against the stock locate code of each frame before it is parsed.
+/// Messages of every other stock are skipped without being parsed or dispatched, so the books of filtered
+/// runs only hold the stocks asked for.
+///
+/// Symbols are resolved to stock locate codes once their 'R' (stock directory) messages arrive, see `resolve`.
+/// Messages with a stock locate code of 0 are not about a single stock (system events, MWCB) and always pass,
+/// as do 'R' messages so that symbols can be resolved.
+///
+/// # Fields
+/// * `symbols` - Stocks to keep, resolved to stock locate codes by `resolve`.
+/// * `allowed` - Indexed by stock locate code, true for the stocks to keep.
+pub struct SymbolFilter {
+    symbols: Vec<Stock>,
+    allowed: Vec<bool>,
+}
+
+impl SymbolFilter {
+    /// # Arguments
+    /// * `symbols` - Stocks to keep. Locate codes are only valid for a single day, so a new filter is needed per file.
+    /// * `stock_locates` - Stock locate codes to keep, whatever their stock.
+    pub fn new(symbols: Vec<Stock>, stock_locates: &[StockLocateCode]) -> Self {
+        let mut allowed = vec![false; usize::from(StockLocateCode::MAX) + 1];
+        for &stock_locate in stock_locates {
+            allowed[usize::from(stock_locate)] = true;
+        }
+        Self { symbols, allowed }
+    }
+
+    /// Allow the stock locate codes of any symbols that are now in the stock directory.
+    /// Call after each 'R' message has been applied to the directory.
+    ///
+    /// # Arguments
+    /// * `stock_directory_manager` - The stock directory of the day being replayed.
+    pub fn resolve(&mut self, stock_directory_manager: &StockDirectoryManager) {
+        for &stock in &self.symbols {
+            if let Some(stock_locate) = stock_directory_manager.stock_to_locate(stock) {
+                self.allowed[usize::from(stock_locate)] = true;
+            }
+        }
+    }
+
+    /// Returns true if messages of `stock_locate` are kept.
+    pub fn allows(&self, stock_locate: StockLocateCode) -> bool {
+        stock_locate == 0 || self.allowed[usize::from(stock_locate)]
+    }
+
+    /// Returns true if a frame is kept, reading only the stock locate code at the start of its header.
+    /// Frames too short to hold one are kept, and left for the parser to reject.
+    ///
+    /// # Arguments
+    /// * `message_type` - The message type byte.
+    /// * `body` - The bytes following the message type byte.
+    pub fn allows_frame(&self, message_type: u8, body: &[u8]) -> bool {
+        if message_type == StockDirectory::MESSAGE_TYPE || body.len() < 2 {
+            return true;
+        }
+        self.allows(BigEndian::read_u16(body))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_symbol_filter() {
+        let mut filter = SymbolFilter::new(vec![*b"AAPL    "], &[7]);
+        let mut stock_directory_manager = StockDirectoryManager::new();
+
+        // Unresolved symbols are skipped until their directory message arrives
+        assert!(!filter.allows_frame(b'A', &1u16.to_be_bytes()));
+        assert!(filter.allows_frame(b'R', &1u16.to_be_bytes()));
+        stock_directory_manager.add_stock(StockDirectory::example(1, *b"AAPL    "));
+        stock_directory_manager.add_stock(StockDirectory::example(2, *b"MSFT    "));
+        filter.resolve(&stock_directory_manager);
+
+        assert!(filter.allows_frame(b'A', &1u16.to_be_bytes()));
+        assert!(!filter.allows_frame(b'A', &2u16.to_be_bytes()));
+        assert!(filter.allows_frame(b'A', &7u16.to_be_bytes()));
+        assert!(filter.allows_frame(b'S', &0u16.to_be_bytes()));
+        assert!(filter.allows_frame(b'A', &[]));
+    }
+}
@@ -1,5 +1,5 @@
 use crate::enums::BoolOrUnavailable;
-use crate::types::{Parse, ParseError};
+use crate::types::{ParseError, Stock, MPID};
 
 pub fn byte_to_bool(byte: u8) -> Result<bool, ParseError> {
     match byte {
@@ -17,6 +17,76 @@ pub fn byte_to_bool_space(byte: u8) -> Result<BoolOrUnavailable, ParseError> {
         b => Err(ParseError::InvalidBooleanByte { invalid_byte: b }),
     }
 }
+/// The inverse of `byte_to_bool`.
+pub fn bool_to_byte(value: bool) -> u8 {
+    if value {
+        b'Y'
+    } else {
+        b'N'
+    }
+}
+
+/// Converts a ticker such as `"AAPL"` into the left justified, space padded `Stock` used by ITCH.
+/// Returns `None` if the ticker is empty, longer than 8 characters or not ASCII.
+pub fn str_to_stock(symbol: &str) -> Option<Stock> {
+    let symbol = symbol.trim();
+    if symbol.is_empty() || symbol.len() > 8 || !symbol.is_ascii() {
+        return None;
+    }
+    let mut stock = [b' '; 8];
+    stock[..symbol.len()].copy_from_slice(symbol.to_ascii_uppercase().as_bytes());
+    Some(stock)
+}
+
+/// Converts a `Stock` back into its ticker with the padding removed.
+pub fn stock_to_str(stock: &Stock) -> &str {
+    std::str::from_utf8(stock).unwrap_or("").trim_end()
+}
+
+/// Converts an `MPID` such as `b"GSCO"` into a string, with any padding removed.
+pub fn mpid_to_str(mpid: &MPID) -> &str {
+    std::str::from_utf8(mpid).unwrap_or("").trim_end()
+}
+
+/// Parses a time of day such as `"09:30"`, `"09:30:00"` or `"09:30:00.250"` into nanoseconds since midnight,
+/// the unit of ITCH timestamps. A plain integer is taken to already be nanoseconds since midnight.
+/// Returns `None` if the time is malformed or not within a day.
+pub fn parse_time_of_day(time: &str) -> Option<u64> {
+    const NANOS_PER_SECOND: u64 = 1_000_000_000;
+    let time = time.trim();
+    if !time.contains(':') {
+        return time.parse().ok();
+    }
+
+    let (clock, fraction) = time.split_once('.').unwrap_or((time, ""));
+    let mut parts = clock.split(':');
+    let hours: u64 = parts.next()?.parse().ok()?;
+    let minutes: u64 = parts.next()?.parse().ok()?;
+    let seconds: u64 = parts.next().map_or(Some(0), |s| s.parse().ok())?;
+    if parts.next().is_some() || hours > 24 || minutes > 59 || seconds > 59 {
+        return None;
+    }
+    if fraction.len() > 9 || !fraction.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let nanos = format!("{fraction:0<9}").parse::<u64>().ok()?;
+
+    let total = (hours * 3600 + minutes * 60 + seconds) * NANOS_PER_SECOND + nanos;
+    (total <= 24 * 3600 * NANOS_PER_SECOND).then_some(total)
+}
+
+/// Formats nanoseconds since midnight as `HH:MM:SS.nnnnnnnnn`, the inverse of `parse_time_of_day`.
+pub fn format_time_of_day(timestamp: u64) -> String {
+    let seconds = timestamp / 1_000_000_000;
+    format!(
+        "{:02}:{:02}:{:02}.{:09}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60,
+        timestamp % 1_000_000_000
+    )
+}
+
 // Was too slow
 // pub fn u8s_to_ticker(input: &[u8]) -> String {
 //     let mut ticker = String::new();
@@ -0,0 +1,212 @@
+use crate::auction::Cross;
+use crate::enums::{BoolOrUnavailable, CrossType, IPOReleaseQualifier, MessageTypes};
+use crate::events::{Event, EventSink};
+use crate::orderbook::StockLocateCode;
+use crate::stockmessages::IPOQuotingPeriodUpdate;
+use crate::types::{Price4, PriceConversions};
+use std::collections::{HashMap, VecDeque};
+
+/// An update to the quoting period of an IPO, from an `IPOQuotingPeriodUpdate` ('K') message.
+///
+/// # Fields
+/// * `timestamp` - Nanoseconds since midnight, of the message.
+/// * `release_time` - Nanoseconds since midnight, of the scheduled quotation release.
+/// * `qualifier` - Whether the release is anticipated at `release_time`, or cancelled or postponed.
+/// * `ipo_price` - The IPO price.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IPOUpdate {
+    pub timestamp: u64,
+    pub release_time: u64,
+    pub qualifier: IPOReleaseQualifier,
+    pub ipo_price: Price4,
+}
+
+impl From<&IPOQuotingPeriodUpdate> for IPOUpdate {
+    fn from(update: &IPOQuotingPeriodUpdate) -> Self {
+        Self {
+            timestamp: update.header.timestamp,
+            // The release time is sent in seconds since midnight
+            release_time: u64::from(update.ipo_quotation_release_time) * 1_000_000_000,
+            qualifier: update.ipo_quotation_release_qualifier,
+            ipo_price: Price4::new(update.ipo_price),
+        }
+    }
+}
+
+/// The IPO of a single stock.
+///
+/// # Fields
+/// * `ipo_flag` - True if the stock directory flagged the stock as a new IPO security.
+/// * `updates` - Every quoting period update, in file order.
+/// * `cross` - The IPO cross (`CrossType::IPOCrossOrHaltedSecurity`) that released the stock, `None` until it crosses.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IPO {
+    pub ipo_flag: bool,
+    pub updates: Vec<IPOUpdate>,
+    pub cross: Option<Cross>,
+}
+
+impl IPO {
+    /// The latest quoting period update, `None` if there were none.
+    pub fn latest(&self) -> Option<&IPOUpdate> {
+        self.updates.last()
+    }
+
+    /// The scheduled quotation release time, in nanoseconds since midnight.
+    /// `None` if there is no schedule, or the latest update cancelled or postponed the release.
+    pub fn release_time(&self) -> Option<u64> {
+        self.latest()
+            .filter(|update| update.qualifier == IPOReleaseQualifier::Anticipated)
+            .map(|update| update.release_time)
+    }
+
+    /// The latest IPO price, `None` if there were no updates.
+    pub fn ipo_price(&self) -> Option<Price4> {
+        self.latest().map(|update| update.ipo_price)
+    }
+
+    /// The number of times the release was cancelled or postponed.
+    pub fn postponements(&self) -> usize {
+        self.updates
+            .iter()
+            .filter(|update| update.qualifier == IPOReleaseQualifier::Postponed)
+            .count()
+    }
+
+    /// Returns true once the IPO cross has happened.
+    pub fn is_released(&self) -> bool {
+        self.cross.is_some()
+    }
+}
+
+/// Tracks the IPOs of the day from the stock directory IPO flag and `IPOQuotingPeriodUpdate` ('K') messages, and ties
+/// each IPO to the crossing trade ('Q') that released it.
+///
+/// # Fields
+/// * `ipos` - The IPO of every stock flagged in the stock directory or with a quoting period update.
+#[derive(Debug, Default)]
+pub struct IPOTracker {
+    pub ipos: HashMap<StockLocateCode, IPO>,
+}
+
+impl IPOTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get_ipo(&self, stock_locate: StockLocateCode) -> Option<&IPO> {
+        self.ipos.get(&stock_locate)
+    }
+}
+
+impl EventSink for IPOTracker {
+    fn on_message(&mut self, message: &MessageTypes, _events: &mut VecDeque<Event>) {
+        match message {
+            MessageTypes::StockDirectory(directory)
+                if directory.ipo_flag == BoolOrUnavailable::Bool(true) =>
+            {
+                self.ipos
+                    .entry(directory.header.stock_locate)
+                    .or_default()
+                    .ipo_flag = true;
+            }
+            MessageTypes::IPOQuotingPeriodUpdate(update) => {
+                self.ipos
+                    .entry(update.header.stock_locate)
+                    .or_default()
+                    .updates
+                    .push(IPOUpdate::from(update));
+            }
+            MessageTypes::CrossingTrade(trade)
+                if trade.cross_type == CrossType::IPOCrossOrHaltedSecurity =>
+            {
+                // Only the first IPO/halt cross releases the IPO, later ones are halt crosses
+                if let Some(ipo) = self
+                    .ipos
+                    .get_mut(&trade.header.stock_locate)
+                    .filter(|ipo| !ipo.is_released())
+                {
+                    ipo.cross = Some(Cross {
+                        timestamp: trade.header.timestamp,
+                        shares: trade.shares,
+                        price: trade.cross_price,
+                        match_number: trade.match_number,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::messageheader::MessageHeader;
+    use crate::stockmessages::StockDirectory;
+    use crate::trademessages::CrossingTrade;
+
+    fn directory(stock_locate: StockLocateCode, ipo_flag: bool) -> MessageTypes {
+        MessageTypes::StockDirectory(StockDirectory {
+            ipo_flag: BoolOrUnavailable::Bool(ipo_flag),
+            ..StockDirectory::example(stock_locate, *b"ARM     ")
+        })
+    }
+
+    fn update(timestamp: u64, release_time: u32, qualifier: IPOReleaseQualifier) -> MessageTypes {
+        MessageTypes::IPOQuotingPeriodUpdate(IPOQuotingPeriodUpdate {
+            header: MessageHeader::example(7, timestamp),
+            stock: *b"ARM     ",
+            ipo_quotation_release_time: release_time,
+            ipo_quotation_release_qualifier: qualifier,
+            ipo_price: 51_0000,
+        })
+    }
+
+    fn cross(timestamp: u64, shares: u64) -> MessageTypes {
+        MessageTypes::CrossingTrade(CrossingTrade {
+            header: MessageHeader::example(7, timestamp),
+            shares,
+            stock: *b"ARM     ",
+            cross_price: Price4::new(56_1000),
+            match_number: timestamp,
+            cross_type: CrossType::IPOCrossOrHaltedSecurity,
+        })
+    }
+
+    #[test]
+    fn test_ipo_tracker() {
+        let mut tracker = IPOTracker::new();
+        let mut events = VecDeque::new();
+        for message in [
+            directory(7, true),
+            directory(8, false),
+            update(10, 11 * 3600, IPOReleaseQualifier::Anticipated),
+            update(20, 0, IPOReleaseQualifier::Postponed),
+        ] {
+            tracker.on_message(&message, &mut events);
+        }
+        assert!(tracker.get_ipo(8).is_none());
+        let ipo = tracker.get_ipo(7).unwrap();
+        assert!(ipo.ipo_flag);
+        assert_eq!(ipo.release_time(), None);
+        assert_eq!(ipo.postponements(), 1);
+
+        for message in [
+            update(30, 13 * 3600 + 30 * 60, IPOReleaseQualifier::Anticipated),
+            cross(40, 1_000_000),
+            cross(50, 500),
+        ] {
+            tracker.on_message(&message, &mut events);
+        }
+        let ipo = tracker.get_ipo(7).unwrap();
+        assert_eq!(ipo.release_time(), Some(48_600_000_000_000));
+        assert_eq!(ipo.ipo_price(), Some(Price4::new(51_0000)));
+        assert_eq!(ipo.updates.len(), 3);
+
+        // The later halt cross is not the IPO cross
+        let ipo_cross = ipo.cross.unwrap();
+        assert_eq!((ipo_cross.timestamp, ipo_cross.shares), (40, 1_000_000));
+        assert!(events.is_empty());
+    }
+}
@@ -0,0 +1,621 @@
+use crate::addordermessages::AddOrder;
+use crate::enums::BuySellIndicator;
+use crate::orderbook::{Depth, OrderBook, OrderReferenceNumber, PriceLevel, QueuePosition};
+use crate::types::{OrderBookError, Price4, PriceConversions};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+/// Marks the end of a queue, or a level with no orders.
+const NIL: u32 = u32::MAX;
+/// The tick of stocks priced at $1 or more, in `Price4` units. Books fall back to a tick of 1 on the first sub-penny price.
+const PENNY: u32 = 100;
+/// Price levels held in the ladder of each side, counting from the best price. Levels further out are in `overflow`.
+const LADDER_TICKS: usize = 1024;
+
+/// A price level of a `LadderOrderBook`. Its orders are a doubly linked list through the order pool, in time priority.
+///
+/// # Fields
+/// * `share_quantity` - The total shares of the orders at the level.
+/// * `order_count` - The number of orders at the level.
+/// * `head` - Pool index of the first order in the queue, `NIL` if the level is empty.
+/// * `tail` - Pool index of the last order in the queue, `NIL` if the level is empty.
+#[derive(Debug, Clone, Copy)]
+struct Level {
+    share_quantity: u32,
+    order_count: u32,
+    head: u32,
+    tail: u32,
+}
+
+impl Default for Level {
+    fn default() -> Self {
+        Self {
+            share_quantity: 0,
+            order_count: 0,
+            head: NIL,
+            tail: NIL,
+        }
+    }
+}
+
+impl Level {
+    fn is_empty(&self) -> bool {
+        self.head == NIL
+    }
+}
+
+/// An order in the pool of a `LadderOrderBook`.
+///
+/// # Fields
+/// * `order` - The order, with the shares it has left.
+/// * `prev` - Pool index of the order ahead of it at its level, `NIL` if it is first.
+/// * `next` - Pool index of the order behind it at its level, `NIL` if it is last.
+struct PooledOrder {
+    order: AddOrder,
+    prev: u32,
+    next: u32,
+}
+
+/// One side of a `LadderOrderBook`.
+///
+/// Prices are handled as ranks, which increase away from the best price: the price itself for asks, and
+/// `u32::MAX - price` for bids. `levels[i]` is the level `i` ticks behind the best price, so the best price is always
+/// `levels[0]` and the levels near it are found by an index instead of a search.
+///
+/// # Fields
+/// * `ascending` - True for asks, whose rank is the price.
+/// * `best_rank` - The rank of `levels[0]`. Only meaningful if `levels` is not empty.
+/// * `levels` - The ladder, at most `LADDER_TICKS` levels from the best price. Never starts or ends with an empty level.
+/// * `overflow` - Rank > level, for the levels beyond the ladder. Empty if `levels` is empty.
+struct Side {
+    ascending: bool,
+    best_rank: u32,
+    levels: VecDeque<Level>,
+    overflow: BTreeMap<u32, Level>,
+}
+
+impl Side {
+    fn new(ascending: bool) -> Self {
+        Self {
+            ascending,
+            best_rank: 0,
+            levels: VecDeque::new(),
+            overflow: BTreeMap::new(),
+        }
+    }
+
+    /// Converts a price to a rank, or a rank back to a price.
+    fn rank(&self, price: u32) -> u32 {
+        if self.ascending {
+            price
+        } else {
+            u32::MAX - price
+        }
+    }
+
+    /// The ladder index of a rank at or behind the best price, if it is within the ladder.
+    fn index(&self, rank: u32, tick: u32) -> Option<usize> {
+        if rank < self.best_rank {
+            return None;
+        }
+        let index = ((rank - self.best_rank) / tick) as usize;
+        (index < LADDER_TICKS).then_some(index)
+    }
+
+    /// Get the level at a rank, creating it if need be. The ladder is moved if the rank is ahead of the best price.
+    fn level_mut(&mut self, rank: u32, tick: u32) -> &mut Level {
+        if self.levels.is_empty() {
+            self.best_rank = rank;
+        } else if rank < self.best_rank {
+            let shift = ((self.best_rank - rank) / tick) as usize;
+            if shift >= LADDER_TICKS {
+                // The whole ladder is now beyond the new best price
+                for (index, level) in std::mem::take(&mut self.levels).into_iter().enumerate() {
+                    if !level.is_empty() {
+                        self.overflow
+                            .insert(self.best_rank + index as u32 * tick, level);
+                    }
+                }
+            } else {
+                for _ in 0..shift {
+                    self.levels.push_front(Level::default());
+                }
+                while self.levels.len() > LADDER_TICKS {
+                    let level = self.levels.pop_back().unwrap();
+                    if !level.is_empty() {
+                        let rank = rank + self.levels.len() as u32 * tick;
+                        self.overflow.insert(rank, level);
+                    }
+                }
+            }
+            self.best_rank = rank;
+        }
+        match self.index(rank, tick) {
+            Some(index) => {
+                if index >= self.levels.len() {
+                    self.levels.resize(index + 1, Level::default());
+                }
+                &mut self.levels[index]
+            }
+            None => self.overflow.entry(rank).or_default(),
+        }
+    }
+
+    fn get(&self, rank: u32, tick: u32) -> Option<&Level> {
+        match self.index(rank, tick) {
+            Some(index) => self.levels.get(index),
+            None => self.overflow.get(&rank),
+        }
+    }
+
+    fn get_mut(&mut self, rank: u32, tick: u32) -> Option<&mut Level> {
+        match self.index(rank, tick) {
+            Some(index) => self.levels.get_mut(index),
+            None => self.overflow.get_mut(&rank),
+        }
+    }
+
+    /// Drop the level at a rank if its last order has gone, moving the ladder if it was the best price.
+    fn remove_if_empty(&mut self, rank: u32, tick: u32) {
+        if self.get(rank, tick).is_none_or(|level| !level.is_empty()) {
+            return;
+        }
+        if self.index(rank, tick).is_none() {
+            self.overflow.remove(&rank);
+            return;
+        }
+        while self.levels.back().is_some_and(Level::is_empty) {
+            self.levels.pop_back();
+        }
+        while self.levels.front().is_some_and(Level::is_empty) {
+            self.levels.pop_front();
+            self.best_rank += tick;
+        }
+        if self.levels.is_empty() {
+            match self.overflow.pop_first() {
+                Some((rank, level)) => {
+                    self.best_rank = rank;
+                    self.levels.push_back(level);
+                }
+                None => return,
+            }
+        }
+        // The ladder now reaches further out, so the nearest overflow levels may fit in it
+        while let Some((&rank, _)) = self.overflow.first_key_value() {
+            let Some(index) = self.index(rank, tick) else {
+                break;
+            };
+            let level = self.overflow.remove(&rank).unwrap();
+            if index >= self.levels.len() {
+                self.levels.resize(index + 1, Level::default());
+            }
+            self.levels[index] = level;
+        }
+    }
+
+    /// The non-empty levels with their prices, best first.
+    fn iter(&self, tick: u32) -> impl Iterator<Item = (u32, &Level)> {
+        let ladder = self
+            .levels
+            .iter()
+            .enumerate()
+            .filter(|(_, level)| !level.is_empty())
+            .map(move |(index, level)| (self.rank(self.best_rank + index as u32 * tick), level));
+        let overflow = self
+            .overflow
+            .iter()
+            .map(|(&rank, level)| (self.rank(rank), level));
+        ladder.chain(overflow)
+    }
+
+    /// Take every non-empty level with its rank, leaving the side empty.
+    fn drain(&mut self, tick: u32) -> Vec<(u32, Level)> {
+        let mut levels: Vec<_> = std::mem::take(&mut self.levels)
+            .into_iter()
+            .enumerate()
+            .filter(|(_, level)| !level.is_empty())
+            .map(|(index, level)| (self.best_rank + index as u32 * tick, level))
+            .collect();
+        levels.extend(std::mem::take(&mut self.overflow));
+        levels
+    }
+}
+
+/// A limit order book tuned for ITCH order flow, where most messages land on the few price levels around the BBO.
+/// Selected with `OrderBookManager::<LadderOrderBook>::default()`.
+///
+/// Each side is a ladder of price levels indexed by their distance in ticks from the best price, so finding a level
+/// near the BBO is an index and the best price is always the first level. Levels more than `LADDER_TICKS` ticks away
+/// are kept in a `BTreeMap`. Orders live in a single pool, reused as orders leave the book, and each level links its
+/// orders in time priority through the pool, so no level allocates.
+///
+/// # Fields
+/// * `bids` - The bid side.
+/// * `asks` - The ask side.
+/// * `tick` - The price increment of the ladders, in `Price4` units. `PENNY` until an order is priced in sub-pennies.
+/// * `orders` - The order pool, `None` for free slots.
+/// * `free` - Free slots of the pool.
+/// * `index` - Order reference number > pool index.
+pub struct LadderOrderBook {
+    bids: Side,
+    asks: Side,
+    tick: u32,
+    orders: Vec<Option<PooledOrder>>,
+    free: Vec<u32>,
+    index: HashMap<OrderReferenceNumber, u32>,
+}
+
+impl Default for LadderOrderBook {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LadderOrderBook {
+    pub fn new() -> Self {
+        Self {
+            bids: Side::new(false),
+            asks: Side::new(true),
+            tick: PENNY,
+            orders: Vec::new(),
+            free: Vec::new(),
+            index: HashMap::new(),
+        }
+    }
+
+    /// Rebuild both ladders with a tick of 1, once a price is not a multiple of the current tick.
+    fn use_tick_of(&mut self, price: u32) {
+        if price.is_multiple_of(self.tick) {
+            return;
+        }
+        log::debug!("Sub-penny price {price}, rebuilding ladders with a tick of 1");
+        for side in [&mut self.bids, &mut self.asks] {
+            for (rank, level) in side.drain(self.tick) {
+                *side.level_mut(rank, 1) = level;
+            }
+        }
+        self.tick = 1;
+    }
+
+    /// Take an order out of the pool, unlinking it from the queue of its level.
+    fn remove(&mut self, slot: u32) -> AddOrder {
+        let pooled = self.orders[slot as usize].take().unwrap();
+        self.free.push(slot);
+        self.index.remove(&pooled.order.order_reference_number);
+        let side = match pooled.order.buy_sell_indicator {
+            BuySellIndicator::Buy => &mut self.bids,
+            BuySellIndicator::Sell => &mut self.asks,
+        };
+        let rank = side.rank(pooled.order.price.value);
+        let level = side.get_mut(rank, self.tick).unwrap();
+        match pooled.prev {
+            NIL => level.head = pooled.next,
+            prev => self.orders[prev as usize].as_mut().unwrap().next = pooled.next,
+        }
+        match pooled.next {
+            NIL => level.tail = pooled.prev,
+            next => self.orders[next as usize].as_mut().unwrap().prev = pooled.prev,
+        }
+        level.share_quantity -= pooled.order.shares;
+        level.order_count -= 1;
+        side.remove_if_empty(rank, self.tick);
+        pooled.order
+    }
+
+    fn side(&self, buy_sell_indicator: BuySellIndicator) -> &Side {
+        match buy_sell_indicator {
+            BuySellIndicator::Buy => &self.bids,
+            BuySellIndicator::Sell => &self.asks,
+        }
+    }
+
+    fn depth(&self, buy_sell_indicator: BuySellIndicator, levels: usize) -> Vec<PriceLevel> {
+        self.side(buy_sell_indicator)
+            .iter(self.tick)
+            .take(levels)
+            .map(|(price, level)| PriceLevel {
+                price: Price4::new(price),
+                share_quantity: level.share_quantity,
+                order_count: level.order_count as usize,
+            })
+            .collect()
+    }
+}
+
+impl OrderBook for LadderOrderBook {
+    fn add_order(&mut self, order: AddOrder) -> Result<(), OrderBookError> {
+        if self.index.contains_key(&order.order_reference_number) {
+            return Err(OrderBookError::DuplicateOrder);
+        }
+        self.use_tick_of(order.price.value);
+        let slot = match self.free.pop() {
+            Some(slot) => slot,
+            None => {
+                self.orders.push(None);
+                (self.orders.len() - 1) as u32
+            }
+        };
+        self.index.insert(order.order_reference_number, slot);
+
+        let side = match order.buy_sell_indicator {
+            BuySellIndicator::Buy => &mut self.bids,
+            BuySellIndicator::Sell => &mut self.asks,
+        };
+        let level = side.level_mut(side.rank(order.price.value), self.tick);
+        let prev = level.tail;
+        match prev {
+            NIL => level.head = slot,
+            prev => self.orders[prev as usize].as_mut().unwrap().next = slot,
+        }
+        level.tail = slot;
+        level.share_quantity += order.shares;
+        level.order_count += 1;
+        self.orders[slot as usize] = Some(PooledOrder {
+            order,
+            prev,
+            next: NIL,
+        });
+        Ok(())
+    }
+
+    /// The price and side are read from the pooled order, rather than trusted.
+    fn cancel_order(
+        &mut self,
+        order_reference_number: OrderReferenceNumber,
+        cancelled_shares: u32,
+        _price: Price4,
+        _buy_sell_indicator: BuySellIndicator,
+        order_cancellation: bool,
+    ) -> Result<u32, OrderBookError> {
+        let &slot = self
+            .index
+            .get(&order_reference_number)
+            .ok_or(OrderBookError::NonExistentOrder)?;
+        let order = &mut self.orders[slot as usize].as_mut().unwrap().order;
+        if cancelled_shares > order.shares {
+            return Err(OrderBookError::InvalidCancellation);
+        }
+        order.shares -= cancelled_shares;
+        let remaining_shares = order.shares;
+        let (price, buy_sell_indicator) = (order.price.value, order.buy_sell_indicator);
+
+        let side = match buy_sell_indicator {
+            BuySellIndicator::Buy => &mut self.bids,
+            BuySellIndicator::Sell => &mut self.asks,
+        };
+        let level = side.get_mut(side.rank(price), self.tick).unwrap();
+        level.share_quantity -= cancelled_shares;
+
+        if remaining_shares == 0 {
+            if order_cancellation {
+                log::warn!("Order cancellation resulted in 0 shares, deleting order");
+            }
+            self.remove(slot);
+        }
+        Ok(remaining_shares)
+    }
+
+    /// The price and side are read from the pooled order, rather than trusted.
+    fn delete_order(
+        &mut self,
+        order_reference_number: OrderReferenceNumber,
+        _price: Price4,
+        _buy_sell_indicator: BuySellIndicator,
+    ) -> Result<AddOrder, OrderBookError> {
+        let &slot = self
+            .index
+            .get(&order_reference_number)
+            .ok_or(OrderBookError::NonExistentOrder)?;
+        Ok(self.remove(slot))
+    }
+
+    fn get_best_bid(&self) -> Option<u32> {
+        self.bids.iter(self.tick).next().map(|(price, _)| price)
+    }
+
+    fn get_best_ask(&self) -> Option<u32> {
+        self.asks.iter(self.tick).next().map(|(price, _)| price)
+    }
+
+    fn get_best_bid_size(&self) -> u32 {
+        self.bids
+            .levels
+            .front()
+            .map_or(0, |level| level.share_quantity)
+    }
+
+    fn get_best_ask_size(&self) -> u32 {
+        self.asks
+            .levels
+            .front()
+            .map_or(0, |level| level.share_quantity)
+    }
+
+    fn get_depth(&self, levels: usize) -> Depth {
+        Depth {
+            bids: self.depth(BuySellIndicator::Buy, levels),
+            asks: self.depth(BuySellIndicator::Sell, levels),
+        }
+    }
+
+    fn queue_position(
+        &self,
+        order_reference_number: OrderReferenceNumber,
+        _price: Price4,
+        _buy_sell_indicator: BuySellIndicator,
+    ) -> Option<QueuePosition> {
+        let mut slot = *self.index.get(&order_reference_number)?;
+        let mut position = QueuePosition {
+            orders_ahead: 0,
+            shares_ahead: 0,
+        };
+        loop {
+            let prev = self.orders[slot as usize].as_ref().unwrap().prev;
+            if prev == NIL {
+                return Some(position);
+            }
+            let order = &self.orders[prev as usize].as_ref().unwrap().order;
+            position.orders_ahead += 1;
+            position.shares_ahead += order.shares;
+            slot = prev;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enums::MessageTypes;
+    use crate::events::EventSink;
+    use crate::messageheader::MessageHeader;
+    use crate::modifyordermessages::{OrderCancel, OrderDelete, OrderExecuted, OrderReplace};
+    use crate::orderbook::{LimitOrderBook, OrderBookManager};
+    use crate::types::GenerateExampleMessage;
+
+    /// A random walk of order flow around a moving mid price, with some orders far from it and some in sub-pennies.
+    fn order_flow(rng: &mut fastrand::Rng, messages: usize, sub_penny: bool) -> Vec<MessageTypes> {
+        let header = MessageHeader::parse(&MessageHeader::generate_binary_example());
+        let mut mid: u32 = 50_0000;
+        let mut live: Vec<u64> = Vec::new();
+        let mut next_order_reference_number = 1;
+        let mut flow = Vec::new();
+        for _ in 0..messages {
+            mid = mid.saturating_add_signed(rng.i32(-300..=300) / 100 * 100);
+            let action = if live.is_empty() { 0 } else { rng.u8(0..10) };
+            let order_reference_number = if live.is_empty() {
+                0
+            } else {
+                live[rng.usize(..live.len())]
+            };
+            let message = match action {
+                0..=4 => {
+                    let buy_sell_indicator = if rng.bool() {
+                        BuySellIndicator::Buy
+                    } else {
+                        BuySellIndicator::Sell
+                    };
+                    let ticks = match rng.u8(0..20) {
+                        0 => rng.u32(1500..3000), // Beyond the ladder
+                        _ => rng.u32(0..30),
+                    };
+                    let offset = ticks * PENNY
+                        + if sub_penny && rng.u8(0..50) == 0 {
+                            rng.u32(1..PENNY)
+                        } else {
+                            0
+                        };
+                    let price = match buy_sell_indicator {
+                        BuySellIndicator::Buy => mid.saturating_sub(offset).max(1),
+                        BuySellIndicator::Sell => mid + offset,
+                    };
+                    live.push(next_order_reference_number);
+                    next_order_reference_number += 1;
+                    MessageTypes::AddOrder(AddOrder {
+                        header: header.clone(),
+                        order_reference_number: next_order_reference_number - 1,
+                        buy_sell_indicator,
+                        shares: rng.u32(1..10) * 100,
+                        stock: *b"AAPL    ",
+                        price: Price4::new(price),
+                        mpid: None,
+                    })
+                }
+                5 => MessageTypes::OrderCancel(OrderCancel {
+                    header: header.clone(),
+                    order_reference_number,
+                    canceled_shares: 100,
+                }),
+                6 => MessageTypes::OrderExecuted(OrderExecuted {
+                    header: header.clone(),
+                    order_reference_number,
+                    executed_shares: 100,
+                    match_number: 0,
+                }),
+                7 => {
+                    live.push(next_order_reference_number);
+                    next_order_reference_number += 1;
+                    MessageTypes::OrderReplace(OrderReplace {
+                        header: header.clone(),
+                        original_order_reference_number: order_reference_number,
+                        new_order_reference_number: next_order_reference_number - 1,
+                        shares: rng.u32(1..10) * 100,
+                        price: Price4::new(mid + rng.u32(0..10) * PENNY),
+                    })
+                }
+                _ => MessageTypes::OrderDelete(OrderDelete {
+                    header: header.clone(),
+                    order_reference_number,
+                }),
+            };
+            flow.push(message);
+        }
+        flow
+    }
+
+    fn assert_same_books(flow: &[MessageTypes]) {
+        let mut btree = OrderBookManager::<LimitOrderBook>::default();
+        let mut ladder = OrderBookManager::<LadderOrderBook>::default();
+        let mut btree_events = VecDeque::new();
+        let mut ladder_events = VecDeque::new();
+        for (i, message) in flow.iter().enumerate() {
+            btree.on_message(message, &mut btree_events);
+            ladder.on_message(message, &mut ladder_events);
+            assert_eq!(ladder_events, btree_events, "Events differ at message {i}");
+            let btree_book = btree.order_books.values().next().unwrap();
+            let ladder_book = ladder.order_books.values().next().unwrap();
+            assert_eq!(
+                OrderBook::get_depth(ladder_book, usize::MAX),
+                OrderBook::get_depth(btree_book, usize::MAX),
+                "Depth differs at message {i}"
+            );
+            btree_events.clear();
+            ladder_events.clear();
+        }
+        for order_reference_number in 1..=flow.len() as u64 {
+            assert_eq!(
+                ladder.get_queue_position(order_reference_number),
+                btree.get_queue_position(order_reference_number)
+            );
+        }
+    }
+
+    #[test]
+    fn test_ladder_overflow() {
+        let header = MessageHeader::example(1, 0);
+        let add = |order_reference_number, price| {
+            MessageTypes::AddOrder(AddOrder::example(
+                1,
+                0,
+                order_reference_number,
+                BuySellIndicator::Buy,
+                price,
+            ))
+        };
+        let delete = |order_reference_number| {
+            MessageTypes::OrderDelete(OrderDelete {
+                header: header.clone(),
+                order_reference_number,
+            })
+        };
+        // The best bid leaves, so the overflow levels become the ladder
+        assert_same_books(&[
+            add(1, 50_0000),
+            add(2, 20_0000),
+            add(3, 19_9900),
+            add(4, 15_0000),
+            delete(1),
+            add(5, 19_9800),
+            delete(2),
+            delete(3),
+            delete(5),
+            delete(4),
+            add(6, 20_0000),
+        ]);
+    }
+
+    #[test]
+    fn test_ladder_matches_limit_order_book() {
+        let mut rng = fastrand::Rng::with_seed(7);
+        assert_same_books(&order_flow(&mut rng, 5000, false));
+        assert_same_books(&order_flow(&mut rng, 5000, true));
+    }
+}
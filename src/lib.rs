@@ -1,7 +1,11 @@
 pub mod addordermessages;
+pub mod decoder;
+pub mod encoder;
 pub mod enums;
+pub mod events;
 pub mod helpers;
 pub mod messageheader;
+pub mod mmapreader;
 pub mod modifyordermessages;
 pub mod noiimessages;
 pub mod stockmessages;
@@ -9,5 +13,25 @@ pub mod systemmessages;
 pub mod trademessages;
 pub mod types;
 
+pub mod auction;
+pub mod bbo;
+pub mod bookview;
+pub mod depth;
+pub mod filter;
+pub mod ipo;
+pub mod ladder;
 pub mod orderbook;
+#[cfg(any(test, feature = "bench"))]
+pub mod orderflow;
+pub mod pacer;
+pub mod participants;
+pub mod session;
+pub mod shard;
+pub mod snapshot;
 pub mod stockdirectory;
+pub mod tradetape;
+pub mod tradingstate;
+
+#[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
+mod tests;
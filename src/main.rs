@@ -1,420 +1,1094 @@
 #![feature(test)]
 extern crate test;
 
+use crate::cli::{Cli, OutputMode};
 use crate::tui::run;
 use byteorder::{BigEndian, ByteOrder};
-use orderbook::OrderBookManager;
+use fastasx::auction::AuctionTracker;
+use fastasx::bbo::BboWriter;
+use fastasx::bookview::{BookPublisher, PublishedBook};
+use fastasx::decoder::{self, LENGTH_PREFIX_SIZE};
+use fastasx::depth::DepthWriter;
+use fastasx::enums::{MessageTypes, SystemEventCode};
+use fastasx::events::{Dispatcher, Event, EventSink, HaltDetector};
+use fastasx::filter::SymbolFilter;
+use fastasx::helpers;
+use fastasx::ipo::IPOTracker;
+use fastasx::mmapreader::MmapReader;
+use fastasx::orderbook::{order_message_header, OrderBookManager};
+use fastasx::pacer::Pacer;
+use fastasx::participants::ParticipantTracker;
+use fastasx::session::SessionTracker;
+use fastasx::shard::{self, ShardedOrderBookManager};
+use fastasx::snapshot::{read_snapshot, SnapshotWriter};
+use fastasx::stockdirectory::StockDirectoryManager;
+use fastasx::tradetape::TradeTapeManager;
+use fastasx::tradingstate::TradingStateManager;
+use fastasx::types::{ParseError, Price4, PriceConversions, Stock};
 use ringbuf::{traits::*, HeapRb};
+use std::collections::VecDeque;
 use std::fs::File;
-use std::io::{self, Read};
-use std::sync::atomic::AtomicBool;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, RwLock};
-use types::{
-    AltBinaryMessageLength, AltMessageHeaderType, BinaryMessageLength, MessageHeaderType, Parse,
-    ParseError,
-};
-
-#[cfg(any(test, feature = "bench"))]
-mod tests;
-
-pub mod addordermessages;
-pub mod enums;
-pub mod helpers;
-pub mod messageheader;
-pub mod modifyordermessages;
-pub mod noiimessages;
-pub mod orderbook;
-pub mod stockdirectory;
-pub mod stockmessages;
-pub mod systemmessages;
-pub mod trademessages;
-pub mod tui;
-pub mod types;
+use std::time::{Duration, Instant};
+
+mod cli;
+mod tui;
 
 const FILE_BUFFER_SIZE: usize = 2048 * 64; // Stack allocated
 const RING_BUFFER_SIZE: usize = 4096 * 2048; // Heap allocated
+const TUI_REFRESH_INTERVAL: Duration = Duration::from_millis(50); // Twice per TUI poll
 
-/// Parses a message of fixed length N.
-/// If the message is not complete, it will attempt to parse an incomplete message.
-/// If the message is not parseable, it will return an error.
+/// Pops exactly `buffer.len()` bytes from the ring buffer, waiting for the producer if they are not available yet.
 ///
 /// # Arguments
 /// * `consumer` - A consumer of bytes - RingBuf crate
-/// * `N` - The fixed length of the message. In the case of ITCH, this is present in the SoupBIN TCP header.
-/// * `T` - The type of message to parse
-/// * `C` - The type of consumer
+/// * `buffer` - The buffer to fill.
+/// * `producer_done` - Set by the producer once it has pushed the whole file.
 ///
 /// # Returns
-/// * `Result<T, ParseError>` - The parsed message, or an error if the message is not parseable
-fn parse_fixed_length_message<const N: usize, T, C>(consumer: &mut C) -> Result<T, ParseError>
+/// * `usize` - The number of bytes popped. Less than `buffer.len()` only if the file ended first.
+fn pop_exact<C>(consumer: &mut C, buffer: &mut [u8], producer_done: &AtomicBool) -> usize
 where
     C: Consumer<Item = u8>,
-    T: Parse + BinaryMessageLength,
 {
-    let mut buffer = [0u8; N]; // N is the fixed length of the message
-    let bytes_read = consumer.pop_slice(&mut buffer);
+    let mut filled = 0;
+    while filled < buffer.len() {
+        // The producer pushes before flagging completion, so a short pop after seeing the flag is final
+        let done = producer_done.load(Ordering::Acquire);
+        filled += consumer.pop_slice(&mut buffer[filled..]);
+        if done && filled < buffer.len() {
+            break;
+        }
+    }
+    filled
+}
+
+/// Statistics collected while processing a single input file.
+///
+/// # Fields
+/// * `message_counts` - Messages seen, indexed by message type byte.
+/// * `unknown_counts` - Messages of an unknown type, skipped by their declared length. Indexed by message type byte.
+/// * `invalid_counts` - Messages of a known type that failed to parse and were skipped. Indexed by message type byte.
+/// * `resyncs` - Times corrupt framing was detected and resynchronised, see `--resync`.
+/// * `resync_bytes` - Bytes skipped while resynchronising.
+/// * `executions` - Executions against resting orders, from 'E' and 'C' messages.
+/// * `executed_shares` - Shares executed against resting orders.
+/// * `warmup_messages` - Messages before `--start`, only used to build the books and stock directory.
+/// * `restored_messages` - Order messages before the `--restore` snapshot was taken, skipped as the snapshot has them.
+/// * `filtered_messages` - Messages of stocks outside `--filter` and `--stock-locates`, skipped before parsing.
+/// * `total_messages` - Messages parsed and applied.
+/// * `gigabytes_read` - Bytes read from the file, in GB.
+/// * `elapsed` - Wall clock time taken to process the file.
+struct FileStats {
+    message_counts: [u64; 256],
+    unknown_counts: [u64; 256],
+    invalid_counts: [u64; 256],
+    resyncs: u64,
+    resync_bytes: u64,
+    executions: u64,
+    executed_shares: u64,
+    warmup_messages: u64,
+    restored_messages: u64,
+    filtered_messages: u64,
+    total_messages: u64,
+    gigabytes_read: f64,
+    elapsed: Duration,
+}
 
-    // Only parse incomplete when the message buffer length is less than the message length
-    if bytes_read < N {
-        let result = parse_incomplete_message::<T, C, N>(consumer, &mut buffer, bytes_read)?;
-        return Ok(result);
+impl FileStats {
+    fn new() -> Self {
+        Self {
+            message_counts: [0; 256],
+            unknown_counts: [0; 256],
+            invalid_counts: [0; 256],
+            resyncs: 0,
+            resync_bytes: 0,
+            executions: 0,
+            executed_shares: 0,
+            warmup_messages: 0,
+            restored_messages: 0,
+            filtered_messages: 0,
+            total_messages: 0,
+            gigabytes_read: 0.0,
+            elapsed: Duration::ZERO,
+        }
+    }
+}
+
+/// Counts the `Execution` events emitted by the order book, for `FileStats`.
+#[derive(Default)]
+struct ExecutionCounter {
+    executions: u64,
+    executed_shares: u64,
+}
+
+impl EventSink for ExecutionCounter {
+    fn on_event(&mut self, event: &Event, _events: &mut VecDeque<Event>) {
+        match event {
+            Event::Execution(execution) => {
+                log::trace!("Execution {execution:?}");
+                self.executions += 1;
+                self.executed_shares += u64::from(execution.executed_shares);
+            }
+            Event::Halt(halt) => log::debug!("Halt {halt:?}"),
+            Event::Session(change) => log::info!(
+                "{:?} at {}",
+                change.session,
+                helpers::format_time_of_day(change.timestamp)
+            ),
+            _ => {}
+        }
     }
-    let result = T::parse(&buffer)?;
-    Ok(result)
 }
 
-/// Parses an incomplete message of fixed length N.
-/// If the message is not complete, it will attempt to parse an incomplete message.
-/// If the message is not parseable, it will return an error.
+/// Builds the books of a `ShardedOrderBookManager` from the messages before `--start`, dropping the events they derive.
+struct ShardedWarmup(Arc<RwLock<ShardedOrderBookManager>>);
+
+impl EventSink for ShardedWarmup {
+    fn on_message(&mut self, message: &MessageTypes, _events: &mut VecDeque<Event>) {
+        self.0.write().unwrap().route(message, false);
+    }
+}
+
+/// Forwards messages but not events to a sink. For sinks that measure against the `TopOfBook` events of the books as
+/// each message is applied, which `--shards` emits late: `ParticipantTracker` still counts attributed orders and their
+/// executions from the messages, but not the time at the inside.
+struct MessagesOnly<S>(S);
+
+impl<S: EventSink> EventSink for MessagesOnly<S> {
+    fn on_message(&mut self, message: &MessageTypes, events: &mut VecDeque<Event>) {
+        self.0.on_message(message, events);
+    }
+}
+
+/// The CSV streams written while replaying, shared by every input file.
 ///
-/// # Arguments
-/// * `consumer` - A consumer of bytes - RingBuf crate
-/// * `N` - The fixed length of the message. This is used to determine the number of bytes required to parse the message.
-/// * `T` - The type of message to parse
-/// * `C` - The type of consumer
-fn parse_incomplete_message<T, C, const N: usize>(
-    consumer: &mut C,
-    message_buffer: &mut [u8; N],
-    mut message_buffer_len: usize,
-) -> Result<T, ParseError>
-where
-    C: Consumer<Item = u8>,
-    T: Parse,
-{
-    loop {
-        let remaining_required_bytes = N - message_buffer_len;
-        if remaining_required_bytes == 0 {
-            // No more bytes required
-            break;
+/// # Fields
+/// * `bbo` - Top of book changes, see `--bbo`.
+/// * `depth` - Depth snapshots, see `--depth`.
+struct StreamWriters {
+    bbo: Option<Arc<RwLock<BboWriter<BufWriter<File>>>>>,
+    depth: Option<Arc<RwLock<DepthWriter<BufWriter<File>>>>>,
+}
+
+impl StreamWriters {
+    /// Creates the files requested on the command line.
+    fn new(cli: &Cli) -> Result<Self, io::Error> {
+        let bbo = match &cli.bbo {
+            Some(path) => Some(Arc::new(RwLock::new(BboWriter::new(
+                BufWriter::new(File::create(path)?),
+                cli.symbols.clone(),
+            )?))),
+            None => None,
+        };
+        let depth = match &cli.depth {
+            Some(path) => Some(Arc::new(RwLock::new(DepthWriter::new(
+                BufWriter::new(File::create(path)?),
+                cli.depth_levels,
+                cli.depth_schedule(),
+                cli.symbols.clone(),
+            )?))),
+            None => None,
+        };
+        Ok(Self { bbo, depth })
+    }
+
+    /// Registers the writers with the dispatcher of a file, after resetting their per-day state.
+    /// Must be called before the order book manager is registered, as depth snapshots are taken between messages.
+    fn add_sinks(
+        &self,
+        dispatcher: &mut Dispatcher,
+        order_book_manager: &Arc<RwLock<OrderBookManager>>,
+    ) {
+        if let Some(bbo) = &self.bbo {
+            bbo.write().unwrap().reset();
+            dispatcher.add_sink(Arc::clone(bbo));
         }
+        if let Some(depth) = &self.depth {
+            depth.write().unwrap().reset(Arc::clone(order_book_manager));
+            dispatcher.add_sink(Arc::clone(depth));
+        }
+    }
 
-        let bytes_read = consumer.pop_slice(
-            &mut message_buffer[message_buffer_len..remaining_required_bytes + message_buffer_len],
-        );
-        message_buffer_len += bytes_read;
+    fn flush(&self) -> Result<(), io::Error> {
+        if let Some(bbo) = &self.bbo {
+            bbo.write().unwrap().flush()?;
+        }
+        if let Some(depth) = &self.depth {
+            depth.write().unwrap().flush()?;
+        }
+        Ok(())
     }
-    T::parse(&message_buffer[..N])
 }
 
-#[allow(unused_variables)]
+/// The state left behind once a file has been processed.
+struct FileRun {
+    order_book_manager: Arc<RwLock<OrderBookManager>>,
+    stock_directory_manager: Arc<RwLock<StockDirectoryManager>>,
+    trade_tape_manager: Arc<RwLock<TradeTapeManager>>,
+    trading_state_manager: Arc<RwLock<TradingStateManager>>,
+    session_tracker: Arc<RwLock<SessionTracker>>,
+    auction_tracker: Arc<RwLock<AuctionTracker>>,
+    participant_tracker: Arc<RwLock<ParticipantTracker>>,
+    ipo_tracker: Arc<RwLock<IPOTracker>>,
+    stats: FileStats,
+}
+
 pub fn main() -> Result<(), io::Error> {
-    env_logger::init();
+    let cli = Cli::parse_and_validate();
 
-    let mut file = File::open("/home/luke/fastasx/data/12302019.NASDAQ_ITCH50")?;
+    let mut logger = env_logger::Builder::from_default_env();
+    if let Some(level) = cli.log_level {
+        logger.filter_level(level);
+    }
+    logger.init();
+    if cli.shards.is_some() {
+        log::warn!("The time each MPID spends at the inside is not tracked with --shards");
+    }
 
-    let rb = HeapRb::<u8>::new(RING_BUFFER_SIZE); // Ringbuffer
-    let (mut producer, mut consumer) = rb.split();
+    let mut export_writer: Option<Box<dyn Write>> = match cli.mode {
+        OutputMode::Export => {
+            let mut writer: Box<dyn Write> = match &cli.output {
+                Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+                None => Box::new(BufWriter::new(io::stdout())),
+            };
+            writeln!(
+                writer,
+                "file,stock,stock_locate,best_bid,best_ask,bid_levels,ask_levels,bid_shares,ask_shares,trades,volume,vwap"
+            )?;
+            Some(writer)
+        }
+        _ => None,
+    };
 
-    let mut consumer_slice_size = [0u8; 3];
+    let stream_writers = StreamWriters::new(&cli)?;
 
-    let mut msg_ct: u64 = 0;
-    let mut last_million_time = std::time::Instant::now();
+    for path in &cli.inputs {
+        log::info!("Processing {}", path.display());
+        let file_run = process_file(path, &cli, &stream_writers)?;
+        match cli.mode {
+            OutputMode::Tui => {}
+            OutputMode::Headless => print_stats(path, &cli, &file_run),
+            OutputMode::Export => {
+                if let Some(writer) = export_writer.as_mut() {
+                    export_books(writer, path, &cli, &file_run)?;
+                }
+            }
+        }
+    }
 
-    let producer_done = AtomicBool::new(false);
+    if let Some(mut writer) = export_writer {
+        writer.flush()?;
+    }
+    stream_writers.flush()?;
+    Ok(())
+}
+
+/// An opened input file.
+///
+/// # Variants
+/// * `Streamed` - Read through the ring buffer by a producer thread.
+/// * `Mapped` - Memory mapped and parsed in place, see `--mmap`.
+enum Input {
+    Streamed(File),
+    Mapped(MmapReader),
+}
 
-    let order_book_manager = Arc::new(RwLock::new(OrderBookManager::new()));
-    let order_book_manager_clone = Arc::clone(&order_book_manager);
+/// Replays a single ITCH file through the order book and stock directory managers.
+/// The TUI is started alongside the parser when `cli.mode` is `OutputMode::Tui`.
+/// Top of book changes and depth snapshots are written to `stream_writers`.
+fn process_file(
+    path: &Path,
+    cli: &Cli,
+    stream_writers: &StreamWriters,
+) -> Result<FileRun, io::Error> {
+    let start_time = Instant::now();
+    let input = if cli.mmap {
+        Input::Mapped(MmapReader::open(path)?)
+    } else {
+        Input::Streamed(File::open(path)?)
+    };
 
-    let stock_directory_manager = Arc::new(RwLock::new(stockdirectory::StockDirectoryManager::new()));
-    let stock_directory_manager_clone = Arc::clone(&stock_directory_manager);
+    let (restored_at, mut order_book_manager) = match &cli.restore {
+        Some(path) => {
+            let (timestamp, order_book_manager) = read_snapshot(BufReader::new(File::open(path)?))
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            log::info!(
+                "Restored {} order books at {}",
+                order_book_manager.order_books.len(),
+                helpers::format_time_of_day(timestamp)
+            );
+            (Some(timestamp), order_book_manager)
+        }
+        None => (None, OrderBookManager::new()),
+    };
+    let window = ReplayWindow {
+        start: cli.start.or(restored_at).unwrap_or(0),
+        end: cli.end.unwrap_or(u64::MAX),
+        restored_at,
+    };
+    if restored_at.is_some_and(|restored_at| window.start < restored_at) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "--start is before the time the --restore snapshot was taken",
+        ));
+    }
+
+    // The shards take over the restored books, which are merged back in once the replay is done
+    let sharded_order_book_manager = cli.shards.map(|shards| {
+        Arc::new(RwLock::new(ShardedOrderBookManager::new(
+            std::mem::take(&mut order_book_manager),
+            usize::from(shards),
+        )))
+    });
+    let order_book_manager = Arc::new(RwLock::new(order_book_manager));
+    let stock_directory_manager = Arc::new(RwLock::new(StockDirectoryManager::new()));
+    let trade_tape_manager = Arc::new(RwLock::new(TradeTapeManager::new()));
+    let trading_state_manager = Arc::new(RwLock::new(TradingStateManager::new()));
+    let session_tracker = Arc::new(RwLock::new(SessionTracker::new()));
+    let auction_tracker = Arc::new(RwLock::new(AuctionTracker::new()));
+    let participant_tracker = Arc::new(RwLock::new(ParticipantTracker::new()));
+    let ipo_tracker = Arc::new(RwLock::new(IPOTracker::new()));
+    let execution_counter = Arc::new(RwLock::new(ExecutionCounter::default()));
+
+    // The snapshot must see the books before the order book applies the message that reaches the snapshot time
+    let snapshot_writer = match (&cli.snapshot, cli.snapshot_at) {
+        (Some(path), Some(timestamp)) => Some(Arc::new(RwLock::new(SnapshotWriter::new(
+            BufWriter::new(File::create(path)?),
+            timestamp,
+            Arc::clone(&order_book_manager),
+        )))),
+        _ => None,
+    };
+
+    // Order messages before the time window only build the books
+    let mut warmup = Dispatcher::new();
+    if let Some(snapshot_writer) = &snapshot_writer {
+        warmup.add_sink(Arc::clone(snapshot_writer));
+    }
+    match &sharded_order_book_manager {
+        Some(sharded) => warmup.add_sink(ShardedWarmup(Arc::clone(sharded))),
+        None => warmup.add_sink(Arc::clone(&order_book_manager)),
+    }
+    // Crossing trades before the time window only close the auctions and IPOs their imbalances and updates opened
+    warmup.add_sink(Arc::clone(&auction_tracker));
+    warmup.add_sink(Arc::clone(&ipo_tracker));
+
+    // The TUI draws copies of its book, published as the books are built
+    let tui_stock = cli.symbols.first().copied().unwrap_or(*b"AAPL    ");
+    let book_publisher = (cli.mode == OutputMode::Tui).then(|| {
+        Arc::new(RwLock::new(BookPublisher::new(
+            Arc::clone(&order_book_manager),
+            tui_stock,
+            TUI_REFRESH_INTERVAL,
+        )))
+    });
+    if let Some(book_publisher) = &book_publisher {
+        warmup.add_sink(Arc::clone(book_publisher));
+    }
+
+    // The writers that read the books between messages go first, then the order book, which emits the executions
+    // the trade tape is built from
+    let mut dispatcher = Dispatcher::new();
+    if let Some(snapshot_writer) = &snapshot_writer {
+        dispatcher.add_sink(Arc::clone(snapshot_writer));
+    }
+    stream_writers.add_sinks(&mut dispatcher, &order_book_manager);
+    match &sharded_order_book_manager {
+        Some(sharded) => dispatcher.add_sink(Arc::clone(sharded)),
+        None => dispatcher.add_sink(Arc::clone(&order_book_manager)),
+    }
+    if let Some(book_publisher) = &book_publisher {
+        dispatcher.add_sink(Arc::clone(book_publisher));
+    }
+    dispatcher.add_sink(Arc::clone(&stock_directory_manager));
+    dispatcher.add_sink(Arc::clone(&trade_tape_manager));
+    dispatcher.add_sink(Arc::clone(&trading_state_manager));
+    dispatcher.add_sink(Arc::clone(&session_tracker));
+    dispatcher.add_sink(Arc::clone(&auction_tracker));
+    match &sharded_order_book_manager {
+        Some(_) => dispatcher.add_sink(MessagesOnly(Arc::clone(&participant_tracker))),
+        None => dispatcher.add_sink(Arc::clone(&participant_tracker)),
+    }
+    dispatcher.add_sink(Arc::clone(&ipo_tracker));
+    dispatcher.add_sink(HaltDetector::new());
+    dispatcher.add_sink(Arc::clone(&execution_counter));
+    let mut replay = Replay::new(dispatcher, warmup, window, cli.speed.map(Pacer::new));
+    if let Some(filter) = cli.symbol_filter() {
+        replay.set_filter(filter, Arc::clone(&stock_directory_manager));
+    }
+    if let Some(sharded) = &sharded_order_book_manager {
+        replay.set_shards(Arc::clone(sharded));
+    }
+
+    std::thread::scope(|s| -> Result<(), io::Error> {
+        let tui_handle = book_publisher.as_ref().map(|book_publisher| {
+            let published_book = book_publisher.read().unwrap().published();
+            s.spawn(move || run_tui(published_book, tui_stock))
+        });
+
+        let replay_result = match input {
+            Input::Mapped(reader) => {
+                replay.stats.gigabytes_read = reader.len() as f64 / 1024.0 / 1024.0 / 1024.0;
+                replay_mmap(&reader, &mut replay, cli.resync);
+                Ok(())
+            }
+            Input::Streamed(file) => replay_ring(file, &mut replay, cli.resync)
+                .map(|gigabytes_read| replay.stats.gigabytes_read = gigabytes_read),
+        };
+
+        if let Some(book_publisher) = &book_publisher {
+            book_publisher.write().unwrap().flush();
+        }
+        if let Some(tui_handle) = tui_handle {
+            tui_handle.join().expect("tui thread panicked")?;
+        }
+        replay_result
+    })?;
+
+    if let Some(sharded) = sharded_order_book_manager {
+        let (books, events) = sharded.write().unwrap().finish();
+        for event in events {
+            replay.dispatcher.dispatch_event(event);
+        }
+        *order_book_manager.write().unwrap() = books;
+    }
+    if let Some(snapshot_writer) = snapshot_writer {
+        snapshot_writer.write().unwrap().finish()?;
+    }
+    let mut stats = replay.stats;
+    let execution_counter = execution_counter.read().unwrap();
+    stats.executions = execution_counter.executions;
+    stats.executed_shares = execution_counter.executed_shares;
+    stats.elapsed = start_time.elapsed();
+    Ok(FileRun {
+        order_book_manager,
+        stock_directory_manager,
+        trade_tape_manager,
+        trading_state_manager,
+        session_tracker,
+        auction_tracker,
+        participant_tracker,
+        ipo_tracker,
+        stats,
+    })
+}
+
+/// Replays a file by streaming it through a ring buffer.
+/// A producer thread reads the file into the ring buffer, while the current thread frames and parses messages.
+///
+/// # Arguments
+/// * `file` - The file to replay.
+/// * `replay` - Applies the parsed messages.
+/// * `resync` - Whether to resynchronise on corrupt framing.
+///
+/// # Returns
+/// * `Ok(f64)` - The number of GB read.
+/// * `Err(io::Error)` - If the file could not be read.
+fn replay_ring(mut file: File, replay: &mut Replay, resync: bool) -> Result<f64, io::Error> {
+    let file_length = file.metadata()?.len();
+    let rb = HeapRb::<u8>::new(RING_BUFFER_SIZE); // Ringbuffer
+    let (mut producer, mut consumer) = rb.split();
+    let producer_done = AtomicBool::new(false);
+    let stop = AtomicBool::new(false);
 
     std::thread::scope(|s| {
-        s.spawn(|| -> Result<(), io::Error> {
+        let producer_handle = s.spawn(|| -> Result<f64, io::Error> {
             let mut file_buffer = [0u8; FILE_BUFFER_SIZE];
             let mut total_bytes_read: f64 = 0.0;
 
-            loop {
+            let result = loop {
+                if stop.load(Ordering::Acquire) {
+                    break Ok(total_bytes_read);
+                }
                 if producer.vacant_len() < (RING_BUFFER_SIZE as f64 * 0.1) as usize {
+                    // Let the consumer catch up, it may be paced or waiting on the TUI lock
+                    std::thread::sleep(Duration::from_millis(1));
                     continue;
                 }
-                let bytes_read = file.read(&mut file_buffer)?;
+                let bytes_read = match file.read(&mut file_buffer) {
+                    Ok(bytes_read) => bytes_read,
+                    Err(e) => break Err(e),
+                };
                 total_bytes_read += bytes_read as f64 / 1024.0 / 1024.0 / 1024.0;
 
                 if bytes_read == 0 {
-                    log::info!("End of file");
-                    break;
+                    break Ok(total_bytes_read);
                 }
                 producer.push_slice(&file_buffer[..bytes_read]);
-            }
-            producer_done.store(true, std::sync::atomic::Ordering::Relaxed);
-            println!("EOF, Producer done: {total_bytes_read:.2}gb");
-            Ok(())
+            };
+            // Always flag completion, otherwise the consumer waits forever on a read error
+            producer_done.store(true, Ordering::Release);
+            log::info!("EOF, Producer done: {total_bytes_read:.2}gb");
+            result
         });
-        s.spawn(|| -> Result<(), io::Error> {
-            while producer_done.load(std::sync::atomic::Ordering::Relaxed) == false {
-                // This prevents the consumption of only the message header (len + type) if the producer is too slow to push a whole message
-                if consumer.occupied_len() < consumer_slice_size.len() {
-                    continue;
+
+        let mut header = [0u8; LENGTH_PREFIX_SIZE + 1];
+        let mut body = vec![0u8; u16::MAX as usize];
+        // Byte offset of the current frame, to check that frames end within the file
+        let mut position: u64 = 0;
+        let fits = |header: &[u8], position: u64| {
+            position + (LENGTH_PREFIX_SIZE as u64) + u64::from(BigEndian::read_u16(header))
+                <= file_length
+        };
+        loop {
+            let popped = pop_exact(
+                &mut consumer,
+                &mut header[..LENGTH_PREFIX_SIZE],
+                &producer_done,
+            );
+            if popped < LENGTH_PREFIX_SIZE {
+                if popped > 0 {
+                    log::warn!("File ends with a truncated length prefix");
                 }
+                break;
+            }
+            if !resync && BigEndian::read_u16(&header) == 0 {
+                log::warn!("Skipping zero length frame");
+                position += LENGTH_PREFIX_SIZE as u64;
+                continue;
+            }
+            if pop_exact(
+                &mut consumer,
+                &mut header[LENGTH_PREFIX_SIZE..],
+                &producer_done,
+            ) == 0
+            {
+                log::warn!("File ends with a truncated message");
+                break;
+            }
 
-                consumer.pop_slice(&mut consumer_slice_size);
-                let length = BigEndian::read_u16(&consumer_slice_size[0..2]);
-                if length > 50 {
-                    log::warn!("Message length too long: {:?}", length);
-                    continue;
-                };
-                match &consumer_slice_size[2] {
-                    &addordermessages::AddOrder::MESSAGE_TYPE => {
-                        let order = parse_fixed_length_message::<
-                            { addordermessages::AddOrder::LENGTH },
-                            addordermessages::AddOrder,
-                            _,
-                        >(&mut consumer)
-                        .expect("msg parse failed");
-                        order_book_manager
-                            .write()
-                            .unwrap()
-                            .add_order(order)
-                            .expect("order book add failed");
-                        msg_ct += 1;
-                        log::trace!("Parsed AddOrder");
-                    }
-                    &addordermessages::AddOrder::ALT_MESSAGE_TYPE => {
-                        let order = parse_fixed_length_message::<
-                            { addordermessages::AddOrder::ALT_LENGTH },
-                            addordermessages::AddOrder,
-                            _,
-                        >(&mut consumer)
-                        .expect("msg parse failed");
-                        order_book_manager
-                            .write()
-                            .unwrap()
-                            .add_order(order)
-                            .expect("order book add failed");
-                        msg_ct += 1;
-                        log::trace!("Parsed AddOrder");
-                    }
-                    &trademessages::BrokenTrade::MESSAGE_TYPE => {
-                        parse_fixed_length_message::<
-                            { trademessages::BrokenTrade::LENGTH },
-                            trademessages::BrokenTrade,
-                            _,
-                        >(&mut consumer)
-                        .expect("msg parse failed");
-                        msg_ct += 1;
-                        log::trace!("Parsed BrokenTrade");
-                    }
-                    &modifyordermessages::OrderExecuted::MESSAGE_TYPE => {
-                        let order = parse_fixed_length_message::<
-                            { modifyordermessages::OrderExecuted::LENGTH },
-                            modifyordermessages::OrderExecuted,
-                            _,
-                        >(&mut consumer)
-                        .expect("msg parse failed");
-                        order_book_manager
-                            .write()
-                            .unwrap()
-                            .execute_order(order)
-                            .expect("order book execute failed");
-                        msg_ct += 1;
-                        log::trace!("Parsed OrderExecuted");
-                    }
-                    &modifyordermessages::OrderExecutedWithPrice::MESSAGE_TYPE => {
-                        parse_fixed_length_message::<
-                            { modifyordermessages::OrderExecutedWithPrice::LENGTH },
-                            modifyordermessages::OrderExecutedWithPrice,
-                            _,
-                        >(&mut consumer)
-                        .expect("msg parse failed");
-                        msg_ct += 1;
-                        log::trace!("Parsed OrderExecutedWithPrice");
-                    }
-                    &modifyordermessages::OrderDelete::MESSAGE_TYPE => {
-                        let order = parse_fixed_length_message::<
-                            { modifyordermessages::OrderDelete::LENGTH },
-                            modifyordermessages::OrderDelete,
-                            _,
-                        >(&mut consumer)
-                        .expect("msg parse failed");
-                        order_book_manager
-                            .write()
-                            .unwrap()
-                            .delete_order(order)
-                            .expect("order book delete failed");
-                        msg_ct += 1;
-                        log::trace!("Parsed OrderDelete");
-                    }
-                    &stockmessages::StockTradingAction::MESSAGE_TYPE => {
-                        parse_fixed_length_message::<
-                            { stockmessages::StockTradingAction::LENGTH },
-                            stockmessages::StockTradingAction,
-                            _,
-                        >(&mut consumer)
-                        .expect("msg parse failed");
-                        msg_ct += 1;
-                        log::trace!("Parsed StockTradingAction");
-                    }
-                    &noiimessages::NetOrderImbalanceIndicator::MESSAGE_TYPE => {
-                        parse_fixed_length_message::<
-                            { noiimessages::NetOrderImbalanceIndicator::LENGTH },
-                            noiimessages::NetOrderImbalanceIndicator,
-                            _,
-                        >(&mut consumer)
-                        .expect("msg parse failed");
-                        msg_ct += 1;
-                        log::trace!("Parsed NetOrderImbalanceIndicator");
-                    }
-                    &stockmessages::IPOQuotingPeriodUpdate::MESSAGE_TYPE => {
-                        parse_fixed_length_message::<
-                            { stockmessages::IPOQuotingPeriodUpdate::LENGTH },
-                            stockmessages::IPOQuotingPeriodUpdate,
-                            _,
-                        >(&mut consumer)
-                        .expect(&format!("{consumer_slice_size:x?} msg parse failed"));
-                        msg_ct += 1;
-                        log::trace!("Parsed IPOQuotingPeriodUpdate");
-                    }
-                    &stockmessages::MarketParticipantPosition::MESSAGE_TYPE => {
-                        parse_fixed_length_message::<
-                            { stockmessages::MarketParticipantPosition::LENGTH },
-                            stockmessages::MarketParticipantPosition,
-                            _,
-                        >(&mut consumer)
-                        .expect("msg parse failed");
-                        msg_ct += 1;
-                        log::trace!("Parsed MarketParticipantPosition");
-                    }
-                    &noiimessages::RetailPriceImprovementIndicator::MESSAGE_TYPE => {
-                        parse_fixed_length_message::<
-                            { noiimessages::RetailPriceImprovementIndicator::LENGTH },
-                            noiimessages::RetailPriceImprovementIndicator,
-                            _,
-                        >(&mut consumer)
-                        .expect("msg parse failed");
-                        msg_ct += 1;
-                        log::trace!("Parsed RetailPriceImprovementIndicator");
-                    }
-                    &trademessages::NonCrossingTrade::MESSAGE_TYPE => {
-                        parse_fixed_length_message::<
-                            { trademessages::NonCrossingTrade::LENGTH },
-                            trademessages::NonCrossingTrade,
-                            _,
-                        >(&mut consumer)
-                        .expect("msg parse failed");
-                        msg_ct += 1;
-                        log::trace!("Parsed NonCrossingTrade");
-                    }
-                    &trademessages::CrossingTrade::MESSAGE_TYPE => {
-                        parse_fixed_length_message::<
-                            { trademessages::CrossingTrade::LENGTH },
-                            trademessages::CrossingTrade,
-                            _,
-                        >(&mut consumer)
-                        .expect("msg parse failed");
-                        msg_ct += 1;
-                        log::trace!("Parsed CrossingTrade");
-                    }
-                    &stockmessages::StockDirectory::MESSAGE_TYPE => {
-                        let message = parse_fixed_length_message::<
-                            { stockmessages::StockDirectory::LENGTH },
-                            stockmessages::StockDirectory,
-                            _,
-                        >(&mut consumer)
-                        .expect("msg parse failed");
-
-                        stock_directory_manager.write().unwrap().add_stock(message);
-                        msg_ct += 1;
-                        log::trace!("Parsed StockDirectory");
-                    }
-                    &systemmessages::SystemEventMessage::MESSAGE_TYPE => {
-                        parse_fixed_length_message::<
-                            { systemmessages::SystemEventMessage::LENGTH },
-                            systemmessages::SystemEventMessage,
-                            _,
-                        >(&mut consumer)
-                        .expect("msg parse failed");
-                        msg_ct += 1;
-                        log::trace!("Parsed SystemEventMessage");
-                    }
-                    &modifyordermessages::OrderReplace::MESSAGE_TYPE => {
-                        let order = parse_fixed_length_message::<
-                            { modifyordermessages::OrderReplace::LENGTH },
-                            modifyordermessages::OrderReplace,
-                            _,
-                        >(&mut consumer)
-                        .expect("msg parse failed");
-                        order_book_manager
-                            .write()
-                            .unwrap()
-                            .replace_order(order)
-                            .expect("order book replace failed");
-                        msg_ct += 1;
-                        log::trace!("Parsed OrderReplace");
-                    }
-                    &stockmessages::MWCBDeclineLevel::MESSAGE_TYPE => {
-                        parse_fixed_length_message::<
-                            { stockmessages::MWCBDeclineLevel::LENGTH },
-                            stockmessages::MWCBDeclineLevel,
-                            _,
-                        >(&mut consumer)
-                        .expect("msg parse failed");
-                        msg_ct += 1;
-                        log::trace!("Parsed MWCBDeclineLevel");
-                    }
-                    &stockmessages::MWCBStatus::MESSAGE_TYPE => {
-                        parse_fixed_length_message::<
-                            { stockmessages::MWCBStatus::LENGTH },
-                            stockmessages::MWCBStatus,
-                            _,
-                        >(&mut consumer)
-                        .expect("msg parse failed");
-                        msg_ct += 1;
-                        log::trace!("Parsed MWCBStatus");
-                    }
-                    &modifyordermessages::OrderCancel::MESSAGE_TYPE => {
-                        let order = parse_fixed_length_message::<
-                            { modifyordermessages::OrderCancel::LENGTH },
-                            modifyordermessages::OrderCancel,
-                            _,
-                        >(&mut consumer)
-                        .expect("msg parse failed");
-                        order_book_manager
-                            .write()
-                            .unwrap()
-                            .cancel_order(order)
-                            .expect("order book cancel failed");
-                        msg_ct += 1;
-                        log::trace!("Parsed OrderCancel");
-                    }
-                    &stockmessages::RegSHOShortSalePriceTestRestriction::MESSAGE_TYPE => {
-                        parse_fixed_length_message::<
-                            { stockmessages::RegSHOShortSalePriceTestRestriction::LENGTH },
-                            stockmessages::RegSHOShortSalePriceTestRestriction,
-                            _,
-                        >(&mut consumer)
-                        .expect("msg parse failed");
-                        msg_ct += 1;
-                        log::trace!("Parsed RegSHOShortSalePriceTestRestriction");
-                    }
-                    _ => {
-                        consumer.pop_slice(&mut consumer_slice_size); // Skip useless bytes
+            // Frames of unknown message types are trusted, only implausible frames start a scan for a valid header
+            let plausible = |header: &[u8; LENGTH_PREFIX_SIZE + 1], position| {
+                decoder::is_plausible_frame_header(
+                    BigEndian::read_u16(header),
+                    header[LENGTH_PREFIX_SIZE],
+                ) && fits(header, position)
+            };
+            if resync && !plausible(&header, position) {
+                let mut skipped = 0;
+                while !(decoder::is_valid_frame_header(
+                    BigEndian::read_u16(&header),
+                    header[LENGTH_PREFIX_SIZE],
+                ) && fits(&header, position))
+                {
+                    header.rotate_left(1);
+                    if pop_exact(
+                        &mut consumer,
+                        &mut header[LENGTH_PREFIX_SIZE..],
+                        &producer_done,
+                    ) == 0
+                    {
+                        break;
                     }
+                    skipped += 1;
+                    position += 1;
                 }
-                if msg_ct % 1_000_000 == 0 {
-                    let elapsed = last_million_time.elapsed();
-                    log::debug!(
-                        "Processed {}m messages in {:.2?} ({:.2}m messages/second)",
-                        msg_ct / 1_000_000,
-                        elapsed,
-                        1_000_000.0 / elapsed.as_secs_f64() / 1_000_000.0
-                    );
-                    last_million_time = std::time::Instant::now();
+                if skipped > 0 {
+                    replay.record_resync(skipped);
                 }
-                consumer_slice_size = [0u8; 3];
             }
-            Ok(())
-        });
-        s.spawn(move || -> Result<(), io::Error> {
-            let mut terminal = ratatui::init();
-            terminal.clear()?;
-            let app_result = run(
-                terminal,
-                order_book_manager_clone,
-                stock_directory_manager_clone,
+
+            let length = usize::from(BigEndian::read_u16(&header));
+            position += (LENGTH_PREFIX_SIZE + length) as u64;
+            let body = &mut body[..length.saturating_sub(1)];
+            if pop_exact(&mut consumer, body, &producer_done) < body.len() {
+                log::warn!("File ends with a truncated message");
+                break;
+            }
+            if !replay.handle_frame(header[LENGTH_PREFIX_SIZE], body) {
+                break;
+            }
+        }
+        // Stop the producer early if the replay ended before the file did
+        stop.store(true, Ordering::Release);
+
+        producer_handle.join().expect("producer thread panicked")
+    })
+}
+
+/// Replays a memory-mapped ITCH file. Messages are parsed in place from the mapping, one frame at a time.
+///
+/// # Arguments
+/// * `reader` - The mapped file.
+/// * `replay` - Applies the parsed messages.
+/// * `resync` - Whether to resynchronise on corrupt framing.
+fn replay_mmap(reader: &MmapReader, replay: &mut Replay, resync: bool) {
+    let mut decoder = reader.decoder();
+    loop {
+        if resync {
+            let skipped = decoder.resync();
+            if skipped > 0 {
+                replay.record_resync(skipped);
+            }
+        }
+        let position = decoder.position();
+        match decoder.next_frame() {
+            Some(Ok((message_type, body))) => {
+                if !replay.handle_frame(message_type, body) {
+                    break;
+                }
+            }
+            Some(Err(e)) => log::warn!("Invalid frame at byte {position}: {e}"),
+            None => break,
+        }
+    }
+    log::info!("EOF, Mmap replay done");
+}
+
+/// Parses the frames of a single file and dispatches the messages to the registered sinks.
+/// Shared by the ring buffer and memory-mapped readers, which only differ in how frames are read.
+///
+/// # Fields
+/// * `dispatcher` - Receives the messages in the time window, and the messages every sink needs before it.
+/// * `warmup` - Receives the order messages before the time window, to build the books.
+/// * `window` - The part of the file to report on.
+/// * `stats` - Statistics of the file so far.
+/// * `pacer` - Paces the messages in the time window to their timestamps, see `--speed`.
+/// * `filter` - Skips the messages of other stocks, and the stock directory its symbols are resolved from.
+/// * `last_million_time` - When the last million messages were reached, for throughput logging.
+struct Replay {
+    dispatcher: Dispatcher,
+    warmup: Dispatcher,
+    window: ReplayWindow,
+    stats: FileStats,
+    pacer: Option<Pacer>,
+    filter: Option<(SymbolFilter, Arc<RwLock<StockDirectoryManager>>)>,
+    shards: Option<Arc<RwLock<ShardedOrderBookManager>>>,
+    last_million_time: Instant,
+}
+
+/// The part of a file to report on, see `--start`, `--end` and `--restore`.
+///
+/// # Fields
+/// * `start` - Nanoseconds since midnight. Before this, order messages only update the books, crossing trades only
+///   close auctions and IPOs, and other trades are dropped. Every other message is dispatched as normal, as sinks
+///   need the stock directory and trading state at the start.
+/// * `end` - Nanoseconds since midnight. The replay stops at the first message at or past this.
+/// * `restored_at` - When the restored snapshot was taken. Order messages before this are already in the books.
+struct ReplayWindow {
+    start: u64,
+    end: u64,
+    restored_at: Option<u64>,
+}
+
+impl Replay {
+    fn new(
+        dispatcher: Dispatcher,
+        warmup: Dispatcher,
+        window: ReplayWindow,
+        pacer: Option<Pacer>,
+    ) -> Self {
+        Self {
+            dispatcher,
+            warmup,
+            window,
+            stats: FileStats::new(),
+            pacer,
+            filter: None,
+            shards: None,
+            last_million_time: Instant::now(),
+        }
+    }
+
+    /// Skip the messages of stocks `filter` does not allow, see `--filter` and `--stock-locates`.
+    ///
+    /// # Arguments
+    /// * `filter` - The allowlist.
+    /// * `stock_directory_manager` - The stock directory the dispatcher builds, used to resolve the filter's symbols.
+    fn set_filter(
+        &mut self,
+        filter: SymbolFilter,
+        stock_directory_manager: Arc<RwLock<StockDirectoryManager>>,
+    ) {
+        self.filter = Some((filter, stock_directory_manager));
+    }
+
+    /// Sync the shards building the books before every message that sinks match against earlier executions, see
+    /// `shard::is_sync_point`, so their events are dispatched first.
+    fn set_shards(&mut self, shards: Arc<RwLock<ShardedOrderBookManager>>) {
+        self.shards = Some(shards);
+    }
+
+    /// Dispatch a message, after the events the shards have not emitted yet if it is a sync point.
+    fn dispatch(&mut self, message: &MessageTypes) {
+        if let Some(shards) = &self.shards {
+            if shard::is_sync_point(message) {
+                // Not in the loop head, the shards are a sink too and must be unlocked before dispatching
+                let events = shards.write().unwrap().sync();
+                for event in events {
+                    self.dispatcher.dispatch_event(event);
+                }
+            }
+        }
+        self.dispatcher.dispatch(message);
+    }
+
+    /// Parses and applies a single frame. Frames that can not be parsed are counted and skipped,
+    /// framing is unaffected as the whole body has already been consumed.
+    ///
+    /// # Arguments
+    /// * `message_type` - The message type byte.
+    /// * `body` - The `length - 1` bytes following the message type byte.
+    ///
+    /// # Returns
+    /// * `bool` - False once the end of the time window has been reached, and the replay should stop.
+    fn handle_frame(&mut self, message_type: u8, body: &[u8]) -> bool {
+        self.stats.message_counts[usize::from(message_type)] += 1;
+        if let Some((filter, _)) = &self.filter {
+            if !filter.allows_frame(message_type, body) {
+                self.stats.filtered_messages += 1;
+                return true;
+            }
+        }
+        let message = match decoder::parse_message(message_type, body) {
+            Ok(message) => message,
+            Err(ParseError::UnknownMessageType { .. }) => {
+                self.stats.unknown_counts[usize::from(message_type)] += 1;
+                log::debug!(
+                    "Skipping unknown message type {:?} ({} bytes)",
+                    message_type as char,
+                    body.len() + 1
+                );
+                return true;
+            }
+            Err(e) => {
+                self.stats.invalid_counts[usize::from(message_type)] += 1;
+                log::warn!("Skipping {:?} message: {e}", message_type as char);
+                return true;
+            }
+        };
+        log::trace!("Parsed {message:?}");
+
+        let timestamp = message.header().timestamp;
+        if timestamp >= self.window.end {
+            log::info!(
+                "Reached the end of the time window at {}",
+                helpers::format_time_of_day(timestamp)
             );
-            ratatui::restore();
-            app_result
-        });
-    });
+            return false;
+        }
+        let is_order_message = order_message_header(&message).is_some();
+        if is_order_message
+            && self
+                .window
+                .restored_at
+                .is_some_and(|restored_at| timestamp < restored_at)
+        {
+            self.stats.restored_messages += 1;
+        } else if timestamp >= self.window.start {
+            if let Some(pacer) = &mut self.pacer {
+                pacer.wait_until(timestamp);
+            }
+            self.dispatch(&message);
+        } else {
+            self.stats.warmup_messages += 1;
+            let is_trade_message = matches!(
+                message,
+                MessageTypes::NonCrossingTrade(_)
+                    | MessageTypes::CrossingTrade(_)
+                    | MessageTypes::BrokenTrade(_)
+            );
+            if is_order_message || matches!(message, MessageTypes::CrossingTrade(_)) {
+                self.warmup.dispatch(&message);
+            } else if !is_trade_message {
+                self.dispatch(&message);
+            }
+        }
+        if let (MessageTypes::StockDirectory(_), Some((filter, stock_directory_manager))) =
+            (&message, &mut self.filter)
+        {
+            filter.resolve(&stock_directory_manager.read().unwrap());
+        }
+
+        self.stats.total_messages += 1;
+        if self.stats.total_messages.is_multiple_of(1_000_000) {
+            let elapsed = self.last_million_time.elapsed();
+            log::debug!(
+                "Processed {}m messages in {:.2?} ({:.2}m messages/second)",
+                self.stats.total_messages / 1_000_000,
+                elapsed,
+                1_000_000.0 / elapsed.as_secs_f64() / 1_000_000.0
+            );
+            self.last_million_time = Instant::now();
+        }
+
+        // Nothing follows the end of messages, so stop rather than wait on the rest of the file
+        if let MessageTypes::SystemEvent(system_event) = &message {
+            if system_event.event_code == SystemEventCode::EndOfMessages {
+                log::info!(
+                    "End of messages at {}",
+                    helpers::format_time_of_day(timestamp)
+                );
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Records that corrupt framing was resynchronised by skipping `skipped` bytes.
+    fn record_resync(&mut self, skipped: usize) {
+        self.stats.resyncs += 1;
+        self.stats.resync_bytes += skipped as u64;
+        log::warn!("Corrupt framing, resynchronised after skipping {skipped} bytes");
+    }
+}
+
+/// Runs the TUI until the user quits, restoring the terminal afterwards.
+fn run_tui(published_book: Arc<PublishedBook>, stock: Stock) -> Result<(), io::Error> {
+    let mut terminal = ratatui::init();
+    terminal.clear()?;
+    let app_result = run(terminal, published_book, stock);
+    ratatui::restore();
+    app_result
+}
+
+/// Prints the processing statistics of a file, plus the top of book of each filtered symbol.
+fn print_stats(path: &Path, cli: &Cli, file_run: &FileRun) {
+    let stats = &file_run.stats;
+    println!(
+        "{}: {} messages ({:.2}gb) in {:.2?} ({:.2}m messages/second)",
+        path.display(),
+        stats.total_messages,
+        stats.gigabytes_read,
+        stats.elapsed,
+        stats.total_messages as f64 / stats.elapsed.as_secs_f64() / 1_000_000.0
+    );
+    for (message_type, count) in stats.message_counts.iter().enumerate() {
+        if *count == 0 {
+            continue;
+        }
+        print!("  '{}': {}", message_type as u8 as char, count);
+        if stats.unknown_counts[message_type] > 0 {
+            print!(" ({} unknown, skipped)", stats.unknown_counts[message_type]);
+        }
+        if stats.invalid_counts[message_type] > 0 {
+            print!(" ({} invalid, skipped)", stats.invalid_counts[message_type]);
+        }
+        println!();
+    }
+    println!(
+        "  {} executions, {} shares executed",
+        stats.executions, stats.executed_shares
+    );
+    if stats.warmup_messages > 0 {
+        println!(
+            "  {} messages before --start, only used to build the books",
+            stats.warmup_messages
+        );
+    }
+    if stats.restored_messages > 0 {
+        println!(
+            "  {} order messages already in the restored snapshot, skipped",
+            stats.restored_messages
+        );
+    }
+    if stats.filtered_messages > 0 {
+        println!(
+            "  {} messages of other stocks, skipped by the filter",
+            stats.filtered_messages
+        );
+    }
+    if stats.resyncs > 0 {
+        println!(
+            "  {} resyncs, {} bytes skipped",
+            stats.resyncs, stats.resync_bytes
+        );
+    }
+
+    let order_book_manager = file_run.order_book_manager.read().unwrap();
+    println!("  {} order books", order_book_manager.order_books.len());
+    let session_tracker = file_run.session_tracker.read().unwrap();
+    if let (Some(open), Some(close)) = (
+        session_tracker.timestamp_of(SystemEventCode::StartOfMarketHours),
+        session_tracker.timestamp_of(SystemEventCode::EndOfMarketHours),
+    ) {
+        println!(
+            "  market hours {} to {}",
+            helpers::format_time_of_day(open),
+            helpers::format_time_of_day(close)
+        );
+    }
+    let trading_state_manager = file_run.trading_state_manager.read().unwrap();
+    if let Some((timestamp, level)) = trading_state_manager.mwcb_breaches.last() {
+        println!(
+            "  market wide circuit breaker {level:?} breached at {}",
+            helpers::format_time_of_day(*timestamp)
+        );
+    }
+    let participant_tracker = file_run.participant_tracker.read().unwrap();
+    if let Some((mpid, stats)) = participant_tracker
+        .stats
+        .iter()
+        .max_by_key(|(_, stats)| stats.shares_displayed)
+    {
+        // The shards emit the top of book too late to tell who was at the inside
+        let at_inside = match cli.shards {
+            Some(_) => String::new(),
+            None => format!(
+                ", {:.2?} at the inside",
+                Duration::from_nanos(stats.time_at_inside)
+            ),
+        };
+        println!(
+            "  {} MPIDs on attributed orders, most displayed by {}: {} orders, {} shares, {} executions{at_inside}",
+            participant_tracker.stats.len(),
+            helpers::mpid_to_str(mpid),
+            stats.orders_added,
+            stats.shares_displayed,
+            stats.executions,
+        );
+    }
+    let ipo_tracker = file_run.ipo_tracker.read().unwrap();
+    if !ipo_tracker.ipos.is_empty() {
+        println!(
+            "  {} IPOs, {} released",
+            ipo_tracker.ipos.len(),
+            ipo_tracker
+                .ipos
+                .values()
+                .filter(|ipo| ipo.is_released())
+                .count()
+        );
+    }
+    if cli.symbols.is_empty() {
+        return;
+    }
+
+    let stock_directory_manager = file_run.stock_directory_manager.read().unwrap();
+    let trade_tape_manager = file_run.trade_tape_manager.read().unwrap();
+    let auction_tracker = file_run.auction_tracker.read().unwrap();
+    for stock in &cli.symbols {
+        let Some(stock_locate) = stock_directory_manager.stock_to_locate(*stock) else {
+            println!("  {}: not in stock directory", helpers::stock_to_str(stock));
+            continue;
+        };
+        match order_book_manager.order_books.get(&stock_locate) {
+            Some(book) => print!(
+                "  {}: bid {} ask {}",
+                helpers::stock_to_str(stock),
+                format_price(book.get_best_bid()),
+                format_price(book.get_best_ask()),
+            ),
+            None => print!("  {}: no order book", helpers::stock_to_str(stock)),
+        }
+        println!(
+            ", {} trades, volume {}, vwap {}",
+            trade_tape_manager.get_trades(stock_locate).len(),
+            trade_tape_manager.get_volume(stock_locate),
+            format_vwap(trade_tape_manager.get_vwap(stock_locate)),
+        );
+        if let Some(status) = trading_state_manager.get_status(stock_locate) {
+            if status.halts() > 0 {
+                println!(
+                    "    halted {} times, {:?} at the end of the file",
+                    status.halts(),
+                    status.trading_state
+                );
+            }
+        }
+        let market_makers = participant_tracker.get_market_makers(stock_locate);
+        if !market_makers.is_empty() {
+            print!("    {} market makers", market_makers.len());
+            match participant_tracker.primary_market_maker(stock_locate) {
+                Some(primary) => println!(", primary {}", helpers::mpid_to_str(&primary.mpid)),
+                None => println!(),
+            }
+        }
+        if let Some(ipo) = ipo_tracker.get_ipo(stock_locate) {
+            print!("    IPO");
+            if let Some(ipo_price) = ipo.ipo_price() {
+                print!(" at {ipo_price}");
+            }
+            if let Some(release_time) = ipo.release_time() {
+                print!(", release at {}", helpers::format_time_of_day(release_time));
+            }
+            if ipo.postponements() > 0 {
+                print!(", postponed {} times", ipo.postponements());
+            }
+            match ipo.cross {
+                Some(cross) => println!(", crossed {} shares at {}", cross.shares, cross.price),
+                None => println!(", not released"),
+            }
+        }
+        for auction in auction_tracker.get_auctions(stock_locate) {
+            print!(
+                "    {:?}, {} imbalance messages",
+                auction.cross_type,
+                auction.imbalances.len()
+            );
+            match auction.cross {
+                Some(cross) => println!(
+                    ", crossed {} shares at {} at {}",
+                    cross.shares,
+                    cross.price,
+                    helpers::format_time_of_day(cross.timestamp)
+                ),
+                None => println!(", not crossed"),
+            }
+        }
+    }
+}
+
+/// Writes one CSV row per order book of a file that passes the symbol filter.
+fn export_books(
+    writer: &mut dyn Write,
+    path: &Path,
+    cli: &Cli,
+    file_run: &FileRun,
+) -> Result<(), io::Error> {
+    let order_book_manager = file_run.order_book_manager.read().unwrap();
+    let stock_directory_manager = file_run.stock_directory_manager.read().unwrap();
+    let trade_tape_manager = file_run.trade_tape_manager.read().unwrap();
+
+    let mut stock_locates: Vec<_> = order_book_manager.order_books.keys().copied().collect();
+    stock_locates.sort_unstable();
+
+    for stock_locate in stock_locates {
+        let Some(stock) = stock_directory_manager.locate_to_stock(stock_locate) else {
+            continue;
+        };
+        if !cli.wants_stock(&stock) {
+            continue;
+        }
+        let book = &order_book_manager.order_books[&stock_locate];
+        writeln!(
+            writer,
+            "{},{},{},{},{},{},{},{},{},{},{},{}",
+            path.display(),
+            helpers::stock_to_str(&stock),
+            stock_locate,
+            format_price(book.get_best_bid()),
+            format_price(book.get_best_ask()),
+            book.bid_book.len(),
+            book.ask_book.len(),
+            book.bid_book
+                .values()
+                .map(|bucket| u64::from(bucket.share_quantity))
+                .sum::<u64>(),
+            book.ask_book
+                .values()
+                .map(|bucket| u64::from(bucket.share_quantity))
+                .sum::<u64>(),
+            trade_tape_manager.get_trades(stock_locate).len(),
+            trade_tape_manager.get_volume(stock_locate),
+            format_vwap(trade_tape_manager.get_vwap(stock_locate)),
+        )?;
+    }
     Ok(())
 }
+
+/// Formats an optional raw `Price4` value, empty if there is no price.
+fn format_price(price: Option<u32>) -> String {
+    price
+        .map(|value| Price4::new(value).to_string())
+        .unwrap_or_default()
+}
+
+/// Formats an optional VWAP to 4 decimal places, empty if nothing has traded.
+fn format_vwap(vwap: Option<f64>) -> String {
+    vwap.map(|value| format!("{value:.4}")).unwrap_or_default()
+}
@@ -29,6 +29,29 @@ impl MessageHeader {
             timestamp: BigEndian::read_u48(&input[4..10]),
         }
     }
+
+    /// Append the header as the first 10 bytes of a message body, the inverse of `parse`.
+    /// The timestamp is truncated to 48 bits, as in the wire format.
+    pub fn encode(&self, output: &mut Vec<u8>) {
+        output.extend_from_slice(&self.stock_locate.to_be_bytes());
+        output.extend_from_slice(&self.tracking_number.to_be_bytes());
+        output.extend_from_slice(&self.timestamp.to_be_bytes()[2..]);
+    }
+
+    /// A header for tests and benchmarks, with a zero tracking number.
+    ///
+    /// # Arguments
+    /// * `stock_locate` - The stock locate code, 0 for messages that are not about a stock.
+    /// * `timestamp` - Nanoseconds since midnight.
+    #[cfg(any(test, feature = "bench"))]
+    pub fn example(stock_locate: u16, timestamp: u64) -> MessageHeader {
+        MessageHeader {
+            message_type: b'_' as char,
+            stock_locate,
+            tracking_number: 0,
+            timestamp,
+        }
+    }
 }
 
 impl BinaryMessageLength for MessageHeader {
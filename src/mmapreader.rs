@@ -0,0 +1,81 @@
+use crate::decoder::SliceDecoder;
+use memmap2::Mmap;
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+/// Zero-copy reader over a memory-mapped ITCH file.
+///
+/// The whole file is mapped into the address space and frames are walked in place, so message bodies are
+/// handed to `Parse::parse` as slices of the mapping. Nothing is copied into intermediate buffers.
+pub struct MmapReader {
+    mmap: Mmap,
+}
+
+impl MmapReader {
+    /// Memory maps the file at `path`.
+    ///
+    /// # Arguments
+    /// * `path` - The ITCH file to map.
+    ///
+    /// # Returns
+    /// * `Ok(MmapReader)` - The mapped file.
+    /// * `Err(io::Error)` - If the file could not be opened or mapped.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, io::Error> {
+        let file = File::open(path)?;
+        // SAFETY: The mapping is read only. Truncating or rewriting the file while it is mapped is undefined
+        // behaviour, which is the same contract every memory-mapped reader has with its input files.
+        let mmap = unsafe { Mmap::map(&file)? };
+        #[cfg(unix)]
+        mmap.advise(memmap2::Advice::Sequential)?;
+        Ok(Self { mmap })
+    }
+
+    /// The raw bytes of the file.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.mmap
+    }
+
+    /// Size of the file in bytes.
+    pub fn len(&self) -> usize {
+        self.mmap.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.mmap.is_empty()
+    }
+
+    /// A decoder over the whole file, starting at the first frame.
+    pub fn decoder(&self) -> SliceDecoder<'_> {
+        SliceDecoder::new(&self.mmap)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::addordermessages::AddOrder;
+    use crate::enums::MessageTypes;
+    use crate::types::{GenerateExampleMessage, MessageHeaderType};
+    use std::io::Write;
+
+    #[test]
+    fn test_mmap_reader() {
+        let body = AddOrder::generate_binary_example();
+        let mut stream = ((body.len() + 1) as u16).to_be_bytes().to_vec();
+        stream.push(AddOrder::MESSAGE_TYPE);
+        stream.extend_from_slice(&body);
+
+        let path = std::env::temp_dir().join(format!("fastasx_mmap_{}.itch", std::process::id()));
+        File::create(&path).unwrap().write_all(&stream).unwrap();
+
+        let reader = MmapReader::open(&path).unwrap();
+        assert_eq!(reader.as_bytes(), stream.as_slice());
+        let messages: Vec<_> = reader.decoder().collect();
+        assert_eq!(messages.len(), 1);
+        assert!(matches!(messages[0], Ok(MessageTypes::AddOrder(_))));
+
+        drop(reader);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
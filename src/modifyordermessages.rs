@@ -1,5 +1,5 @@
-use crate::types::{BinaryMessageLength, MessageHeaderType, Parse, ParseError, Price4, PriceConversions};
-use crate::{helpers::byte_to_bool, messageheader::MessageHeader};
+use crate::types::{BinaryMessageLength, Encode, MessageHeaderType, Parse, ParseError, Price4, PriceConversions};
+use crate::{helpers::{bool_to_byte, byte_to_bool}, messageheader::MessageHeader};
 use byteorder::{BigEndian, ByteOrder};
 
 #[cfg(any(test, feature = "bench"))]
@@ -7,7 +7,7 @@ use crate::types::GenerateExampleMessage;
 #[cfg(any(test, feature = "bench"))]
 use fastrand::Rng;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub struct OrderExecuted {
     pub header: MessageHeader,
     pub order_reference_number: u64,
@@ -40,6 +40,19 @@ impl MessageHeaderType for OrderExecuted {
     const MESSAGE_TYPE: u8 = b'E';
 }
 
+impl Encode for OrderExecuted {
+    fn message_type(&self) -> u8 {
+        Self::MESSAGE_TYPE
+    }
+
+    fn encode(&self, output: &mut Vec<u8>) {
+        self.header.encode(output);
+        output.extend_from_slice(&self.order_reference_number.to_be_bytes());
+        output.extend_from_slice(&self.executed_shares.to_be_bytes());
+        output.extend_from_slice(&self.match_number.to_be_bytes());
+    }
+}
+
 #[cfg(any(test, feature = "bench"))]
 impl GenerateExampleMessage<{ Self::LENGTH }> for OrderExecuted {
     fn generate_binary_example() -> [u8; Self::LENGTH] {
@@ -60,7 +73,7 @@ impl GenerateExampleMessage<{ Self::LENGTH }> for OrderExecuted {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub struct OrderExecutedWithPrice {
     pub order_executed_message: OrderExecuted,
     pub printable: bool,
@@ -92,6 +105,18 @@ impl MessageHeaderType for OrderExecutedWithPrice {
     const MESSAGE_TYPE: u8 = b'C';
 }
 
+impl Encode for OrderExecutedWithPrice {
+    fn message_type(&self) -> u8 {
+        Self::MESSAGE_TYPE
+    }
+
+    fn encode(&self, output: &mut Vec<u8>) {
+        self.order_executed_message.encode(output);
+        output.push(bool_to_byte(self.printable));
+        output.extend_from_slice(&self.exec_price.value.to_be_bytes());
+    }
+}
+
 #[cfg(any(test, feature = "bench"))]
 impl GenerateExampleMessage<{ Self::LENGTH }> for OrderExecutedWithPrice {
     fn generate_binary_example() -> [u8; Self::LENGTH] {
@@ -110,7 +135,7 @@ impl GenerateExampleMessage<{ Self::LENGTH }> for OrderExecutedWithPrice {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub struct OrderCancel {
     pub header: MessageHeader,
     pub order_reference_number: u64,
@@ -146,6 +171,18 @@ impl MessageHeaderType for OrderCancel {
     const MESSAGE_TYPE: u8 = b'X';
 }
 
+impl Encode for OrderCancel {
+    fn message_type(&self) -> u8 {
+        Self::MESSAGE_TYPE
+    }
+
+    fn encode(&self, output: &mut Vec<u8>) {
+        self.header.encode(output);
+        output.extend_from_slice(&self.order_reference_number.to_be_bytes());
+        output.extend_from_slice(&self.canceled_shares.to_be_bytes());
+    }
+}
+
 #[cfg(any(test, feature = "bench"))]
 impl GenerateExampleMessage<{ Self::LENGTH }> for OrderCancel {
     fn generate_binary_example() -> [u8; Self::LENGTH] {
@@ -164,7 +201,7 @@ impl GenerateExampleMessage<{ Self::LENGTH }> for OrderCancel {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub struct OrderDelete {
     pub header: MessageHeader,
     pub order_reference_number: u64,
@@ -193,6 +230,17 @@ impl MessageHeaderType for OrderDelete {
     const MESSAGE_TYPE: u8 = b'D';
 }
 
+impl Encode for OrderDelete {
+    fn message_type(&self) -> u8 {
+        Self::MESSAGE_TYPE
+    }
+
+    fn encode(&self, output: &mut Vec<u8>) {
+        self.header.encode(output);
+        output.extend_from_slice(&self.order_reference_number.to_be_bytes());
+    }
+}
+
 #[cfg(any(test, feature = "bench"))]
 impl GenerateExampleMessage<{ Self::LENGTH }> for OrderDelete {
     fn generate_binary_example() -> [u8; Self::LENGTH] {
@@ -244,6 +292,20 @@ impl MessageHeaderType for OrderReplace {
     const MESSAGE_TYPE: u8 = b'U';
 }
 
+impl Encode for OrderReplace {
+    fn message_type(&self) -> u8 {
+        Self::MESSAGE_TYPE
+    }
+
+    fn encode(&self, output: &mut Vec<u8>) {
+        self.header.encode(output);
+        output.extend_from_slice(&self.original_order_reference_number.to_be_bytes());
+        output.extend_from_slice(&self.new_order_reference_number.to_be_bytes());
+        output.extend_from_slice(&self.shares.to_be_bytes());
+        output.extend_from_slice(&self.price.value.to_be_bytes());
+    }
+}
+
 #[cfg(any(test, feature = "bench"))]
 impl GenerateExampleMessage<{ Self::LENGTH }> for OrderReplace {
     fn generate_binary_example() -> [u8; Self::LENGTH] {
@@ -1,6 +1,6 @@
 use crate::enums::{CrossType, ImbalanceDirection};
 use crate::messageheader::MessageHeader;
-use crate::types::{BinaryMessageLength, MessageHeaderType, Parse, ParseError, Price4, PriceConversions, Stock};
+use crate::types::{BinaryMessageLength, Encode, MessageHeaderType, Parse, ParseError, Price4, PriceConversions, Stock};
 use byteorder::{BigEndian, ByteOrder};
 
 #[cfg(any(test, feature = "bench"))]
@@ -10,16 +10,16 @@ use fastrand::Rng;
 
 #[derive(Debug, PartialEq)]
 pub struct NetOrderImbalanceIndicator {
-    header: MessageHeader,
-    paired_shares: u64,
-    imbalance_shares: u64,
-    imbalance_direction: ImbalanceDirection,
-    stock: Stock,
-    far_price: Price4,
-    near_price: Price4,
-    current_reference_price: Price4,
-    cross_type: CrossType,
-    price_variation_indicator: char,
+    pub header: MessageHeader,
+    pub paired_shares: u64,
+    pub imbalance_shares: u64,
+    pub imbalance_direction: ImbalanceDirection,
+    pub stock: Stock,
+    pub far_price: Price4,
+    pub near_price: Price4,
+    pub current_reference_price: Price4,
+    pub cross_type: CrossType,
+    pub price_variation_indicator: char,
 }
 
 impl Parse for NetOrderImbalanceIndicator {
@@ -69,6 +69,25 @@ impl MessageHeaderType for NetOrderImbalanceIndicator {
     const MESSAGE_TYPE: u8 = b'I';
 }
 
+impl Encode for NetOrderImbalanceIndicator {
+    fn message_type(&self) -> u8 {
+        Self::MESSAGE_TYPE
+    }
+
+    fn encode(&self, output: &mut Vec<u8>) {
+        self.header.encode(output);
+        output.extend_from_slice(&self.paired_shares.to_be_bytes());
+        output.extend_from_slice(&self.imbalance_shares.to_be_bytes());
+        output.push(u8::from(&self.imbalance_direction));
+        output.extend_from_slice(&self.stock);
+        output.extend_from_slice(&self.far_price.value.to_be_bytes());
+        output.extend_from_slice(&self.near_price.value.to_be_bytes());
+        output.extend_from_slice(&self.current_reference_price.value.to_be_bytes());
+        output.push(u8::from(&self.cross_type));
+        output.push(self.price_variation_indicator as u8);
+    }
+}
+
 #[cfg(any(test, feature = "bench"))]
 impl GenerateExampleMessage<{ Self::LENGTH }> for NetOrderImbalanceIndicator {
     fn generate_binary_example() -> [u8; Self::LENGTH] {
@@ -104,9 +123,9 @@ impl GenerateExampleMessage<{ Self::LENGTH }> for NetOrderImbalanceIndicator {
 // Deprecated?
 #[derive(Debug, PartialEq)]
 pub struct RetailPriceImprovementIndicator {
-    header: MessageHeader,
-    stock: Stock,
-    interest_flag: char,
+    pub header: MessageHeader,
+    pub stock: Stock,
+    pub interest_flag: char,
 }
 
 impl Parse for RetailPriceImprovementIndicator {
@@ -130,3 +149,33 @@ impl BinaryMessageLength for RetailPriceImprovementIndicator {
 impl MessageHeaderType for RetailPriceImprovementIndicator {
     const MESSAGE_TYPE: u8 = b'N';
 }
+
+impl Encode for RetailPriceImprovementIndicator {
+    fn message_type(&self) -> u8 {
+        Self::MESSAGE_TYPE
+    }
+
+    fn encode(&self, output: &mut Vec<u8>) {
+        self.header.encode(output);
+        output.extend_from_slice(&self.stock);
+        output.push(self.interest_flag as u8);
+    }
+}
+
+#[cfg(any(test, feature = "bench"))]
+impl GenerateExampleMessage<{ Self::LENGTH }> for RetailPriceImprovementIndicator {
+    fn generate_binary_example() -> [u8; Self::LENGTH] {
+        let mut rng = Rng::new();
+
+        let header = MessageHeader::generate_binary_example();
+        let stock = rng.u64(..).to_be_bytes();
+        let interest_flag = b'B';
+
+        let mut message = [0u8; Self::LENGTH];
+        message[..10].copy_from_slice(&header);
+        message[10..18].copy_from_slice(&stock);
+        message[18] = interest_flag;
+
+        message
+    }
+}
@@ -1,34 +1,116 @@
 use crate::addordermessages::AddOrder;
-use crate::enums::BuySellIndicator;
+use crate::enums::{BuySellIndicator, MessageTypes};
+use crate::events::{Event, EventSink, TopOfBook};
+use crate::messageheader::MessageHeader;
 use crate::modifyordermessages::{
     OrderCancel, OrderDelete, OrderExecuted, OrderExecutedWithPrice, OrderReplace,
 };
-use crate::types::{OrderBookError, Price4};
+use crate::types::{OrderBookError, Price4, PriceConversions};
 use std::cmp::Reverse;
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, HashMap, VecDeque};
 
 pub type StockLocateCode = u16;
-type OrderReferenceNumber = u64;
+pub type OrderReferenceNumber = u64;
+
+/// An execution against a resting order, from an `OrderExecuted` ('E') or `OrderExecutedWithPrice` ('C') message.
+///
+/// # Fields
+/// * `timestamp` - Nanoseconds since midnight.
+/// * `stock_locate` - The stock locate code of the order.
+/// * `order_reference_number` - The resting order that was executed.
+/// * `buy_sell_indicator` - The side of the resting order.
+/// * `executed_shares` - The number of shares executed.
+/// * `match_number` - The match number, referenced by `BrokenTrade` messages.
+/// * `price` - The execution price. For 'E' messages this is the resting price of the order.
+/// * `printable` - Whether the execution should be displayed on time and sales. Always true for 'E' messages.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Execution {
+    pub timestamp: u64,
+    pub stock_locate: StockLocateCode,
+    pub order_reference_number: OrderReferenceNumber,
+    pub buy_sell_indicator: BuySellIndicator,
+    pub executed_shares: u32,
+    pub match_number: u64,
+    pub price: Price4,
+    pub printable: bool,
+}
+
+/// An aggregated price level of a book, see `LimitOrderBook::get_depth`.
+///
+/// # Fields
+/// * `price` - The price of the level.
+/// * `share_quantity` - The total shares resting at the price.
+/// * `order_count` - The number of orders resting at the price.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PriceLevel {
+    pub price: Price4,
+    pub share_quantity: u32,
+    pub order_count: usize,
+}
+
+/// The place of an order in the queue of its price level, see `OrderBookManager::get_queue_position`.
+///
+/// # Fields
+/// * `orders_ahead` - The orders at the same price that will be filled first, 0 at the front of the queue.
+/// * `shares_ahead` - The shares of those orders.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueuePosition {
+    pub orders_ahead: usize,
+    pub shares_ahead: u32,
+}
+
+/// The top price levels of both sides of a book (market by price).
+///
+/// # Fields
+/// * `bids` - The bid levels, best (highest) first.
+/// * `asks` - The ask levels, best (lowest) first.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Depth {
+    pub bids: Vec<PriceLevel>,
+    pub asks: Vec<PriceLevel>,
+}
+
+/// The header of a message that updates an order book, `None` for every other message.
+pub fn order_message_header(message: &MessageTypes) -> Option<&MessageHeader> {
+    match message {
+        MessageTypes::AddOrder(order) => Some(&order.header),
+        MessageTypes::OrderExecuted(order) => Some(&order.header),
+        MessageTypes::OrderExecutedWithPrice(order) => Some(&order.order_executed_message.header),
+        MessageTypes::OrderCancel(order) => Some(&order.header),
+        MessageTypes::OrderDelete(order) => Some(&order.header),
+        MessageTypes::OrderReplace(order) => Some(&order.header),
+        _ => None,
+    }
+}
 
 /// Manages the order books for all stocks.
+/// Books are `LimitOrderBook`s unless another `OrderBook` is given, e.g. `OrderBookManager::<LadderOrderBook>::default()`.
 ///
 /// # Fields
 /// - order_books: This is the stock locate code > limit order book.
 /// - order_price_map: This is the order reference number > (stock locate code, price, buy sell indicator). This is needed as no orders (except buys) have a price.
-pub struct OrderBookManager {
-    pub order_books: HashMap<StockLocateCode, LimitOrderBook>,
+pub struct OrderBookManager<B = LimitOrderBook> {
+    pub order_books: HashMap<StockLocateCode, B>,
     order_price_map: HashMap<OrderReferenceNumber, (StockLocateCode, Price4, BuySellIndicator)>,
 }
 
-impl OrderBookManager {
-    pub fn new() -> Self {
+impl<B: OrderBook> Default for OrderBookManager<B> {
+    fn default() -> Self {
         log::debug!("Initialising OrderBookManager");
         Self {
             order_books: HashMap::new(),
             order_price_map: HashMap::new(),
         }
     }
+}
+
+impl OrderBookManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
 
+impl<B: OrderBook> OrderBookManager<B> {
     /// Add an order to the order book.
     ///
     /// # Arguments
@@ -48,84 +130,125 @@ impl OrderBookManager {
         );
         self.order_books
             .entry(order.header.stock_locate)
-            .or_insert_with(|| LimitOrderBook::new()) //  or_insert with handles missing value: creates new book
+            .or_default() // or_default handles missing value: creates new book
             .add_order(order)?;
         Ok(())
     }
 
-    /// Execute an order in the order book.
+    /// Execute an order in the order book, at the price it was resting at.
     ///
     /// # Arguments
     /// * `order` - The order to execute.
     ///
     /// # Returns
-    /// * `Ok(u64)` - The match number of the executed order.
+    /// * `Ok(Execution)` - The execution, at the resting price of the order.
     /// * `Err(OrderBookError)` - If the order does not exist.
-    pub fn execute_order(&mut self, order: OrderExecuted) -> Result<u64, OrderBookError> {
-        match self.order_price_map.get(&order.order_reference_number) {
-            Some((stock_locate, price, buy_sell_indicator)) => {
-                self.order_books
-                    .entry(*stock_locate)
-                    .or_insert_with(LimitOrderBook::new)
-                    .cancel_order(
-                        order.order_reference_number,
-                        order.executed_shares,
-                        *price, // Must deref as we do .get, which returns a borrow, not a value (like .remove)
-                        *buy_sell_indicator,
-                        false,
-                    )?;
-                Ok(order.match_number)
-            }
-            None => Err(OrderBookError::NonExistentOrder),
-        }
+    pub fn execute_order(&mut self, order: OrderExecuted) -> Result<Execution, OrderBookError> {
+        self.execute(&order, None, true)
     }
 
     /// Execute an order in the order book with a price.
+    /// The shares are taken off the order at the price it was resting at, which may differ from the execution price.
     ///
     /// # Arguments
     /// * `order` - The order to execute.
     ///
     /// # Returns
-    /// * `Ok((u64, Price, bool))` - The match number, execution price, and whether the order is printable.
+    /// * `Ok(Execution)` - The execution, at the execution price of the message.
     /// * `Err(OrderBookError)` - If the order does not exist.
     pub fn execute_order_with_price(
         &mut self,
         order: OrderExecutedWithPrice,
-    ) -> Result<(u64, Price4, bool), OrderBookError> {
-        match self
-            .order_price_map
-            .get(&order.order_executed_message.order_reference_number)
-        {
+    ) -> Result<Execution, OrderBookError> {
+        self.execute(
+            &order.order_executed_message,
+            Some(order.exec_price),
+            order.printable,
+        )
+    }
+
+    /// Take executed shares off an order, see `execute_order` and `execute_order_with_price`.
+    ///
+    /// # Arguments
+    /// * `order` - The execution message.
+    /// * `exec_price` - The execution price, or `None` to execute at the resting price.
+    /// * `printable` - Whether the execution should be displayed on time and sales.
+    fn execute(
+        &mut self,
+        order: &OrderExecuted,
+        exec_price: Option<Price4>,
+        printable: bool,
+    ) -> Result<Execution, OrderBookError> {
+        match self.order_price_map.get(&order.order_reference_number) {
             Some((stock_locate, price, buy_sell_indicator)) => {
-                self.order_books
+                let remaining_shares = self
+                    .order_books
                     .entry(*stock_locate)
-                    .or_insert_with(LimitOrderBook::new)
+                    .or_default()
                     .cancel_order(
-                        order.order_executed_message.order_reference_number,
-                        order.order_executed_message.executed_shares,
+                        order.order_reference_number,
+                        order.executed_shares,
                         *price, // Must deref as we do .get, which returns a borrow, not a value (like .remove)
                         *buy_sell_indicator,
                         false,
                     )?;
-                Ok((
-                    order.order_executed_message.match_number,
-                    order.exec_price,
-                    order.printable,
-                ))
+                let execution = Execution {
+                    timestamp: order.header.timestamp,
+                    stock_locate: *stock_locate,
+                    order_reference_number: order.order_reference_number,
+                    buy_sell_indicator: *buy_sell_indicator,
+                    executed_shares: order.executed_shares,
+                    match_number: order.match_number,
+                    price: exec_price.unwrap_or(*price),
+                    printable,
+                };
+                // Fully executed orders leave the book, no delete message follows
+                if remaining_shares == 0 {
+                    self.order_price_map.remove(&order.order_reference_number);
+                }
+                Ok(execution)
             }
             None => Err(OrderBookError::NonExistentOrder),
         }
     }
 
+    /// Get the position of a resting order in the queue of its price level.
+    ///
+    /// # Arguments
+    /// * `order_reference_number` - The order to look up.
+    ///
+    /// # Returns
+    /// * `Some(QueuePosition)` - The orders and shares ahead of the order.
+    /// * `None` - If the order is not in the book.
+    pub fn get_queue_position(
+        &self,
+        order_reference_number: OrderReferenceNumber,
+    ) -> Option<QueuePosition> {
+        let (stock_locate, price, buy_sell_indicator) =
+            self.order_price_map.get(&order_reference_number)?;
+        self.order_books.get(stock_locate)?.queue_position(
+            order_reference_number,
+            *price,
+            *buy_sell_indicator,
+        )
+    }
+
     /// Replace an order in the order book. Changes the order reference number, shares, price.
+    /// The new order goes to the back of the queue, even if the price is unchanged.
     ///
     /// # Arguments
     /// * `order` - The order to replace.
     ///
     /// # Returns
     /// * `Ok(())` - If the order was replaced successfully.
-    /// * `Err(OrderBookError)` - If the order could not be replaced.
+    /// * `Err(OrderBookError)` - If the order could not be replaced, `NonExistentOrder` if the original order is not in
+    ///   the book.
     pub fn replace_order(&mut self, order: OrderReplace) -> Result<(), OrderBookError> {
+        self.replace(&order)
+    }
+
+    /// See `replace_order`.
+    fn replace(&mut self, order: &OrderReplace) -> Result<(), OrderBookError> {
         match self
             .order_price_map
             .remove(&order.original_order_reference_number)
@@ -134,7 +257,7 @@ impl OrderBookManager {
                 let mut old_order = self
                     .order_books
                     .entry(stock_locate)
-                    .or_insert_with(LimitOrderBook::new)
+                    .or_default()
                     .delete_order(
                         order.original_order_reference_number,
                         price,
@@ -154,13 +277,13 @@ impl OrderBookManager {
                 // Insert the new order into the order book.
                 self.order_books
                     .entry(stock_locate)
-                    .or_insert_with(LimitOrderBook::new)
+                    .or_default()
                     .add_order(old_order)?;
                 Ok(())
             }
             None => {
                 log::warn!("Attempted to replace non-existent order: {:?}", order);
-                Ok(())
+                Err(OrderBookError::NonExistentOrder)
             }
         }
     }
@@ -174,11 +297,17 @@ impl OrderBookManager {
     /// * `Ok(())` - If the order was cancelled successfully.
     /// * `Err(OrderBookError)` - If the order could not be cancelled.
     pub fn cancel_order(&mut self, order: OrderCancel) -> Result<(), OrderBookError> {
+        self.cancel(&order)
+    }
+
+    /// See `cancel_order`.
+    fn cancel(&mut self, order: &OrderCancel) -> Result<(), OrderBookError> {
         match self.order_price_map.get(&order.order_reference_number) {
             Some((stock_locate, price, buy_sell_indicator)) => {
-                self.order_books
+                let remaining_shares = self
+                    .order_books
                     .entry(*stock_locate)
-                    .or_insert_with(LimitOrderBook::new)
+                    .or_default()
                     .cancel_order(
                         order.order_reference_number,
                         order.canceled_shares,
@@ -186,6 +315,9 @@ impl OrderBookManager {
                         *buy_sell_indicator,
                         true,
                     )?;
+                if remaining_shares == 0 {
+                    self.order_price_map.remove(&order.order_reference_number);
+                }
                 Ok(())
             }
             None => {
@@ -202,13 +334,19 @@ impl OrderBookManager {
     /// # Returns
     /// * `Ok(())` - If the order was deleted successfully.
     /// * `Err(OrderBookError)` - If the order could not be deleted.
+    ///
     /// We remove the order from the order map (getting stock locate, price and buy sell indicator), then use this data to delete it from the order book.
     pub fn delete_order(&mut self, order: OrderDelete) -> Result<(), OrderBookError> {
+        self.delete(&order)
+    }
+
+    /// See `delete_order`.
+    fn delete(&mut self, order: &OrderDelete) -> Result<(), OrderBookError> {
         match self.order_price_map.remove(&order.order_reference_number) {
             Some((stock_locate, price, buy_sell_indicator)) => {
                 self.order_books
                     .entry(stock_locate)
-                    .or_insert_with(LimitOrderBook::new)
+                    .or_default()
                     .delete_order(order.order_reference_number, price, buy_sell_indicator)?;
                 Ok(())
             }
@@ -218,6 +356,160 @@ impl OrderBookManager {
             }
         }
     }
+
+    /// Split the books into `shards` managers, by stock locate code modulo `shards`.
+    /// Each order goes to the manager holding the book it rests in.
+    ///
+    /// # Arguments
+    /// * `shards` - The number of managers to split into, at least 1.
+    ///
+    /// # Returns
+    /// * `Vec<OrderBookManager>` - Manager `i` holds the books with `stock_locate % shards == i`.
+    pub fn split(self, shards: usize) -> Vec<Self> {
+        let mut managers: Vec<_> = (0..shards).map(|_| Self::default()).collect();
+        for (stock_locate, book) in self.order_books {
+            managers[usize::from(stock_locate) % shards]
+                .order_books
+                .insert(stock_locate, book);
+        }
+        for (order_reference_number, entry) in self.order_price_map {
+            managers[usize::from(entry.0) % shards]
+                .order_price_map
+                .insert(order_reference_number, entry);
+        }
+        managers
+    }
+
+    /// Move the books of another manager into this one, the inverse of `split`.
+    /// The managers must hold disjoint sets of books.
+    pub fn merge(&mut self, other: Self) {
+        self.order_books.extend(other.order_books);
+        self.order_price_map.extend(other.order_price_map);
+    }
+}
+
+/// Applies order messages to the order books.
+/// Emits an `Execution` event for every execution, and a `TopOfBook` event whenever the best prices of a book, or the
+/// shares at them, change.
+/// Messages are applied through the by-reference helpers behind the public methods, so only added orders, which the
+/// book keeps, are copied.
+impl<B: OrderBook> EventSink for OrderBookManager<B> {
+    fn on_message(&mut self, message: &MessageTypes, events: &mut VecDeque<Event>) {
+        let Some(header) = order_message_header(message) else {
+            return;
+        };
+        let top_of_book = |manager: &Self| {
+            manager.order_books.get(&header.stock_locate).map(|book| {
+                (
+                    book.get_best_bid(),
+                    book.get_best_bid_size(),
+                    book.get_best_ask(),
+                    book.get_best_ask_size(),
+                )
+            })
+        };
+        let before = top_of_book(self);
+
+        let result = match message {
+            MessageTypes::AddOrder(order) => self.add_order(order.clone()),
+            MessageTypes::OrderExecuted(order) => self
+                .execute(order, None, true)
+                .map(|execution| events.push_back(Event::Execution(execution))),
+            MessageTypes::OrderExecutedWithPrice(order) => self
+                .execute(
+                    &order.order_executed_message,
+                    Some(order.exec_price),
+                    order.printable,
+                )
+                .map(|execution| events.push_back(Event::Execution(execution))),
+            MessageTypes::OrderCancel(order) => self.cancel(order),
+            MessageTypes::OrderDelete(order) => self.delete(order),
+            MessageTypes::OrderReplace(order) => self.replace(order),
+            _ => Ok(()),
+        };
+        if let Err(e) = result {
+            log::warn!("Failed to apply {:?}: {}", message, e);
+        }
+
+        let after = top_of_book(self);
+        if let Some((best_bid, bid_size, best_ask, ask_size)) = after.filter(|_| after != before) {
+            events.push_back(Event::TopOfBook(TopOfBook {
+                timestamp: header.timestamp,
+                stock_locate: header.stock_locate,
+                best_bid: best_bid.map(Price4::new),
+                bid_size,
+                best_ask: best_ask.map(Price4::new),
+                ask_size,
+            }));
+        }
+    }
+}
+
+/// The operations an `OrderBookManager` needs from the book of a single stock.
+/// The manager keeps the stock, price and side of every order, so books are told where each order rests.
+pub trait OrderBook: Default {
+    /// Add an order to the back of the queue at its price.
+    ///
+    /// # Returns
+    /// * `Ok(())` - If the order was added.
+    /// * `Err(OrderBookError)` - If the order is already in the book.
+    fn add_order(&mut self, order: AddOrder) -> Result<(), OrderBookError>;
+
+    /// Cancel or execute shares off an order. Orders left with no shares are removed.
+    ///
+    /// # Arguments
+    /// * `order_reference_number` - The order to take shares off.
+    /// * `cancelled_shares` - The shares to take off.
+    /// * `price` - The price the order rests at.
+    /// * `buy_sell_indicator` - The side the order rests on.
+    /// * `order_cancellation` - True if the order is being cancelled, false if it is being executed.
+    ///
+    /// # Returns
+    /// * `Ok(u32)` - The shares left on the order.
+    /// * `Err(OrderBookError)` - If the order does not exist, or has fewer shares than `cancelled_shares`.
+    fn cancel_order(
+        &mut self,
+        order_reference_number: OrderReferenceNumber,
+        cancelled_shares: u32,
+        price: Price4,
+        buy_sell_indicator: BuySellIndicator,
+        order_cancellation: bool,
+    ) -> Result<u32, OrderBookError>;
+
+    /// Remove an order from the book.
+    ///
+    /// # Returns
+    /// * `Ok(AddOrder)` - The order, with the shares it had left.
+    /// * `Err(OrderBookError)` - If the order does not exist.
+    fn delete_order(
+        &mut self,
+        order_reference_number: OrderReferenceNumber,
+        price: Price4,
+        buy_sell_indicator: BuySellIndicator,
+    ) -> Result<AddOrder, OrderBookError>;
+
+    /// The highest bid price, `None` if there are no bids.
+    fn get_best_bid(&self) -> Option<u32>;
+
+    /// The lowest ask price, `None` if there are no asks.
+    fn get_best_ask(&self) -> Option<u32>;
+
+    /// The shares at the highest bid, 0 if there are no bids.
+    fn get_best_bid_size(&self) -> u32;
+
+    /// The shares at the lowest ask, 0 if there are no asks.
+    fn get_best_ask_size(&self) -> u32;
+
+    /// The top `levels` price levels of each side, best first.
+    fn get_depth(&self, levels: usize) -> Depth;
+
+    /// The orders and shares ahead of a resting order in the queue of its price level, `None` if it is not in the book.
+    fn queue_position(
+        &self,
+        order_reference_number: OrderReferenceNumber,
+        price: Price4,
+        buy_sell_indicator: BuySellIndicator,
+    ) -> Option<QueuePosition>;
 }
 
 /// Per stock limit order book.
@@ -234,6 +526,12 @@ pub struct LimitOrderBook {
     pub lowest_ask: u32,
 }
 
+impl Default for LimitOrderBook {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl LimitOrderBook {
     pub fn new() -> Self {
         log::debug!("Creating new limit order book");
@@ -251,7 +549,12 @@ impl LimitOrderBook {
     /// * `Ok(())` - If the order was added successfully.
     /// * `Err(OrderBookError)` - If the order could not be added.
     fn update_best_prices(&mut self) {
-        self.lowest_ask = self.ask_book.keys().next().map(|&p| p.value).unwrap_or(u32::MAX);
+        self.lowest_ask = self
+            .ask_book
+            .keys()
+            .next()
+            .map(|&p| p.value)
+            .unwrap_or(u32::MAX);
         self.highest_bid = self.bid_book.keys().next().map(|r| r.0.value).unwrap_or(0);
     }
 
@@ -287,6 +590,52 @@ impl LimitOrderBook {
         }
     }
 
+    /// Get the number of shares at the best ask price.
+    ///
+    /// # Returns
+    /// * `u32` - The shares at the best ask price, 0 if there are no asks.
+    pub fn get_best_ask_size(&self) -> u32 {
+        self.ask_book
+            .values()
+            .next()
+            .map_or(0, |bucket| bucket.share_quantity)
+    }
+
+    /// Get the number of shares at the best bid price.
+    ///
+    /// # Returns
+    /// * `u32` - The shares at the best bid price, 0 if there are no bids.
+    pub fn get_best_bid_size(&self) -> u32 {
+        self.bid_book
+            .values()
+            .next()
+            .map_or(0, |bucket| bucket.share_quantity)
+    }
+
+    /// Get the top price levels of each side of the book.
+    ///
+    /// # Arguments
+    /// * `levels` - The maximum number of levels per side.
+    ///
+    /// # Returns
+    /// * `Depth` - The levels, best first. A side has fewer than `levels` levels if the book is thinner.
+    pub fn get_depth(&self, levels: usize) -> Depth {
+        Depth {
+            bids: self
+                .bid_book
+                .iter()
+                .take(levels)
+                .map(|(price, bucket)| bucket.level(price.0))
+                .collect(),
+            asks: self
+                .ask_book
+                .iter()
+                .take(levels)
+                .map(|(price, bucket)| bucket.level(*price))
+                .collect(),
+        }
+    }
+
     pub fn add_order(&mut self, order: AddOrder) -> Result<(), OrderBookError> {
         match order.buy_sell_indicator {
             BuySellIndicator::Buy => {
@@ -307,6 +656,11 @@ impl LimitOrderBook {
         Ok(())
     }
 
+    /// Cancel or execute shares off an order. Orders left with no shares are removed, along with empty price buckets.
+    ///
+    /// # Returns
+    /// * `Ok(u32)` - The shares left on the order.
+    /// * `Err(OrderBookError)` - If the order does not exist, or has fewer shares than `cancelled_shares`.
     pub fn cancel_order(
         &mut self,
         order_reference_number: OrderReferenceNumber,
@@ -314,28 +668,41 @@ impl LimitOrderBook {
         price: Price4,
         buy_sell_indicator: BuySellIndicator,
         order_cancellation: bool, // If true, the order is being cancelled, not executed
-    ) -> Result<(), OrderBookError> {
+    ) -> Result<u32, OrderBookError> {
         match buy_sell_indicator {
             BuySellIndicator::Buy => {
                 let price_bucket = self.bid_book.get_mut(&Reverse(price)).unwrap();
-                price_bucket.cancel_order(
+                let remaining_shares = price_bucket.cancel_order(
                     order_reference_number,
                     cancelled_shares,
                     order_cancellation,
                 )?;
+
+                if price_bucket.share_quantity == 0 {
+                    log::trace!("Buy side price bucket at {} is empty, removing it", price);
+                    self.bid_book.remove(&Reverse(price));
+                }
+
                 self.update_best_prices();
+                Ok(remaining_shares)
             }
             BuySellIndicator::Sell => {
                 let price_bucket = self.ask_book.get_mut(&price).unwrap();
-                price_bucket.cancel_order(
+                let remaining_shares = price_bucket.cancel_order(
                     order_reference_number,
                     cancelled_shares,
                     order_cancellation,
                 )?;
+
+                if price_bucket.share_quantity == 0 {
+                    log::trace!("Sell side price bucket at {} is empty, removing it", price);
+                    self.ask_book.remove(&price);
+                }
+
                 self.update_best_prices();
+                Ok(remaining_shares)
             }
         }
-        Ok(())
     }
 
     pub fn delete_order(
@@ -373,10 +740,86 @@ impl LimitOrderBook {
     }
 }
 
-/// A price bucket is a collection of orders at a given price.
+impl OrderBook for LimitOrderBook {
+    fn add_order(&mut self, order: AddOrder) -> Result<(), OrderBookError> {
+        LimitOrderBook::add_order(self, order)
+    }
+
+    fn cancel_order(
+        &mut self,
+        order_reference_number: OrderReferenceNumber,
+        cancelled_shares: u32,
+        price: Price4,
+        buy_sell_indicator: BuySellIndicator,
+        order_cancellation: bool,
+    ) -> Result<u32, OrderBookError> {
+        LimitOrderBook::cancel_order(
+            self,
+            order_reference_number,
+            cancelled_shares,
+            price,
+            buy_sell_indicator,
+            order_cancellation,
+        )
+    }
+
+    fn delete_order(
+        &mut self,
+        order_reference_number: OrderReferenceNumber,
+        price: Price4,
+        buy_sell_indicator: BuySellIndicator,
+    ) -> Result<AddOrder, OrderBookError> {
+        LimitOrderBook::delete_order(self, order_reference_number, price, buy_sell_indicator)
+    }
+
+    fn get_best_bid(&self) -> Option<u32> {
+        LimitOrderBook::get_best_bid(self)
+    }
+
+    fn get_best_ask(&self) -> Option<u32> {
+        LimitOrderBook::get_best_ask(self)
+    }
+
+    fn get_best_bid_size(&self) -> u32 {
+        LimitOrderBook::get_best_bid_size(self)
+    }
+
+    fn get_best_ask_size(&self) -> u32 {
+        LimitOrderBook::get_best_ask_size(self)
+    }
+
+    fn get_depth(&self, levels: usize) -> Depth {
+        LimitOrderBook::get_depth(self, levels)
+    }
+
+    fn queue_position(
+        &self,
+        order_reference_number: OrderReferenceNumber,
+        price: Price4,
+        buy_sell_indicator: BuySellIndicator,
+    ) -> Option<QueuePosition> {
+        let price_bucket = match buy_sell_indicator {
+            BuySellIndicator::Buy => self.bid_book.get(&Reverse(price)),
+            BuySellIndicator::Sell => self.ask_book.get(&price),
+        }?;
+        price_bucket.queue_position(order_reference_number)
+    }
+}
+
+/// A price bucket is a collection of orders at a given price, in price-time priority.
+///
+/// # Fields
+/// * `share_quantity` - The total shares of the orders in the bucket.
+/// * `orders` - Order reference number > order.
+/// * `queue` - Arrival sequence > order reference number. Iterating it gives the orders in time priority.
+/// * `sequences` - Order reference number > arrival sequence, to find an order in `queue`.
+/// * `next_sequence` - The arrival sequence of the next order added.
 pub struct PriceBucket {
     pub share_quantity: u32,
     pub orders: HashMap<OrderReferenceNumber, AddOrder>, // By order reference number
+    queue: BTreeMap<u64, OrderReferenceNumber>,
+    sequences: HashMap<OrderReferenceNumber, u64>,
+    next_sequence: u64,
 }
 
 impl PriceBucket {
@@ -385,28 +828,85 @@ impl PriceBucket {
         Self {
             share_quantity: 0,
             orders: HashMap::new(),
+            queue: BTreeMap::new(),
+            sequences: HashMap::new(),
+            next_sequence: 0,
+        }
+    }
+
+    /// The orders in the bucket, in time priority (first to be filled first).
+    pub fn orders_in_priority(&self) -> impl Iterator<Item = &AddOrder> {
+        self.queue
+            .values()
+            .map(|order_reference_number| &self.orders[order_reference_number])
+    }
+
+    /// Get the position of an order in the queue of the bucket.
+    ///
+    /// # Arguments
+    /// * `order_reference_number` - The order to look up.
+    ///
+    /// # Returns
+    /// * `Some(QueuePosition)` - The orders and shares ahead of the order.
+    /// * `None` - If the order is not in the bucket.
+    pub fn queue_position(
+        &self,
+        order_reference_number: OrderReferenceNumber,
+    ) -> Option<QueuePosition> {
+        let sequence = self.sequences.get(&order_reference_number)?;
+        let (orders_ahead, shares_ahead) = self.queue.range(..sequence).fold(
+            (0, 0),
+            |(orders_ahead, shares_ahead), (_, order_reference_number)| {
+                (
+                    orders_ahead + 1,
+                    shares_ahead + self.orders[order_reference_number].shares,
+                )
+            },
+        );
+        Some(QueuePosition {
+            orders_ahead,
+            shares_ahead,
+        })
+    }
+
+    fn level(&self, price: Price4) -> PriceLevel {
+        PriceLevel {
+            price,
+            share_quantity: self.share_quantity,
+            order_count: self.orders.len(),
         }
     }
 
+    /// Add an order to the back of the queue.
     fn add_order(&mut self, order: AddOrder) -> Result<(), OrderBookError> {
-        self.share_quantity += order.shares;
-        match self.orders.insert(order.order_reference_number, order) {
-            Some(_) => Err(OrderBookError::DuplicateOrder),
-            None => Ok(()),
+        if self.orders.contains_key(&order.order_reference_number) {
+            return Err(OrderBookError::DuplicateOrder);
         }
+        self.share_quantity += order.shares;
+        self.queue
+            .insert(self.next_sequence, order.order_reference_number);
+        self.sequences
+            .insert(order.order_reference_number, self.next_sequence);
+        self.next_sequence += 1;
+        self.orders.insert(order.order_reference_number, order);
+        Ok(())
     }
 
-    /// Cancel shares off an order in order book.
+    /// Cancel shares off an order in order book. The order keeps its place in the queue.
     /// # Arguments
     /// * `order_reference_number` - The order reference number of the order to cancel.
     /// * `cancelled_shares` - The number of shares to cancel.
     /// * `order_cancellation` - If true, the order is being cancelled, not executed.
+    ///
+    /// # Returns
+    /// * `Ok(u32)` - The shares left on the order. The order is deleted if this is 0.
+    /// * `Err(OrderBookError)` - If the order does not exist, or has fewer shares than `cancelled_shares`.
     fn cancel_order(
         &mut self,
         order_reference_number: OrderReferenceNumber,
         cancelled_shares: u32,
         order_cancellation: bool, // If true, the order is being cancelled, not executed
-    ) -> Result<(), OrderBookError> {
+    ) -> Result<u32, OrderBookError> {
         let order = self
             .orders
             .get_mut(&order_reference_number)
@@ -416,13 +916,16 @@ impl PriceBucket {
         }
         self.share_quantity -= cancelled_shares;
         order.shares -= cancelled_shares;
+        let remaining_shares = order.shares;
 
-        if order.shares == 0 && order_cancellation {
-            log::warn!("Order cancellation resulted in 0 shares, deleting order");
+        if remaining_shares == 0 {
+            if order_cancellation {
+                log::warn!("Order cancellation resulted in 0 shares, deleting order");
+            }
             self.delete_order(order_reference_number)?;
         }
 
-        Ok(())
+        Ok(remaining_shares)
     }
 
     fn delete_order(
@@ -433,12 +936,16 @@ impl PriceBucket {
             .orders
             .remove(&order_reference_number)
             .ok_or(OrderBookError::NonExistentOrder)?;
+        if let Some(sequence) = self.sequences.remove(&order_reference_number) {
+            self.queue.remove(&sequence);
+        }
         self.share_quantity -= order.shares;
         Ok(order)
     }
 }
 
 #[cfg(test)]
+#[allow(unused_must_use, clippy::assertions_on_constants)]
 mod tests {
     use super::*;
     use crate::addordermessages::AddOrder;
@@ -745,4 +1252,214 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_execute_order_with_price() {
+        use crate::modifyordermessages::{OrderExecuted, OrderExecutedWithPrice};
+
+        let mut book_manager = OrderBookManager::new();
+        let header = MessageHeader::parse(&MessageHeader::generate_binary_example());
+
+        let add_order = AddOrder {
+            header: header.clone(),
+            order_reference_number: 1,
+            buy_sell_indicator: BuySellIndicator::Sell,
+            shares: 100,
+            stock: *b"AAPL    ",
+            price: Price4::new(15000u32),
+            mpid: None,
+        };
+        book_manager.add_order(add_order).unwrap();
+
+        // Executed at a different price to the one the order was resting at
+        let execution = book_manager
+            .execute_order_with_price(OrderExecutedWithPrice {
+                order_executed_message: OrderExecuted {
+                    header: header.clone(),
+                    order_reference_number: 1,
+                    executed_shares: 40,
+                    match_number: 77,
+                },
+                printable: false,
+                exec_price: Price4::new(14950u32),
+            })
+            .unwrap();
+
+        assert_eq!(execution.stock_locate, header.stock_locate);
+        assert_eq!(execution.buy_sell_indicator, BuySellIndicator::Sell);
+        assert_eq!(execution.executed_shares, 40);
+        assert_eq!(execution.match_number, 77);
+        assert_eq!(execution.price, Price4::new(14950u32));
+        assert!(!execution.printable);
+
+        // The shares come off the resting price level
+        let price_bucket = book_manager.order_books[&header.stock_locate]
+            .ask_book
+            .get(&Price4::new(15000u32))
+            .unwrap();
+        assert_eq!(price_bucket.share_quantity, 60);
+        assert_eq!(price_bucket.orders[&1].shares, 60);
+    }
+
+    #[test]
+    fn test_execute_order_with_price_full_execution() {
+        use crate::modifyordermessages::{OrderExecuted, OrderExecutedWithPrice};
+
+        let mut book_manager = OrderBookManager::new();
+        let header = MessageHeader::parse(&MessageHeader::generate_binary_example());
+
+        for (order_reference_number, price) in [(1, 15000u32), (2, 15100u32)] {
+            book_manager
+                .add_order(AddOrder {
+                    header: header.clone(),
+                    order_reference_number,
+                    buy_sell_indicator: BuySellIndicator::Sell,
+                    shares: 100,
+                    stock: *b"AAPL    ",
+                    price: Price4::new(price),
+                    mpid: None,
+                })
+                .unwrap();
+        }
+
+        // ITCH sends no delete after a full execution, so the order and its empty price level must go
+        book_manager
+            .execute_order_with_price(OrderExecutedWithPrice {
+                order_executed_message: OrderExecuted {
+                    header: header.clone(),
+                    order_reference_number: 1,
+                    executed_shares: 100,
+                    match_number: 78,
+                },
+                printable: true,
+                exec_price: Price4::new(14990u32),
+            })
+            .unwrap();
+
+        assert!(!book_manager.order_price_map.contains_key(&1));
+        let order_book = &book_manager.order_books[&header.stock_locate];
+        assert!(!order_book.ask_book.contains_key(&Price4::new(15000u32)));
+        assert_eq!(order_book.get_best_ask(), Some(15100));
+        assert_eq!(order_book.get_best_ask_size(), 100);
+    }
+
+    #[test]
+    fn test_get_depth() {
+        let mut book_manager = OrderBookManager::new();
+        let header = MessageHeader::parse(&MessageHeader::generate_binary_example());
+
+        let orders = [
+            (1, BuySellIndicator::Buy, 100, 15000u32),
+            (2, BuySellIndicator::Buy, 50, 15000u32),
+            (3, BuySellIndicator::Buy, 10, 14900u32),
+            (4, BuySellIndicator::Buy, 20, 14800u32),
+            (5, BuySellIndicator::Sell, 70, 15100u32),
+        ];
+        for (order_reference_number, buy_sell_indicator, shares, price) in orders {
+            book_manager
+                .add_order(AddOrder {
+                    header: header.clone(),
+                    order_reference_number,
+                    buy_sell_indicator,
+                    shares,
+                    stock: *b"AAPL    ",
+                    price: Price4::new(price),
+                    mpid: None,
+                })
+                .unwrap();
+        }
+
+        let depth = book_manager.order_books[&header.stock_locate].get_depth(2);
+        assert_eq!(
+            depth.bids,
+            [
+                PriceLevel {
+                    price: Price4::new(15000u32),
+                    share_quantity: 150,
+                    order_count: 2,
+                },
+                PriceLevel {
+                    price: Price4::new(14900u32),
+                    share_quantity: 10,
+                    order_count: 1,
+                },
+            ]
+        );
+        assert_eq!(
+            depth.asks,
+            [PriceLevel {
+                price: Price4::new(15100u32),
+                share_quantity: 70,
+                order_count: 1,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_queue_position() {
+        let mut book_manager = OrderBookManager::new();
+        let header = MessageHeader::parse(&MessageHeader::generate_binary_example());
+
+        for (order_reference_number, shares) in [(1, 100), (2, 200), (3, 300)] {
+            book_manager
+                .add_order(AddOrder {
+                    header: header.clone(),
+                    order_reference_number,
+                    buy_sell_indicator: BuySellIndicator::Buy,
+                    shares,
+                    stock: *b"AAPL    ",
+                    price: Price4::new(15000u32),
+                    mpid: None,
+                })
+                .unwrap();
+        }
+        let position = |book_manager: &OrderBookManager, order_reference_number| {
+            book_manager
+                .get_queue_position(order_reference_number)
+                .map(|position| (position.orders_ahead, position.shares_ahead))
+        };
+        assert_eq!(position(&book_manager, 1), Some((0, 0)));
+        assert_eq!(position(&book_manager, 3), Some((2, 300)));
+
+        // A partial cancel keeps priority
+        book_manager
+            .cancel_order(OrderCancel {
+                header: header.clone(),
+                order_reference_number: 1,
+                canceled_shares: 60,
+            })
+            .unwrap();
+        assert_eq!(position(&book_manager, 1), Some((0, 0)));
+        assert_eq!(position(&book_manager, 3), Some((2, 240)));
+
+        // A replace goes to the back of the queue, even at the same price
+        book_manager
+            .replace_order(OrderReplace {
+                header: header.clone(),
+                original_order_reference_number: 1,
+                new_order_reference_number: 4,
+                shares: 40,
+                price: Price4::new(15000u32),
+            })
+            .unwrap();
+        assert_eq!(position(&book_manager, 1), None);
+        assert_eq!(position(&book_manager, 2), Some((0, 0)));
+        assert_eq!(position(&book_manager, 4), Some((2, 500)));
+
+        // A delete moves everything behind it forward
+        book_manager
+            .delete_order(OrderDelete {
+                header: header.clone(),
+                order_reference_number: 2,
+            })
+            .unwrap();
+        assert_eq!(position(&book_manager, 4), Some((1, 300)));
+
+        let book = &book_manager.order_books[&header.stock_locate];
+        let queue: Vec<_> = book.bid_book[&Reverse(Price4::new(15000u32))]
+            .orders_in_priority()
+            .map(|order| order.order_reference_number)
+            .collect();
+        assert_eq!(queue, [3, 4]);
+    }
 }
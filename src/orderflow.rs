@@ -0,0 +1,467 @@
+use crate::addordermessages::AddOrder;
+use crate::enums::{BuySellIndicator, MessageTypes};
+use crate::messageheader::MessageHeader;
+use crate::modifyordermessages::{
+    OrderCancel, OrderDelete, OrderExecuted, OrderExecutedWithPrice, OrderReplace,
+};
+use crate::orderbook::{OrderReferenceNumber, StockLocateCode};
+use crate::stockmessages::StockDirectory;
+use crate::types::{GenerateExampleMessage, Parse, Price4, PriceConversions, Stock};
+use fastrand::Rng;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+/// 09:30, in nanoseconds since midnight.
+const MARKET_OPEN: u64 = 34_200_000_000_000;
+/// One cent, in `Price4` units.
+const TICK: u32 = 100;
+/// Resting orders per stock the flow hovers around. Stocks with more only see deletes and executions until they drop.
+const ORDERS_PER_STOCK: usize = 200;
+
+/// A resting order of the generated flow.
+struct LiveOrder {
+    stock: usize,
+    buy_sell_indicator: BuySellIndicator,
+    price: u32,
+    shares: u32,
+}
+
+/// The resting orders of a single stock, by side and price, in time priority.
+///
+/// # Fields
+/// * `stock_locate` - The stock locate code of the stock.
+/// * `reference_price` - Where quoting starts while a side is empty.
+/// * `bids` - Price > order reference numbers, in time priority.
+/// * `asks` - Price > order reference numbers, in time priority.
+/// * `orders` - The number of resting orders.
+struct StockFlow {
+    stock_locate: StockLocateCode,
+    reference_price: u32,
+    bids: BTreeMap<u32, VecDeque<OrderReferenceNumber>>,
+    asks: BTreeMap<u32, VecDeque<OrderReferenceNumber>>,
+    orders: usize,
+}
+
+impl StockFlow {
+    fn levels(
+        &mut self,
+        buy_sell_indicator: BuySellIndicator,
+    ) -> &mut BTreeMap<u32, VecDeque<OrderReferenceNumber>> {
+        match buy_sell_indicator {
+            BuySellIndicator::Buy => &mut self.bids,
+            BuySellIndicator::Sell => &mut self.asks,
+        }
+    }
+
+    fn best_bid(&self) -> Option<u32> {
+        self.bids.keys().next_back().copied()
+    }
+
+    fn best_ask(&self) -> Option<u32> {
+        self.asks.keys().next().copied()
+    }
+
+    /// A price for a new order, a few ticks from the best price of the other side but never crossing it.
+    /// Most orders land on or next to the touch, as in real ITCH feeds, so spreads are mostly a tick or two.
+    fn quote(&self, rng: &mut Rng, buy_sell_indicator: BuySellIndicator) -> u32 {
+        // Geometric, so each tick further from the touch is about half as likely
+        let mut ticks = 0;
+        while ticks < 50 && rng.bool() {
+            ticks += 1;
+        }
+        match buy_sell_indicator {
+            BuySellIndicator::Buy => {
+                let best_ask = self
+                    .best_ask()
+                    .or(self.best_bid().map(|best_bid| best_bid + TICK))
+                    .unwrap_or(self.reference_price + TICK);
+                best_ask.saturating_sub((ticks + 1) * TICK).max(TICK)
+            }
+            BuySellIndicator::Sell => {
+                let best_bid = self
+                    .best_bid()
+                    .or(self
+                        .best_ask()
+                        .map(|best_ask| best_ask.saturating_sub(TICK)))
+                    .unwrap_or(self.reference_price);
+                best_bid + (ticks + 1) * TICK
+            }
+        }
+    }
+}
+
+/// Generates a coherent ITCH order flow, as a stand in for a NASDAQ file in benchmarks and tests.
+/// Yields a `StockDirectory` message per stock, followed by `messages` order messages with increasing timestamps.
+///
+/// Every message refers to an order that is resting at that point, so the flow applies to an `OrderBookManager`
+/// without errors, and books never cross. The mix follows a typical day of NASDAQ order messages:
+/// * Stocks are picked with a Zipf distribution, so a few stocks see most of the flow.
+/// * Prices are in cents, from $5 to $500, and cluster within a few ticks of the spread.
+/// * About 45% of messages are adds, 35% deletes, 10% replaces, 5% cancels and 5% executions.
+/// * Executions take the oldest order at the best price, as a marketable order would.
+///
+/// The same seed always generates the same flow.
+///
+/// # Fields
+/// * `rng` - The source of randomness.
+/// * `stocks` - The state of each stock, indexed by stock locate code - 1.
+/// * `weights` - Cumulative Zipf weights of the stocks.
+/// * `orders` - Order reference number > resting order.
+/// * `live` - Order reference numbers of the resting orders, for picking one at random.
+/// * `positions` - Order reference number > position in `live`.
+/// * `directory` - Stock directory messages not yet yielded.
+/// * `remaining` - Order messages left to yield.
+/// * `timestamp` - Nanoseconds since midnight of the last message.
+/// * `next_order_reference_number` - The reference number of the next order added.
+/// * `next_match_number` - The match number of the next execution.
+pub struct OrderFlow {
+    rng: Rng,
+    stocks: Vec<StockFlow>,
+    weights: Vec<f64>,
+    orders: HashMap<OrderReferenceNumber, LiveOrder>,
+    live: Vec<OrderReferenceNumber>,
+    positions: HashMap<OrderReferenceNumber, usize>,
+    directory: VecDeque<MessageTypes>,
+    remaining: usize,
+    timestamp: u64,
+    next_order_reference_number: OrderReferenceNumber,
+    next_match_number: u64,
+}
+
+impl OrderFlow {
+    /// # Arguments
+    /// * `seed` - Seeds the flow.
+    /// * `stocks` - The number of stocks, with stock locate codes from 1.
+    /// * `messages` - The number of order messages to generate, after the stock directory.
+    pub fn new(seed: u64, stocks: u16, messages: usize) -> Self {
+        assert!(stocks > 0, "At least one stock is needed");
+        let mut rng = Rng::with_seed(seed);
+        let example = StockDirectory::parse(&StockDirectory::generate_binary_example()).unwrap();
+        let mut directory = VecDeque::new();
+        let mut flows = Vec::new();
+        let mut weights = Vec::new();
+        let mut total = 0.0;
+        for stock_locate in 1..=stocks {
+            directory.push_back(MessageTypes::StockDirectory(StockDirectory {
+                header: MessageHeader::example(stock_locate, MARKET_OPEN),
+                stock: symbol(stock_locate),
+                ..example.clone()
+            }));
+            // Log uniform from $5 to $500, in cents
+            let dollars = (5f64.ln() + rng.f64() * 100f64.ln()).exp();
+            flows.push(StockFlow {
+                stock_locate,
+                reference_price: (dollars * 100.0) as u32 * TICK,
+                bids: BTreeMap::new(),
+                asks: BTreeMap::new(),
+                orders: 0,
+            });
+            total += 1.0 / f64::from(stock_locate);
+            weights.push(total);
+        }
+        Self {
+            rng,
+            stocks: flows,
+            weights,
+            orders: HashMap::new(),
+            live: Vec::new(),
+            positions: HashMap::new(),
+            directory,
+            remaining: messages,
+            timestamp: MARKET_OPEN,
+            next_order_reference_number: 1,
+            next_match_number: 1,
+        }
+    }
+
+    /// Pick a stock, busier stocks more often.
+    fn pick_stock(&mut self) -> usize {
+        let target = self.rng.f64() * self.weights[self.weights.len() - 1];
+        self.weights
+            .partition_point(|&weight| weight < target)
+            .min(self.weights.len() - 1)
+    }
+
+    fn add(&mut self, stock: usize, buy_sell_indicator: BuySellIndicator, price: u32, shares: u32) {
+        let order_reference_number = self.next_order_reference_number;
+        self.next_order_reference_number += 1;
+        let flow = &mut self.stocks[stock];
+        flow.levels(buy_sell_indicator)
+            .entry(price)
+            .or_default()
+            .push_back(order_reference_number);
+        flow.orders += 1;
+        self.orders.insert(
+            order_reference_number,
+            LiveOrder {
+                stock,
+                buy_sell_indicator,
+                price,
+                shares,
+            },
+        );
+        self.positions
+            .insert(order_reference_number, self.live.len());
+        self.live.push(order_reference_number);
+    }
+
+    fn remove(&mut self, order_reference_number: OrderReferenceNumber) -> LiveOrder {
+        let order = self.orders.remove(&order_reference_number).unwrap();
+        let position = self.positions.remove(&order_reference_number).unwrap();
+        self.live.swap_remove(position);
+        if let Some(&moved) = self.live.get(position) {
+            self.positions.insert(moved, position);
+        }
+        let flow = &mut self.stocks[order.stock];
+        flow.orders -= 1;
+        let levels = flow.levels(order.buy_sell_indicator);
+        let queue = levels.get_mut(&order.price).unwrap();
+        queue.retain(|&queued| queued != order_reference_number);
+        if queue.is_empty() {
+            levels.remove(&order.price);
+        }
+        order
+    }
+
+    /// A random round lot, or now and then an odd lot.
+    fn shares(&mut self) -> u32 {
+        if self.rng.u8(0..10) == 0 {
+            self.rng.u32(1..100)
+        } else {
+            self.rng.u32(1..=10) * 100
+        }
+    }
+
+    fn next_message(&mut self) -> MessageTypes {
+        self.timestamp += self.rng.u64(1..2_000);
+        let stock = self.pick_stock();
+        let flow = &self.stocks[stock];
+        let stock_locate = flow.stock_locate;
+        let header = MessageHeader::example(stock_locate, self.timestamp);
+        let roll = match flow.orders {
+            0 => 0,
+            orders if orders >= ORDERS_PER_STOCK => self.rng.u8(45..100),
+            _ => self.rng.u8(0..100),
+        };
+        let buy_sell_indicator = if self.rng.bool() {
+            BuySellIndicator::Buy
+        } else {
+            BuySellIndicator::Sell
+        };
+
+        match roll {
+            0..45 => {
+                let price = self.stocks[stock].quote(&mut self.rng, buy_sell_indicator);
+                let shares = self.shares();
+                let order_reference_number = self.next_order_reference_number;
+                self.add(stock, buy_sell_indicator, price, shares);
+                MessageTypes::AddOrder(AddOrder {
+                    header,
+                    order_reference_number,
+                    buy_sell_indicator,
+                    shares,
+                    stock: symbol(stock_locate),
+                    price: Price4::new(price),
+                    mpid: None,
+                })
+            }
+            45..95 => {
+                // Replaces, cancels and deletes of a resting order of the stock
+                let order_reference_number = self.pick_order(stock);
+                let order = &self.orders[&order_reference_number];
+                match roll {
+                    45..55 => {
+                        let (buy_sell_indicator, shares) = (order.buy_sell_indicator, order.shares);
+                        self.remove(order_reference_number);
+                        let price = self.stocks[stock].quote(&mut self.rng, buy_sell_indicator);
+                        let new_shares = if self.rng.bool() {
+                            shares
+                        } else {
+                            self.shares()
+                        };
+                        let new_order_reference_number = self.next_order_reference_number;
+                        self.add(stock, buy_sell_indicator, price, new_shares);
+                        MessageTypes::OrderReplace(OrderReplace {
+                            header,
+                            original_order_reference_number: order_reference_number,
+                            new_order_reference_number,
+                            shares: new_shares,
+                            price: Price4::new(price),
+                        })
+                    }
+                    55..60 if order.shares > 1 => {
+                        let canceled_shares = self.rng.u32(1..order.shares);
+                        self.orders.get_mut(&order_reference_number).unwrap().shares -=
+                            canceled_shares;
+                        MessageTypes::OrderCancel(OrderCancel {
+                            header,
+                            order_reference_number,
+                            canceled_shares,
+                        })
+                    }
+                    _ => {
+                        self.remove(order_reference_number);
+                        MessageTypes::OrderDelete(OrderDelete {
+                            header,
+                            order_reference_number,
+                        })
+                    }
+                }
+            }
+            _ => self.execute(stock, buy_sell_indicator, header),
+        }
+    }
+
+    /// Pick a resting order of a stock. Falls back to any resting order of the stock's side of the book.
+    fn pick_order(&mut self, stock: usize) -> OrderReferenceNumber {
+        // Most stocks hold a small share of the resting orders, so sample a few before walking the book
+        for _ in 0..8 {
+            let order_reference_number = self.live[self.rng.usize(..self.live.len())];
+            if self.orders[&order_reference_number].stock == stock {
+                return order_reference_number;
+            }
+        }
+        let flow = &self.stocks[stock];
+        let levels = if flow.bids.is_empty() || (self.rng.bool() && !flow.asks.is_empty()) {
+            &flow.asks
+        } else {
+            &flow.bids
+        };
+        let queues: Vec<_> = levels.values().collect();
+        let queue = queues[self.rng.usize(..queues.len())];
+        queue[self.rng.usize(..queue.len())]
+    }
+
+    /// Execute shares of the oldest order at the best price of a side, falling back to the other side if it is empty.
+    fn execute(
+        &mut self,
+        stock: usize,
+        buy_sell_indicator: BuySellIndicator,
+        header: MessageHeader,
+    ) -> MessageTypes {
+        let flow = &self.stocks[stock];
+        let best = |buy_sell_indicator| match buy_sell_indicator {
+            BuySellIndicator::Buy => flow.bids.iter().next_back(),
+            BuySellIndicator::Sell => flow.asks.iter().next(),
+        };
+        let (_, queue) = best(buy_sell_indicator)
+            .or_else(|| best(opposite(buy_sell_indicator)))
+            .unwrap();
+        let order_reference_number = queue[0];
+        let order = &self.orders[&order_reference_number];
+        let executed_shares = match self.rng.bool() {
+            true => order.shares,
+            false => self.rng.u32(1..=order.shares),
+        };
+        let price = order.price;
+        if executed_shares == order.shares {
+            self.remove(order_reference_number);
+        } else {
+            self.orders.get_mut(&order_reference_number).unwrap().shares -= executed_shares;
+        }
+        let match_number = self.next_match_number;
+        self.next_match_number += 1;
+        let executed = OrderExecuted {
+            header,
+            order_reference_number,
+            executed_shares,
+            match_number,
+        };
+        // A few executions are at a price other than the resting price, e.g. in a cross
+        if self.rng.u8(0..10) == 0 {
+            MessageTypes::OrderExecutedWithPrice(OrderExecutedWithPrice {
+                order_executed_message: executed,
+                printable: true,
+                exec_price: Price4::new(price + TICK),
+            })
+        } else {
+            MessageTypes::OrderExecuted(executed)
+        }
+    }
+}
+
+impl Iterator for OrderFlow {
+    type Item = MessageTypes;
+
+    fn next(&mut self) -> Option<MessageTypes> {
+        if let Some(message) = self.directory.pop_front() {
+            return Some(message);
+        }
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        Some(self.next_message())
+    }
+}
+
+fn opposite(buy_sell_indicator: BuySellIndicator) -> BuySellIndicator {
+    match buy_sell_indicator {
+        BuySellIndicator::Buy => BuySellIndicator::Sell,
+        BuySellIndicator::Sell => BuySellIndicator::Buy,
+    }
+}
+
+/// A ticker for a stock locate code, e.g. `S0001`.
+fn symbol(stock_locate: StockLocateCode) -> Stock {
+    let mut stock = *b"        ";
+    let ticker = format!("S{stock_locate:04}");
+    stock[..ticker.len()].copy_from_slice(ticker.as_bytes());
+    stock
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orderbook::OrderBookManager;
+
+    #[test]
+    fn test_order_flow() {
+        let flow: Vec<_> = OrderFlow::new(1, 50, 50_000).collect();
+        assert_eq!(flow.len(), 50_050);
+        assert_eq!(
+            OrderFlow::new(1, 50, 1000)
+                .nth(1000)
+                .map(|m| format!("{m:?}")),
+            Some(format!("{:?}", flow[1000]))
+        );
+
+        // Every message applies cleanly, and the books never cross
+        let mut order_book_manager = OrderBookManager::new();
+        let mut counts = HashMap::new();
+        for message in flow.into_iter().skip(50) {
+            let (kind, result) = match message {
+                MessageTypes::AddOrder(order) => ("add", order_book_manager.add_order(order)),
+                MessageTypes::OrderExecuted(order) => (
+                    "execute",
+                    order_book_manager.execute_order(order).map(|_| ()),
+                ),
+                MessageTypes::OrderExecutedWithPrice(order) => (
+                    "execute",
+                    order_book_manager
+                        .execute_order_with_price(order)
+                        .map(|_| ()),
+                ),
+                MessageTypes::OrderCancel(order) => {
+                    ("cancel", order_book_manager.cancel_order(order))
+                }
+                MessageTypes::OrderDelete(order) => {
+                    ("delete", order_book_manager.delete_order(order))
+                }
+                MessageTypes::OrderReplace(order) => {
+                    ("replace", order_book_manager.replace_order(order))
+                }
+                message => panic!("Unexpected message {message:?}"),
+            };
+            result.unwrap();
+            *counts.entry(kind).or_insert(0) += 1;
+        }
+        for book in order_book_manager.order_books.values() {
+            if let (Some(best_bid), Some(best_ask)) = (book.get_best_bid(), book.get_best_ask()) {
+                assert!(best_bid < best_ask);
+            }
+        }
+        for kind in ["add", "execute", "cancel", "delete", "replace"] {
+            assert!(counts[kind] > 1000, "Too few {kind} messages: {counts:?}");
+        }
+    }
+}
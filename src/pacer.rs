@@ -0,0 +1,72 @@
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Gaps shorter than this are not slept, as sleeps are not that precise. Lateness does not accumulate,
+/// as every message is due relative to the first one.
+const MIN_SLEEP: Duration = Duration::from_millis(1);
+
+/// Paces a replay to the exchange timestamps of its messages, at a multiple of real time.
+///
+/// # Fields
+/// * `speed` - The multiple of real time, e.g. 1.0 for wall clock speed or 10.0 for 10x.
+/// * `origin` - The timestamp of the first message, and when it was replayed.
+pub struct Pacer {
+    speed: f64,
+    origin: Option<(u64, Instant)>,
+}
+
+impl Pacer {
+    /// # Arguments
+    /// * `speed` - The multiple of real time, must be positive.
+    pub fn new(speed: f64) -> Self {
+        assert!(
+            speed.is_finite() && speed > 0.0,
+            "Replay speed must be positive"
+        );
+        Self {
+            speed,
+            origin: None,
+        }
+    }
+
+    /// Sleep until a message is due. The first message is due immediately, and the rest are due once the gap
+    /// between their timestamp and the first one, divided by the speed, has passed.
+    ///
+    /// # Arguments
+    /// * `timestamp` - The timestamp of the message, in nanoseconds since midnight.
+    pub fn wait_until(&mut self, timestamp: u64) {
+        let (origin_timestamp, origin_instant) = *self
+            .origin
+            .get_or_insert_with(|| (timestamp, Instant::now()));
+        let offset = timestamp.saturating_sub(origin_timestamp) as f64 / self.speed;
+        let due = origin_instant + Duration::from_nanos(offset as u64);
+        let ahead = due.saturating_duration_since(Instant::now());
+        if ahead >= MIN_SLEEP {
+            thread::sleep(ahead);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pacer() {
+        let mut pacer = Pacer::new(10.0);
+        let start = Instant::now();
+        pacer.wait_until(34_200_000_000_000);
+        assert!(start.elapsed() < Duration::from_millis(500));
+
+        // 200ms of exchange time at 10x
+        pacer.wait_until(34_200_200_000_000);
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_millis(20), "{elapsed:?}");
+        assert!(elapsed < Duration::from_millis(500), "{elapsed:?}");
+
+        // Timestamps that are already due do not sleep
+        let start = Instant::now();
+        pacer.wait_until(34_200_100_000_000);
+        assert!(start.elapsed() < Duration::from_millis(500));
+    }
+}
@@ -0,0 +1,400 @@
+use crate::enums::{
+    BuySellIndicator, MarketMakerMode, MarketParticipantState, MessageTypes, SystemEventCode,
+};
+use crate::events::{Event, EventSink, TopOfBook};
+use crate::modifyordermessages::OrderExecuted;
+use crate::orderbook::{OrderReferenceNumber, StockLocateCode};
+use crate::types::{Price4, MPID};
+use std::collections::{HashMap, VecDeque};
+
+/// The registration of a market participant in a stock, from a `MarketParticipantPosition` ('L') message.
+///
+/// # Fields
+/// * `mpid` - The market participant identifier.
+/// * `primary_market_maker` - True if the participant is the primary market maker of the stock.
+/// * `market_maker_mode` - The quoting obligations of the participant.
+/// * `market_participant_state` - Whether the participant is active in the stock.
+/// * `timestamp` - Nanoseconds since midnight, of the latest message for the participant.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MarketMaker {
+    pub mpid: MPID,
+    pub primary_market_maker: bool,
+    pub market_maker_mode: MarketMakerMode,
+    pub market_participant_state: MarketParticipantState,
+    pub timestamp: u64,
+}
+
+impl MarketMaker {
+    pub fn is_active(&self) -> bool {
+        self.market_participant_state == MarketParticipantState::Active
+    }
+}
+
+/// Displayed liquidity statistics of a market participant, from its attributed ('F') orders.
+///
+/// # Fields
+/// * `orders_added` - The attributed orders added.
+/// * `shares_displayed` - The shares displayed by attributed orders when added or replaced.
+/// * `executions` - The executions against attributed orders.
+/// * `shares_executed` - The shares executed against attributed orders.
+/// * `time_at_inside` - Nanoseconds an attributed order of the participant rested at the best bid or best ask,
+///   summed across stocks. An interval is counted once the inside of its stock next changes, or at the end of messages.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ParticipantStats {
+    pub orders_added: u64,
+    pub shares_displayed: u64,
+    pub executions: u64,
+    pub shares_executed: u64,
+    pub time_at_inside: u64,
+}
+
+/// A resting attributed order.
+#[derive(Debug, Clone, Copy)]
+struct AttributedOrder {
+    mpid: MPID,
+    buy_sell_indicator: BuySellIndicator,
+    price: Price4,
+    shares: u32,
+}
+
+/// The participants with an attributed order at the inside of a stock.
+///
+/// # Fields
+/// * `best_bid` - The best bid as of the last `TopOfBook` event.
+/// * `best_ask` - The best ask as of the last `TopOfBook` event.
+/// * `since` - Nanoseconds since midnight, of the last `TopOfBook` event.
+/// * `mpids` - The participants with an attributed order at `best_bid` or `best_ask`.
+#[derive(Debug, Default)]
+struct Inside {
+    best_bid: Option<Price4>,
+    best_ask: Option<Price4>,
+    since: u64,
+    mpids: Vec<MPID>,
+}
+
+/// Keeps a registry of the market makers of every stock from `MarketParticipantPosition` ('L') messages, and
+/// statistics for every MPID from the attributed add orders ('F') that carry it.
+///
+/// Time at the inside is measured against the `TopOfBook` events of an `OrderBookManager`, so register this after it.
+/// Orders added before this sink saw any messages, e.g. before `--start`, are not attributed.
+///
+/// # Fields
+/// * `market_makers` - The market makers of each stock, in the order they were first registered.
+/// * `stats` - The statistics of each MPID seen on an attributed order.
+#[derive(Debug, Default)]
+pub struct ParticipantTracker {
+    pub market_makers: HashMap<StockLocateCode, Vec<MarketMaker>>,
+    pub stats: HashMap<MPID, ParticipantStats>,
+    orders: HashMap<StockLocateCode, HashMap<OrderReferenceNumber, AttributedOrder>>,
+    insides: HashMap<StockLocateCode, Inside>,
+}
+
+impl ParticipantTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The market makers registered in a stock, in the order they were first registered.
+    pub fn get_market_makers(&self, stock_locate: StockLocateCode) -> &[MarketMaker] {
+        self.market_makers
+            .get(&stock_locate)
+            .map_or(&[], |market_makers| market_makers.as_slice())
+    }
+
+    /// The primary market maker of a stock, `None` if there is none.
+    pub fn primary_market_maker(&self, stock_locate: StockLocateCode) -> Option<&MarketMaker> {
+        self.get_market_makers(stock_locate)
+            .iter()
+            .find(|market_maker| market_maker.primary_market_maker)
+    }
+
+    pub fn get_stats(&self, mpid: &MPID) -> Option<&ParticipantStats> {
+        self.stats.get(mpid)
+    }
+
+    /// The participants with an attributed order at the best bid or best ask of a stock.
+    pub fn at_inside(&self, stock_locate: StockLocateCode) -> &[MPID] {
+        self.insides
+            .get(&stock_locate)
+            .map_or(&[], |inside| inside.mpids.as_slice())
+    }
+
+    fn register(&mut self, stock_locate: StockLocateCode, market_maker: MarketMaker) {
+        let market_makers = self.market_makers.entry(stock_locate).or_default();
+        match market_makers
+            .iter_mut()
+            .find(|registered| registered.mpid == market_maker.mpid)
+        {
+            Some(registered) => *registered = market_maker,
+            None => market_makers.push(market_maker),
+        }
+    }
+
+    /// Take shares off an attributed order, removing it once it has none left.
+    ///
+    /// # Returns
+    /// * `Option<MPID>` - The participant of the order, `None` if the order is not attributed.
+    fn reduce_order(
+        &mut self,
+        stock_locate: StockLocateCode,
+        order_reference_number: OrderReferenceNumber,
+        shares: u32,
+    ) -> Option<MPID> {
+        let orders = self.orders.get_mut(&stock_locate)?;
+        let order = orders.get_mut(&order_reference_number)?;
+        let mpid = order.mpid;
+        order.shares = order.shares.saturating_sub(shares);
+        if order.shares == 0 {
+            orders.remove(&order_reference_number);
+        }
+        Some(mpid)
+    }
+
+    fn execute_order(&mut self, order: &OrderExecuted) {
+        if let Some(mpid) = self.reduce_order(
+            order.header.stock_locate,
+            order.order_reference_number,
+            order.executed_shares,
+        ) {
+            let stats = self.stats.entry(mpid).or_default();
+            stats.executions += 1;
+            stats.shares_executed += u64::from(order.executed_shares);
+        }
+    }
+
+    /// Credit the participants at the inside of a stock with the time since the last change, then move to the new
+    /// inside.
+    fn update_inside(&mut self, top_of_book: &TopOfBook) {
+        let inside = self.insides.entry(top_of_book.stock_locate).or_default();
+        let elapsed = top_of_book.timestamp.saturating_sub(inside.since);
+        for mpid in &inside.mpids {
+            self.stats.entry(*mpid).or_default().time_at_inside += elapsed;
+        }
+
+        inside.best_bid = top_of_book.best_bid;
+        inside.best_ask = top_of_book.best_ask;
+        inside.since = top_of_book.timestamp;
+        inside.mpids.clear();
+        if let Some(orders) = self.orders.get(&top_of_book.stock_locate) {
+            for order in orders.values() {
+                let best = match order.buy_sell_indicator {
+                    BuySellIndicator::Buy => inside.best_bid,
+                    BuySellIndicator::Sell => inside.best_ask,
+                };
+                if best == Some(order.price) && !inside.mpids.contains(&order.mpid) {
+                    inside.mpids.push(order.mpid);
+                }
+            }
+        }
+    }
+
+    /// Credit the participants at the inside of every stock up to a time of day, e.g. the end of messages.
+    fn close_insides(&mut self, timestamp: u64) {
+        for inside in self.insides.values_mut() {
+            let elapsed = timestamp.saturating_sub(inside.since);
+            for mpid in &inside.mpids {
+                self.stats.entry(*mpid).or_default().time_at_inside += elapsed;
+            }
+            inside.since = timestamp;
+        }
+    }
+}
+
+impl EventSink for ParticipantTracker {
+    fn on_message(&mut self, message: &MessageTypes, _events: &mut VecDeque<Event>) {
+        match message {
+            MessageTypes::MarketParticipantPosition(position) => self.register(
+                position.header.stock_locate,
+                MarketMaker {
+                    mpid: position.mp_id.to_be_bytes(),
+                    primary_market_maker: position.primary_market_maker,
+                    market_maker_mode: position.market_maker_mode,
+                    market_participant_state: position.market_participant_state,
+                    timestamp: position.header.timestamp,
+                },
+            ),
+            MessageTypes::AddOrder(order) => {
+                if let Some(mpid) = order.mpid {
+                    let stats = self.stats.entry(mpid).or_default();
+                    stats.orders_added += 1;
+                    stats.shares_displayed += u64::from(order.shares);
+                    self.orders
+                        .entry(order.header.stock_locate)
+                        .or_default()
+                        .insert(
+                            order.order_reference_number,
+                            AttributedOrder {
+                                mpid,
+                                buy_sell_indicator: order.buy_sell_indicator,
+                                price: order.price,
+                                shares: order.shares,
+                            },
+                        );
+                }
+            }
+            MessageTypes::OrderExecuted(order) => self.execute_order(order),
+            MessageTypes::OrderExecutedWithPrice(order) => {
+                self.execute_order(&order.order_executed_message)
+            }
+            MessageTypes::OrderCancel(order) => {
+                self.reduce_order(
+                    order.header.stock_locate,
+                    order.order_reference_number,
+                    order.canceled_shares,
+                );
+            }
+            MessageTypes::OrderDelete(order) => {
+                if let Some(orders) = self.orders.get_mut(&order.header.stock_locate) {
+                    orders.remove(&order.order_reference_number);
+                }
+            }
+            MessageTypes::OrderReplace(order) => {
+                // The replacement keeps the attribution of the original order
+                let Some(orders) = self.orders.get_mut(&order.header.stock_locate) else {
+                    return;
+                };
+                if let Some(mut replaced) = orders.remove(&order.original_order_reference_number) {
+                    replaced.price = order.price;
+                    replaced.shares = order.shares;
+                    orders.insert(order.new_order_reference_number, replaced);
+                    self.stats
+                        .entry(replaced.mpid)
+                        .or_default()
+                        .shares_displayed += u64::from(order.shares);
+                }
+            }
+            MessageTypes::SystemEvent(system_event)
+                if system_event.event_code == SystemEventCode::EndOfMessages =>
+            {
+                self.close_insides(system_event.header.timestamp);
+            }
+            _ => {}
+        }
+    }
+
+    fn on_event(&mut self, event: &Event, _events: &mut VecDeque<Event>) {
+        if let Event::TopOfBook(top_of_book) = event {
+            if self.orders.contains_key(&top_of_book.stock_locate)
+                || self.insides.contains_key(&top_of_book.stock_locate)
+            {
+                self.update_inside(top_of_book);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::addordermessages::AddOrder;
+    use crate::events::Dispatcher;
+    use crate::messageheader::MessageHeader;
+    use crate::modifyordermessages::{OrderDelete, OrderReplace};
+    use crate::orderbook::OrderBookManager;
+    use crate::stockmessages::MarketParticipantPosition;
+    use crate::systemmessages::SystemEventMessage;
+    use crate::types::PriceConversions;
+    use std::sync::{Arc, RwLock};
+
+    fn add_order(
+        timestamp: u64,
+        order_reference_number: u64,
+        price: u32,
+        mpid: Option<MPID>,
+    ) -> MessageTypes {
+        MessageTypes::AddOrder(AddOrder {
+            mpid,
+            ..AddOrder::example(
+                7,
+                timestamp,
+                order_reference_number,
+                BuySellIndicator::Buy,
+                price,
+            )
+        })
+    }
+
+    fn position(timestamp: u64, mpid: &MPID, state: MarketParticipantState) -> MessageTypes {
+        MessageTypes::MarketParticipantPosition(MarketParticipantPosition {
+            header: MessageHeader::example(7, timestamp),
+            mp_id: u32::from_be_bytes(*mpid),
+            stock: *b"AAPL    ",
+            primary_market_maker: mpid == b"GSCO",
+            market_maker_mode: MarketMakerMode::Normal,
+            market_participant_state: state,
+        })
+    }
+
+    #[test]
+    fn test_participant_tracker() {
+        let tracker = Arc::new(RwLock::new(ParticipantTracker::new()));
+        let mut dispatcher = Dispatcher::new();
+        dispatcher.add_sink(OrderBookManager::new());
+        dispatcher.add_sink(Arc::clone(&tracker));
+
+        for message in [
+            position(1, b"GSCO", MarketParticipantState::Active),
+            position(2, b"MSCO", MarketParticipantState::Active),
+            position(3, b"MSCO", MarketParticipantState::Suspended),
+            // GSCO joins the bid, then MSCO improves it
+            add_order(10, 1, 150_0000, Some(*b"GSCO")),
+            add_order(20, 2, 150_0000, None),
+            add_order(30, 3, 150_1000, Some(*b"MSCO")),
+            MessageTypes::OrderExecuted(OrderExecuted {
+                header: MessageHeader::example(7, 40),
+                order_reference_number: 3,
+                executed_shares: 40,
+                match_number: 1,
+            }),
+            // MSCO moves away from the inside, leaving GSCO back at the bid
+            MessageTypes::OrderReplace(OrderReplace {
+                header: MessageHeader::example(7, 50),
+                original_order_reference_number: 3,
+                new_order_reference_number: 4,
+                shares: 200,
+                price: Price4::new(149_0000),
+            }),
+            MessageTypes::OrderDelete(OrderDelete {
+                header: MessageHeader::example(7, 60),
+                order_reference_number: 1,
+            }),
+            MessageTypes::SystemEvent(SystemEventMessage {
+                header: MessageHeader::example(7, 100),
+                event_code: SystemEventCode::EndOfMessages,
+            }),
+        ] {
+            dispatcher.dispatch(&message);
+        }
+
+        let tracker = tracker.read().unwrap();
+        assert_eq!(tracker.get_market_makers(7).len(), 2);
+        assert_eq!(
+            tracker.get_market_makers(7)[1].market_participant_state,
+            MarketParticipantState::Suspended
+        );
+        assert_eq!(tracker.primary_market_maker(7).unwrap().mpid, *b"GSCO");
+        assert!(tracker.get_market_makers(8).is_empty());
+
+        assert_eq!(
+            tracker.get_stats(b"GSCO"),
+            Some(&ParticipantStats {
+                orders_added: 1,
+                shares_displayed: 100,
+                executions: 0,
+                shares_executed: 0,
+                time_at_inside: 20 + 10,
+            })
+        );
+        assert_eq!(
+            tracker.get_stats(b"MSCO"),
+            Some(&ParticipantStats {
+                orders_added: 1,
+                shares_displayed: 100 + 200,
+                executions: 1,
+                shares_executed: 40,
+                time_at_inside: 20,
+            })
+        );
+        assert!(tracker.at_inside(7).is_empty());
+    }
+}
@@ -0,0 +1,189 @@
+use crate::enums::{MessageTypes, SystemEventCode};
+use crate::events::{Event, EventSink};
+use std::collections::VecDeque;
+
+/// The part of the trading day, as signalled by system event ('S') messages.
+///
+/// # Variants
+/// * `Closed` - Before the start of system hours, or after the end of system hours.
+/// * `PreMarket` - From the start of system hours until the start of market hours.
+/// * `Regular` - From the start of market hours until the end of market hours.
+/// * `PostMarket` - From the end of market hours until the end of system hours.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MarketSession {
+    #[default]
+    Closed,
+    PreMarket,
+    Regular,
+    PostMarket,
+}
+
+impl From<SystemEventCode> for MarketSession {
+    /// The session that a system event starts.
+    fn from(event_code: SystemEventCode) -> Self {
+        match event_code {
+            SystemEventCode::StartOfMessages => MarketSession::Closed,
+            SystemEventCode::StartOfSystemHours => MarketSession::PreMarket,
+            SystemEventCode::StartOfMarketHours => MarketSession::Regular,
+            SystemEventCode::EndOfMarketHours => MarketSession::PostMarket,
+            SystemEventCode::EndOfSystemHours => MarketSession::Closed,
+            SystemEventCode::EndOfMessages => MarketSession::Closed,
+        }
+    }
+}
+
+/// The market session changed.
+///
+/// # Fields
+/// * `timestamp` - Nanoseconds since midnight, of the system event.
+/// * `event_code` - The system event that changed the session.
+/// * `session` - The new session.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SessionChange {
+    pub timestamp: u64,
+    pub event_code: SystemEventCode,
+    pub session: MarketSession,
+}
+
+/// Tracks the phases of the day from system event ('S') messages, and emits a `Session` event whenever the market
+/// session changes, so other sinks can split regular hours from pre and post-market.
+///
+/// # Fields
+/// * `system_events` - (timestamp, event code) of every system event, in file order.
+#[derive(Debug, Default)]
+pub struct SessionTracker {
+    pub system_events: Vec<(u64, SystemEventCode)>,
+}
+
+impl SessionTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The current phase, i.e. the last system event, `None` before the first.
+    pub fn phase(&self) -> Option<SystemEventCode> {
+        self.system_events.last().map(|(_, event_code)| *event_code)
+    }
+
+    /// The current market session.
+    pub fn session(&self) -> MarketSession {
+        self.phase().map(MarketSession::from).unwrap_or_default()
+    }
+
+    /// The market session at a time of day, once every system event up to and including `timestamp` is applied.
+    ///
+    /// # Arguments
+    /// * `timestamp` - Nanoseconds since midnight.
+    pub fn session_at(&self, timestamp: u64) -> MarketSession {
+        self.system_events
+            .iter()
+            .take_while(|(event_timestamp, _)| *event_timestamp <= timestamp)
+            .last()
+            .map(|(_, event_code)| MarketSession::from(*event_code))
+            .unwrap_or_default()
+    }
+
+    /// The time of day a system event happened, `None` if it has not (yet).
+    ///
+    /// # Arguments
+    /// * `event_code` - The system event to look up.
+    pub fn timestamp_of(&self, event_code: SystemEventCode) -> Option<u64> {
+        self.system_events
+            .iter()
+            .find(|(_, code)| *code == event_code)
+            .map(|(timestamp, _)| *timestamp)
+    }
+
+    /// Returns true once the end of messages event is seen. Nothing follows it in a file.
+    pub fn is_finished(&self) -> bool {
+        self.phase() == Some(SystemEventCode::EndOfMessages)
+    }
+}
+
+impl EventSink for SessionTracker {
+    fn on_message(&mut self, message: &MessageTypes, events: &mut VecDeque<Event>) {
+        if let MessageTypes::SystemEvent(system_event) = message {
+            let previous = self.session();
+            let timestamp = system_event.header.timestamp;
+            self.system_events
+                .push((timestamp, system_event.event_code));
+
+            let session = self.session();
+            if session != previous {
+                events.push_back(Event::Session(SessionChange {
+                    timestamp,
+                    event_code: system_event.event_code,
+                    session,
+                }));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::messageheader::MessageHeader;
+    use crate::systemmessages::SystemEventMessage;
+
+    fn system_event(timestamp: u64, event_code: SystemEventCode) -> MessageTypes {
+        MessageTypes::SystemEvent(SystemEventMessage {
+            header: MessageHeader::example(0, timestamp),
+            event_code,
+        })
+    }
+
+    #[test]
+    fn test_session_tracker() {
+        let mut tracker = SessionTracker::new();
+        let mut events = VecDeque::new();
+        assert_eq!(tracker.phase(), None);
+
+        for (timestamp, event_code) in [
+            (10, SystemEventCode::StartOfMessages),
+            (20, SystemEventCode::StartOfSystemHours),
+            (30, SystemEventCode::StartOfMarketHours),
+            (40, SystemEventCode::EndOfMarketHours),
+            (50, SystemEventCode::EndOfSystemHours),
+        ] {
+            tracker.on_message(&system_event(timestamp, event_code), &mut events);
+            assert!(!tracker.is_finished());
+        }
+        assert_eq!(tracker.phase(), Some(SystemEventCode::EndOfSystemHours));
+        assert_eq!(tracker.session(), MarketSession::Closed);
+        assert_eq!(
+            tracker.timestamp_of(SystemEventCode::StartOfMarketHours),
+            Some(30)
+        );
+
+        assert_eq!(tracker.session_at(15), MarketSession::Closed);
+        assert_eq!(tracker.session_at(20), MarketSession::PreMarket);
+        assert_eq!(tracker.session_at(39), MarketSession::Regular);
+        assert_eq!(tracker.session_at(45), MarketSession::PostMarket);
+
+        // The start and end of messages do not change the session
+        let sessions: Vec<_> = events
+            .iter()
+            .map(|event| match event {
+                Event::Session(change) => (change.timestamp, change.session),
+                _ => panic!("Expected a session event"),
+            })
+            .collect();
+        assert_eq!(
+            sessions,
+            [
+                (20, MarketSession::PreMarket),
+                (30, MarketSession::Regular),
+                (40, MarketSession::PostMarket),
+                (50, MarketSession::Closed),
+            ]
+        );
+
+        tracker.on_message(
+            &system_event(60, SystemEventCode::EndOfMessages),
+            &mut events,
+        );
+        assert!(tracker.is_finished());
+        assert_eq!(events.len(), 4);
+    }
+}
@@ -0,0 +1,368 @@
+use crate::enums::MessageTypes;
+use crate::events::{Event, EventSink};
+use crate::orderbook::{order_message_header, OrderBookManager};
+use std::collections::VecDeque;
+use std::sync::mpsc::{self, Receiver, Sender, SyncSender};
+use std::sync::Mutex;
+use std::thread::{self, JoinHandle};
+
+/// Order messages sent to a shard in one go, to keep the channel overhead off the hot path.
+const BATCH_SIZE: usize = 1024;
+/// Batches queued per shard before the replay waits on it.
+const QUEUED_BATCHES: usize = 64;
+
+/// Builds the order books on `N` worker threads, each owning the books of a disjoint set of stocks.
+/// Order messages are routed by the stock locate code in their header, modulo the number of shards.
+///
+/// Every ITCH order message carries the stock locate code of the order it refers to, and a stock locate code maps
+/// to a single stock for the whole day, so every message of an order reaches the shard the order was added to.
+/// Each shard keeps its own order reference number map, and no state is shared between shards.
+/// A message whose stock locate code does not match its order is reported as a non-existent order, as it would be
+/// by a single `OrderBookManager`.
+///
+/// Events are applied asynchronously: `Execution` and `TopOfBook` events are emitted in order for each stock, but
+/// reach the other sinks a batch or more after the message that caused them, and are interleaved across stocks.
+/// Sinks that read the books directly, e.g. `DepthWriter` and `SnapshotWriter`, need a single `OrderBookManager`.
+/// Before dispatching a message that sinks match against earlier executions, see `is_sync_point`, call `sync` and
+/// dispatch the events it returns first.
+/// Call `finish` once the last message is dispatched, to wait for the shards and collect the books.
+///
+/// # Fields
+/// * `shards` - The worker threads, indexed by stock locate code modulo their count.
+/// * `events` - Events emitted by every shard, in the order each shard emitted them. Only behind a mutex so the
+///   manager can be shared like an `OrderBookManager`, it is never locked.
+pub struct ShardedOrderBookManager {
+    shards: Vec<Shard>,
+    events: Mutex<Receiver<ShardOutput>>,
+}
+
+/// A worker thread owning the books of some stocks.
+///
+/// # Fields
+/// * `commands` - Queue of the worker, bounded so the replay can not run ahead of the books.
+/// * `batch` - Order messages not sent to the worker yet, and whether their events are wanted.
+/// * `handle` - Returns the books once `commands` is closed.
+struct Shard {
+    commands: SyncSender<ShardCommand>,
+    batch: Vec<(MessageTypes, bool)>,
+    handle: JoinHandle<OrderBookManager>,
+}
+
+enum ShardCommand {
+    Apply(Vec<(MessageTypes, bool)>),
+    Sync,
+}
+
+enum ShardOutput {
+    Events(Vec<Event>),
+    Synced,
+}
+
+/// Returns true for messages that sinks match against the events of every earlier order message, so the shards
+/// must be synced before they are dispatched: broken trades ('B') retract executions, crossing trades ('Q') follow
+/// the executions of their stock on the tape, and system events ('S') close the day.
+pub fn is_sync_point(message: &MessageTypes) -> bool {
+    matches!(
+        message,
+        MessageTypes::BrokenTrade(_)
+            | MessageTypes::CrossingTrade(_)
+            | MessageTypes::SystemEvent(_)
+    )
+}
+
+impl ShardedOrderBookManager {
+    /// Start the worker threads.
+    ///
+    /// # Arguments
+    /// * `order_book_manager` - The books to start from, e.g. a restored snapshot. Split between the shards.
+    /// * `shards` - The number of worker threads, at least 1.
+    pub fn new(order_book_manager: OrderBookManager, shards: usize) -> Self {
+        assert!(shards > 0, "At least one shard is needed");
+        let (output, events) = mpsc::channel();
+        let shards = order_book_manager
+            .split(shards)
+            .into_iter()
+            .enumerate()
+            .map(|(index, order_book_manager)| {
+                let (commands, queue) = mpsc::sync_channel(QUEUED_BATCHES);
+                let output = output.clone();
+                let handle = thread::Builder::new()
+                    .name(format!("order-book-shard-{index}"))
+                    .spawn(move || run_shard(order_book_manager, queue, output))
+                    .expect("failed to spawn order book shard");
+                Shard {
+                    commands,
+                    batch: Vec::with_capacity(BATCH_SIZE),
+                    handle,
+                }
+            })
+            .collect();
+        Self {
+            shards,
+            events: Mutex::new(events),
+        }
+    }
+
+    /// Route an order message to the shard owning its stock. Other messages are ignored.
+    ///
+    /// # Arguments
+    /// * `message` - The parsed message.
+    /// * `emit_events` - Whether to emit the events derived from the message, false to only build the books.
+    pub fn route(&mut self, message: &MessageTypes, emit_events: bool) {
+        let Some(header) = order_message_header(message) else {
+            return;
+        };
+        let message = match message {
+            MessageTypes::AddOrder(order) => MessageTypes::AddOrder(order.clone()),
+            MessageTypes::OrderExecuted(order) => MessageTypes::OrderExecuted(order.clone()),
+            MessageTypes::OrderExecutedWithPrice(order) => {
+                MessageTypes::OrderExecutedWithPrice(order.clone())
+            }
+            MessageTypes::OrderCancel(order) => MessageTypes::OrderCancel(order.clone()),
+            MessageTypes::OrderDelete(order) => MessageTypes::OrderDelete(order.clone()),
+            MessageTypes::OrderReplace(order) => MessageTypes::OrderReplace(order.clone()),
+            _ => return,
+        };
+        assert!(!self.shards.is_empty(), "The shards have been finished");
+        let index = usize::from(header.stock_locate) % self.shards.len();
+        let shard = &mut self.shards[index];
+        shard.batch.push((message, emit_events));
+        if shard.batch.len() == BATCH_SIZE {
+            shard.send_batch();
+        }
+    }
+
+    /// Wait for every shard to apply the messages routed so far.
+    ///
+    /// # Returns
+    /// * `Vec<Event>` - The events emitted by the shards that were not yet collected.
+    pub fn sync(&mut self) -> Vec<Event> {
+        for shard in &mut self.shards {
+            shard.send_batch();
+            shard
+                .commands
+                .send(ShardCommand::Sync)
+                .expect("order book shard exited");
+        }
+        let mut events = Vec::new();
+        let mut synced = 0;
+        while synced < self.shards.len() {
+            match self
+                .events
+                .get_mut()
+                .unwrap()
+                .recv()
+                .expect("order book shard exited")
+            {
+                ShardOutput::Events(shard_events) => events.extend(shard_events),
+                ShardOutput::Synced => synced += 1,
+            }
+        }
+        events
+    }
+
+    /// Wait for every shard to apply the messages routed so far, and stop them.
+    /// No more messages can be routed afterwards.
+    ///
+    /// # Returns
+    /// * `(OrderBookManager, Vec<Event>)` - The books of every shard, and the events that were not yet collected.
+    pub fn finish(&mut self) -> (OrderBookManager, Vec<Event>) {
+        let events = self.sync();
+        let mut order_book_manager = OrderBookManager::new();
+        for shard in self.shards.drain(..) {
+            drop(shard.commands);
+            order_book_manager.merge(shard.handle.join().expect("order book shard panicked"));
+        }
+        (order_book_manager, events)
+    }
+}
+
+impl Shard {
+    fn send_batch(&mut self) {
+        if self.batch.is_empty() {
+            return;
+        }
+        let batch = std::mem::replace(&mut self.batch, Vec::with_capacity(BATCH_SIZE));
+        self.commands
+            .send(ShardCommand::Apply(batch))
+            .expect("order book shard exited");
+    }
+}
+
+/// The worker loop of a shard. Applies batches until the replay hangs up, then returns the books.
+fn run_shard(
+    mut order_book_manager: OrderBookManager,
+    commands: Receiver<ShardCommand>,
+    output: Sender<ShardOutput>,
+) -> OrderBookManager {
+    let mut events = VecDeque::new();
+    let mut emitted = Vec::new();
+    for command in commands {
+        match command {
+            ShardCommand::Apply(batch) => {
+                for (message, emit_events) in batch {
+                    order_book_manager.on_message(&message, &mut events);
+                    if emit_events {
+                        emitted.extend(events.drain(..));
+                    } else {
+                        events.clear();
+                    }
+                }
+                if !emitted.is_empty() {
+                    // The replay only hangs up after the last sync, so a failed send loses nothing
+                    let _ = output.send(ShardOutput::Events(std::mem::take(&mut emitted)));
+                }
+            }
+            ShardCommand::Sync => {
+                let _ = output.send(ShardOutput::Synced);
+            }
+        }
+    }
+    order_book_manager
+}
+
+/// Routes order messages to the shards, and emits the events the shards have sent back since the last message.
+impl EventSink for ShardedOrderBookManager {
+    fn on_message(&mut self, message: &MessageTypes, events: &mut VecDeque<Event>) {
+        self.route(message, true);
+        while let Ok(output) = self.events.get_mut().unwrap().try_recv() {
+            if let ShardOutput::Events(shard_events) = output {
+                events.extend(shard_events);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::addordermessages::AddOrder;
+    use crate::enums::BuySellIndicator;
+    use crate::events::Dispatcher;
+    use crate::messageheader::MessageHeader;
+    use crate::modifyordermessages::{OrderExecuted, OrderReplace};
+    use crate::types::{Price4, PriceConversions};
+    use std::sync::{Arc, RwLock};
+
+    /// Adds, replaces and partially executes an order on each of 20 stocks.
+    fn messages() -> Vec<MessageTypes> {
+        let mut messages = Vec::new();
+        for stock_locate in 1..=20u16 {
+            let order_reference_number = u64::from(stock_locate) * 10;
+            messages.push(MessageTypes::AddOrder(AddOrder::example(
+                stock_locate,
+                1,
+                order_reference_number,
+                BuySellIndicator::Buy,
+                150_0000 + u32::from(stock_locate),
+            )));
+        }
+        for stock_locate in 1..=20u16 {
+            let order_reference_number = u64::from(stock_locate) * 10;
+            messages.push(MessageTypes::OrderReplace(OrderReplace {
+                header: MessageHeader::example(stock_locate, 2),
+                original_order_reference_number: order_reference_number,
+                new_order_reference_number: order_reference_number + 1,
+                shares: 200,
+                price: Price4::new(149_0000),
+            }));
+            messages.push(MessageTypes::OrderExecuted(OrderExecuted {
+                header: MessageHeader::example(stock_locate, 3),
+                order_reference_number: order_reference_number + 1,
+                executed_shares: 50,
+                match_number: order_reference_number,
+            }));
+        }
+        messages
+    }
+
+    #[test]
+    fn test_sharded_order_book_manager() {
+        let mut expected = Dispatcher::new();
+        let order_book_manager = Arc::new(RwLock::new(OrderBookManager::new()));
+        expected.add_sink(Arc::clone(&order_book_manager));
+
+        let mut sharded = ShardedOrderBookManager::new(OrderBookManager::new(), 3);
+        let mut events = VecDeque::new();
+        for message in messages() {
+            expected.dispatch(&message);
+            sharded.on_message(&message, &mut events);
+        }
+        let (sharded_books, remaining) = sharded.finish();
+        events.extend(remaining);
+
+        // Every stock's events are emitted in order, whichever shard it is on
+        let executions: Vec<_> = events
+            .iter()
+            .filter_map(|event| match event {
+                Event::Execution(execution) => Some(execution.stock_locate),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(executions.len(), 20);
+        for stock_locate in 1..=20u16 {
+            let bid_sizes: Vec<_> = events
+                .iter()
+                .filter_map(|event| match event {
+                    Event::TopOfBook(top) if top.stock_locate == stock_locate => Some(top.bid_size),
+                    _ => None,
+                })
+                .collect();
+            assert_eq!(bid_sizes, [100, 200, 150]);
+        }
+
+        let order_book_manager = order_book_manager.read().unwrap();
+        assert_eq!(sharded_books.order_books.len(), 20);
+        for (stock_locate, book) in &order_book_manager.order_books {
+            let sharded_book = &sharded_books.order_books[stock_locate];
+            assert_eq!(sharded_book.get_depth(5), book.get_depth(5));
+        }
+        for stock_locate in 1..=20u64 {
+            assert_eq!(
+                sharded_books.get_queue_position(stock_locate * 10 + 1),
+                order_book_manager.get_queue_position(stock_locate * 10 + 1)
+            );
+        }
+    }
+
+    #[test]
+    fn test_sharded_order_book_manager_sync() {
+        let mut sharded = ShardedOrderBookManager::new(OrderBookManager::new(), 3);
+        let mut events = VecDeque::new();
+        for message in messages() {
+            sharded.on_message(&message, &mut events);
+        }
+        assert!(!is_sync_point(&messages()[0]));
+
+        // Once synced, every execution so far has been emitted and the shards carry on
+        events.extend(sharded.sync());
+        let executions = events
+            .iter()
+            .filter(|event| matches!(event, Event::Execution(_)))
+            .count();
+        assert_eq!(executions, 20);
+        sharded.route(&messages()[0], true);
+        let (books, _) = sharded.finish();
+        assert_eq!(books.order_books.len(), 20);
+    }
+
+    #[test]
+    fn test_sharded_order_book_manager_restore() {
+        let mut order_book_manager = OrderBookManager::new();
+        let mut events = VecDeque::new();
+        for message in messages().iter().take(20) {
+            order_book_manager.on_message(message, &mut events);
+        }
+
+        // Orders added before the split are executed by the shard they were split into
+        let mut sharded = ShardedOrderBookManager::new(order_book_manager, 4);
+        for message in messages().iter().skip(20) {
+            sharded.route(message, false);
+        }
+        let (order_book_manager, events) = sharded.finish();
+        assert!(events.is_empty());
+        for book in order_book_manager.order_books.values() {
+            assert_eq!(book.get_best_bid_size(), 150);
+        }
+    }
+}
@@ -0,0 +1,297 @@
+use crate::decoder::Decoder;
+use crate::encoder::Encoder;
+use crate::enums::MessageTypes;
+use crate::events::{Event, EventSink};
+use crate::orderbook::{order_message_header, OrderBookManager, PriceBucket};
+use crate::types::SnapshotError;
+use byteorder::{BigEndian, ByteOrder};
+use std::collections::VecDeque;
+use std::io::{self, Read, Write};
+use std::sync::{Arc, RwLock};
+
+/// Identifies a market by order (L3) snapshot file.
+pub const SNAPSHOT_MAGIC: [u8; 6] = *b"FASXL3";
+/// The snapshot format version, bumped on incompatible changes.
+pub const SNAPSHOT_VERSION: u16 = 1;
+/// Magic, version, timestamp and order count.
+const SNAPSHOT_HEADER_LENGTH: usize = 24;
+
+/// Write every resting order of an order book manager as a market by order (L3) snapshot.
+///
+/// The snapshot is a 24 byte header (magic, version, timestamp, order count), followed by the orders as
+/// length prefixed ITCH 'A' and 'F' messages, so the order section can be read by any ITCH decoder.
+/// Orders are written book by book, best price first, in time priority, so loading them back preserves queue positions.
+/// The order price map is not written, as it is rebuilt from the orders.
+///
+/// # Arguments
+/// * `writer` - Destination of the snapshot.
+/// * `order_book_manager` - The books to snapshot.
+/// * `timestamp` - Nanoseconds since midnight that the snapshot is taken at.
+///
+/// # Returns
+/// * `Ok(u64)` - The number of orders written.
+/// * `Err(io::Error)` - If the snapshot could not be written.
+pub fn write_snapshot<W: Write>(
+    writer: &mut W,
+    order_book_manager: &OrderBookManager,
+    timestamp: u64,
+) -> Result<u64, io::Error> {
+    let mut stock_locates: Vec<_> = order_book_manager.order_books.keys().copied().collect();
+    stock_locates.sort_unstable();
+    let buckets = || {
+        stock_locates.iter().flat_map(|stock_locate| {
+            let book = &order_book_manager.order_books[stock_locate];
+            book.bid_book.values().chain(book.ask_book.values())
+        })
+    };
+    let order_count: u64 = buckets().map(|bucket| bucket.orders.len() as u64).sum();
+
+    let mut header = [0u8; SNAPSHOT_HEADER_LENGTH];
+    header[..6].copy_from_slice(&SNAPSHOT_MAGIC);
+    BigEndian::write_u16(&mut header[6..8], SNAPSHOT_VERSION);
+    BigEndian::write_u64(&mut header[8..16], timestamp);
+    BigEndian::write_u64(&mut header[16..24], order_count);
+    writer.write_all(&header)?;
+
+    // Orders are written as 'A' (no MPID) or 'F' (with MPID) frames, in time priority
+    let mut encoder = Encoder::new(&mut *writer);
+    for order in buckets().flat_map(PriceBucket::orders_in_priority) {
+        encoder.write(order)?;
+    }
+    encoder.flush()?;
+    Ok(order_count)
+}
+
+/// Load a snapshot written by `write_snapshot`.
+///
+/// # Arguments
+/// * `reader` - Source of the snapshot.
+///
+/// # Returns
+/// * `Ok((u64, OrderBookManager))` - The timestamp the snapshot was taken at, and the restored books.
+/// * `Err(SnapshotError)` - If the snapshot is invalid or could not be read.
+pub fn read_snapshot<R: Read>(mut reader: R) -> Result<(u64, OrderBookManager), SnapshotError> {
+    let mut header = [0u8; SNAPSHOT_HEADER_LENGTH];
+    reader.read_exact(&mut header)?;
+    if header[..6] != SNAPSHOT_MAGIC {
+        return Err(SnapshotError::InvalidMagic);
+    }
+    let version = BigEndian::read_u16(&header[6..8]);
+    if version != SNAPSHOT_VERSION {
+        return Err(SnapshotError::UnsupportedVersion { version });
+    }
+    let timestamp = BigEndian::read_u64(&header[8..16]);
+    let expected = BigEndian::read_u64(&header[16..24]);
+
+    let mut order_book_manager = OrderBookManager::new();
+    let mut read = 0;
+    for message in Decoder::new(reader) {
+        match message? {
+            MessageTypes::AddOrder(order) => order_book_manager.add_order(order)?,
+            _ => return Err(SnapshotError::UnexpectedMessage),
+        }
+        read += 1;
+    }
+    if read != expected {
+        return Err(SnapshotError::OrderCount { read, expected });
+    }
+    Ok((timestamp, order_book_manager))
+}
+
+/// Writes a snapshot of the books as they stand at a given time.
+/// Must be registered before the order book manager, so the books have not yet seen the first order message at or past
+/// the snapshot time when it is taken. If the replay ends first, `finish` writes the final state.
+///
+/// # Fields
+/// * `writer` - Destination of the snapshot.
+/// * `timestamp` - Nanoseconds since midnight to take the snapshot at.
+/// * `order_book_manager` - The books being replayed.
+/// * `written` - Whether the snapshot has been written.
+/// * `error` - The error hit while writing, returned by `finish`.
+pub struct SnapshotWriter<W: Write> {
+    writer: W,
+    timestamp: u64,
+    order_book_manager: Arc<RwLock<OrderBookManager>>,
+    written: bool,
+    error: Option<io::Error>,
+}
+
+impl<W: Write> SnapshotWriter<W> {
+    pub fn new(
+        writer: W,
+        timestamp: u64,
+        order_book_manager: Arc<RwLock<OrderBookManager>>,
+    ) -> Self {
+        Self {
+            writer,
+            timestamp,
+            order_book_manager,
+            written: false,
+            error: None,
+        }
+    }
+
+    /// Write the snapshot if the replay ended before the snapshot time, as the books can no longer change.
+    ///
+    /// # Returns
+    /// * `Ok(())` - If the snapshot was written.
+    /// * `Err(io::Error)` - If the snapshot could not be written.
+    pub fn finish(&mut self) -> Result<(), io::Error> {
+        self.write();
+        match self.error.take() {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+
+    fn write(&mut self) {
+        if self.written {
+            return;
+        }
+        self.written = true;
+        let order_book_manager = self.order_book_manager.read().unwrap();
+        let result = write_snapshot(&mut self.writer, &order_book_manager, self.timestamp)
+            .and_then(|orders| self.writer.flush().map(|_| orders));
+        match result {
+            Ok(orders) => log::info!("Wrote snapshot of {orders} orders at {}", self.timestamp),
+            Err(e) => {
+                log::error!("Failed to write snapshot: {e}");
+                self.error = Some(e);
+            }
+        }
+    }
+}
+
+impl<W: Write> EventSink for SnapshotWriter<W> {
+    fn on_message(&mut self, message: &MessageTypes, _events: &mut VecDeque<Event>) {
+        if self.written {
+            return;
+        }
+        if order_message_header(message).is_some_and(|header| header.timestamp >= self.timestamp) {
+            self.write();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::addordermessages::AddOrder;
+    use crate::enums::BuySellIndicator;
+    use crate::events::Dispatcher;
+    use crate::messageheader::MessageHeader;
+    use crate::modifyordermessages::OrderDelete;
+
+    fn add_order(
+        timestamp: u64,
+        order_reference_number: u64,
+        buy_sell_indicator: BuySellIndicator,
+        price: u32,
+        mpid: Option<[u8; 4]>,
+    ) -> AddOrder {
+        let stock_locate = 1 + (order_reference_number % 2) as u16;
+        AddOrder {
+            shares: 100 * order_reference_number as u32,
+            mpid,
+            ..AddOrder::example(
+                stock_locate,
+                timestamp,
+                order_reference_number,
+                buy_sell_indicator,
+                price,
+            )
+        }
+    }
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let mut order_book_manager = OrderBookManager::new();
+        let orders = [
+            add_order(1, 1, BuySellIndicator::Buy, 150_0000, None),
+            add_order(2, 2, BuySellIndicator::Sell, 151_0000, Some(*b"JPMC")),
+            add_order(3, 3, BuySellIndicator::Buy, 150_0000, None),
+            add_order(4, 4, BuySellIndicator::Buy, 149_0000, None),
+            add_order(5, 5, BuySellIndicator::Buy, 150_0000, Some(*b"GSCO")),
+        ];
+        for order in &orders {
+            order_book_manager.add_order(order.clone()).unwrap();
+        }
+
+        let mut bytes = Vec::new();
+        assert_eq!(
+            write_snapshot(&mut bytes, &order_book_manager, 42).unwrap(),
+            5
+        );
+        let (timestamp, restored) = read_snapshot(&bytes[..]).unwrap();
+        assert_eq!(timestamp, 42);
+
+        for order in &orders {
+            let book = &restored.order_books[&order.header.stock_locate];
+            let bucket = match order.buy_sell_indicator {
+                BuySellIndicator::Buy => &book.bid_book[&std::cmp::Reverse(order.price)],
+                BuySellIndicator::Sell => &book.ask_book[&order.price],
+            };
+            assert_eq!(&bucket.orders[&order.order_reference_number], order);
+            assert_eq!(
+                restored.get_queue_position(order.order_reference_number),
+                order_book_manager.get_queue_position(order.order_reference_number)
+            );
+        }
+
+        bytes[0] = b'X';
+        assert!(matches!(
+            read_snapshot(&bytes[..]),
+            Err(SnapshotError::InvalidMagic)
+        ));
+    }
+
+    #[test]
+    fn test_snapshot_writer() {
+        let order_book_manager = Arc::new(RwLock::new(OrderBookManager::new()));
+        let snapshot_writer = Arc::new(RwLock::new(SnapshotWriter::new(
+            Vec::new(),
+            200,
+            Arc::clone(&order_book_manager),
+        )));
+        let mut dispatcher = Dispatcher::new();
+        dispatcher.add_sink(Arc::clone(&snapshot_writer));
+        dispatcher.add_sink(Arc::clone(&order_book_manager));
+
+        dispatcher.dispatch(&MessageTypes::AddOrder(add_order(
+            100,
+            1,
+            BuySellIndicator::Buy,
+            150_0000,
+            None,
+        )));
+        dispatcher.dispatch(&MessageTypes::AddOrder(add_order(
+            150,
+            3,
+            BuySellIndicator::Buy,
+            150_0000,
+            None,
+        )));
+        // At the snapshot time, so not in the snapshot
+        dispatcher.dispatch(&MessageTypes::OrderDelete(OrderDelete {
+            header: MessageHeader::example(2, 200),
+            order_reference_number: 1,
+        }));
+
+        let mut snapshot_writer = snapshot_writer.write().unwrap();
+        snapshot_writer.finish().unwrap();
+        assert_eq!(
+            order_book_manager.read().unwrap().get_queue_position(1),
+            None
+        );
+
+        let (timestamp, restored) = read_snapshot(&snapshot_writer.writer[..]).unwrap();
+        assert_eq!(timestamp, 200);
+        assert!(restored.get_queue_position(1).is_some());
+        assert_eq!(
+            restored
+                .get_queue_position(3)
+                .map(|position| position.orders_ahead),
+            Some(1)
+        );
+    }
+}
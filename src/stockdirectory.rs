@@ -1,11 +1,12 @@
 use crate::enums::{
     BoolOrUnavailable, FinancialStatusIndicator, IssueClassificationCodes, LuldReferencePriceTier,
-    MarketCategory, ShortSaleThresholdIndicator,
+    MarketCategory, MessageTypes, ShortSaleThresholdIndicator,
 };
+use crate::events::{Event, EventSink};
 use crate::orderbook::StockLocateCode;
 use crate::stockmessages::StockDirectory;
 use crate::types::Stock;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::fmt;
 
 #[derive(Debug)]
@@ -14,6 +15,12 @@ pub struct StockDirectoryManager {
     pub stock_to_stock_locate: HashMap<Stock, StockLocateCode>,
 }
 
+impl Default for StockDirectoryManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl StockDirectoryManager {
     pub fn new() -> Self {
         Self {
@@ -28,7 +35,7 @@ impl StockDirectoryManager {
             std::str::from_utf8(&message.stock).unwrap()
         );
         self.stock_to_stock_locate
-            .insert(message.stock.clone(), message.header.stock_locate.clone());
+            .insert(message.stock, message.header.stock_locate);
 
         self.directory
             .entry(message.header.stock_locate)
@@ -42,7 +49,7 @@ impl StockDirectoryManager {
 
     pub fn locate_to_stock(&self, stock_locate: u16) -> Option<Stock> {
         let stock_data = self.directory.get(&stock_locate)?;
-        Some(stock_data.stock.clone())
+        Some(stock_data.stock)
     }
 
     pub fn stock_to_locate(&self, stock: Stock) -> Option<StockLocateCode> {
@@ -51,6 +58,14 @@ impl StockDirectoryManager {
     }
 }
 
+impl EventSink for StockDirectoryManager {
+    fn on_message(&mut self, message: &MessageTypes, _events: &mut VecDeque<Event>) {
+        if let MessageTypes::StockDirectory(message) = message {
+            self.add_stock(message.clone());
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct StockData {
     pub stock: Stock,
@@ -1,11 +1,12 @@
 use crate::enums::{
     BoolOrUnavailable, FinancialStatusIndicator, IPOReleaseQualifier, IssueClassificationCodes,
-    LuldReferencePriceTier, MWCBLevel, MarketCategory, MarketMakerMode, MarketParticipantState,
-    RegSHOAction, ShortSaleThresholdIndicator, TradingReasonCodes, TradingState,
+    LuldReferencePriceTier, MWCBLevel, MarketCategory, MarketCode, MarketMakerMode,
+    MarketParticipantState, OpenEligibilityStatus, OperationalHaltAction, RegSHOAction,
+    ShortSaleThresholdIndicator, TradingReasonCodes, TradingState,
 };
-use crate::helpers::{byte_to_bool, byte_to_bool_space};
+use crate::helpers::{bool_to_byte, byte_to_bool, byte_to_bool_space};
 use crate::messageheader::MessageHeader;
-use crate::types::{BinaryMessageLength, MessageHeaderType, Parse, ParseError, Price4, Price8, PriceConversions, Stock};
+use crate::types::{BinaryMessageLength, Encode, MessageHeaderType, Parse, ParseError, Price4, Price8, PriceConversions, Stock};
 use byteorder::{BigEndian, ByteOrder};
 
 #[cfg(any(test, feature = "bench"))]
@@ -13,7 +14,7 @@ use crate::types::{EnumTestHelpers, GenerateExampleMessage};
 #[cfg(any(test, feature = "bench"))]
 use fastrand::Rng;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub struct StockDirectory {
     pub header: MessageHeader,
     pub stock: Stock,
@@ -68,6 +69,46 @@ impl MessageHeaderType for StockDirectory {
     const MESSAGE_TYPE: u8 = b'R';
 }
 
+impl Encode for StockDirectory {
+    fn message_type(&self) -> u8 {
+        Self::MESSAGE_TYPE
+    }
+
+    fn encode(&self, output: &mut Vec<u8>) {
+        self.header.encode(output);
+        output.extend_from_slice(&self.stock);
+        output.push(u8::from(&self.market_category));
+        output.push(u8::from(&self.financial_status_indicator));
+        output.extend_from_slice(&self.round_lot_size.to_be_bytes());
+        output.push(bool_to_byte(self.round_lots_only));
+        output.push(u8::from(&self.issue_classification));
+        output.extend_from_slice(&self.issue_sub_type.to_be_bytes());
+        output.push(self.authenticity as u8);
+        output.push(u8::from(&self.short_sale_threshold_indicator));
+        output.push(u8::from(&self.ipo_flag));
+        output.push(u8::from(&self.luld_reference_price_tier));
+        output.push(u8::from(&self.etp_flag));
+        output.extend_from_slice(&self.etp_leverage_factor.to_be_bytes());
+        output.push(bool_to_byte(self.inverse_indicator));
+    }
+}
+
+#[cfg(any(test, feature = "bench"))]
+impl StockDirectory {
+    /// A randomly generated directory entry for `stock`, stamped at midnight, for tests and benchmarks.
+    ///
+    /// # Arguments
+    /// * `stock_locate` - The stock locate code to assign to the stock.
+    /// * `stock` - The left justified, space padded ticker.
+    pub fn example(stock_locate: u16, stock: Stock) -> StockDirectory {
+        StockDirectory {
+            header: MessageHeader::example(stock_locate, 0),
+            stock,
+            ..StockDirectory::parse(&StockDirectory::generate_binary_example()).unwrap()
+        }
+    }
+}
+
 #[cfg(any(test, feature = "bench"))]
 impl GenerateExampleMessage<{ Self::LENGTH }> for StockDirectory {
     fn generate_binary_example() -> [u8; Self::LENGTH] {
@@ -99,11 +140,11 @@ impl GenerateExampleMessage<{ Self::LENGTH }> for StockDirectory {
         message[24] = round_lots_only;
         message[25] = issue_classification;
         message[26..28].copy_from_slice(&issue_sub_type);
-        message[28] = authenticity as u8;
+        message[28] = authenticity;
         message[29] = short_sale_threshold_indicator;
-        message[30] = ipo_flag as u8;
-        message[31] = luld_reference_price_tier as u8;
-        message[32] = etp_flag as u8;
+        message[30] = ipo_flag;
+        message[31] = luld_reference_price_tier;
+        message[32] = etp_flag;
         message[33..37].copy_from_slice(&etp_leverage_factor);
         message[37] = inverse_indicator;
 
@@ -113,11 +154,11 @@ impl GenerateExampleMessage<{ Self::LENGTH }> for StockDirectory {
 
 #[derive(Debug, PartialEq)]
 pub struct StockTradingAction {
-    header: MessageHeader,
-    stock: Stock,
-    trading_state: TradingState,
-    reserved: u8,
-    reason: TradingReasonCodes,
+    pub header: MessageHeader,
+    pub stock: Stock,
+    pub trading_state: TradingState,
+    pub reserved: u8,
+    pub reason: TradingReasonCodes,
 }
 
 impl Parse for StockTradingAction {
@@ -146,6 +187,20 @@ impl MessageHeaderType for StockTradingAction {
     const MESSAGE_TYPE: u8 = b'H';
 }
 
+impl Encode for StockTradingAction {
+    fn message_type(&self) -> u8 {
+        Self::MESSAGE_TYPE
+    }
+
+    fn encode(&self, output: &mut Vec<u8>) {
+        self.header.encode(output);
+        output.extend_from_slice(&self.stock);
+        output.push(u8::from(&self.trading_state));
+        output.push(self.reserved);
+        output.extend_from_slice(&<[u8; 4]>::from(&self.reason));
+    }
+}
+
 #[cfg(any(test, feature = "bench"))]
 impl GenerateExampleMessage<{ Self::LENGTH }> for StockTradingAction {
     fn generate_binary_example() -> [u8; Self::LENGTH] {
@@ -171,9 +226,9 @@ impl GenerateExampleMessage<{ Self::LENGTH }> for StockTradingAction {
 
 #[derive(Debug, PartialEq)]
 pub struct RegSHOShortSalePriceTestRestriction {
-    header: MessageHeader,
-    stock: Stock,
-    reg_sho_action: RegSHOAction,
+    pub header: MessageHeader,
+    pub stock: Stock,
+    pub reg_sho_action: RegSHOAction,
 }
 
 impl Parse for RegSHOShortSalePriceTestRestriction {
@@ -200,6 +255,18 @@ impl MessageHeaderType for RegSHOShortSalePriceTestRestriction {
     const MESSAGE_TYPE: u8 = b'Y';
 }
 
+impl Encode for RegSHOShortSalePriceTestRestriction {
+    fn message_type(&self) -> u8 {
+        Self::MESSAGE_TYPE
+    }
+
+    fn encode(&self, output: &mut Vec<u8>) {
+        self.header.encode(output);
+        output.extend_from_slice(&self.stock);
+        output.push(u8::from(&self.reg_sho_action));
+    }
+}
+
 #[cfg(any(test, feature = "bench"))]
 impl GenerateExampleMessage<{ Self::LENGTH }> for RegSHOShortSalePriceTestRestriction {
     fn generate_binary_example() -> [u8; Self::LENGTH] {
@@ -221,12 +288,12 @@ impl GenerateExampleMessage<{ Self::LENGTH }> for RegSHOShortSalePriceTestRestri
 
 #[derive(Debug, PartialEq)]
 pub struct MarketParticipantPosition {
-    header: MessageHeader,
-    mp_id: u32,
-    stock: Stock,
-    primary_market_maker: bool,
-    market_maker_mode: MarketMakerMode,
-    market_participant_state: MarketParticipantState,
+    pub header: MessageHeader,
+    pub mp_id: u32,
+    pub stock: Stock,
+    pub primary_market_maker: bool,
+    pub market_maker_mode: MarketMakerMode,
+    pub market_participant_state: MarketParticipantState,
 }
 
 impl Parse for MarketParticipantPosition {
@@ -256,6 +323,21 @@ impl MessageHeaderType for MarketParticipantPosition {
     const MESSAGE_TYPE: u8 = b'L';
 }
 
+impl Encode for MarketParticipantPosition {
+    fn message_type(&self) -> u8 {
+        Self::MESSAGE_TYPE
+    }
+
+    fn encode(&self, output: &mut Vec<u8>) {
+        self.header.encode(output);
+        output.extend_from_slice(&self.mp_id.to_be_bytes());
+        output.extend_from_slice(&self.stock);
+        output.push(bool_to_byte(self.primary_market_maker));
+        output.push(u8::from(&self.market_maker_mode));
+        output.push(u8::from(&self.market_participant_state));
+    }
+}
+
 #[cfg(any(test, feature = "bench"))]
 impl GenerateExampleMessage<{ Self::LENGTH }> for MarketParticipantPosition {
     fn generate_binary_example() -> [u8; Self::LENGTH] {
@@ -284,10 +366,10 @@ impl GenerateExampleMessage<{ Self::LENGTH }> for MarketParticipantPosition {
 // Market-Wide Circuit Breaker (MWCB) Messaging
 #[derive(Debug, PartialEq)]
 pub struct MWCBDeclineLevel {
-    header: MessageHeader,
-    level1: Price8,
-    level2: Price8,
-    level3: Price8,
+    pub header: MessageHeader,
+    pub level1: Price8,
+    pub level2: Price8,
+    pub level3: Price8,
 }
 
 impl Parse for MWCBDeclineLevel {
@@ -315,6 +397,19 @@ impl MessageHeaderType for MWCBDeclineLevel {
     const MESSAGE_TYPE: u8 = b'V';
 }
 
+impl Encode for MWCBDeclineLevel {
+    fn message_type(&self) -> u8 {
+        Self::MESSAGE_TYPE
+    }
+
+    fn encode(&self, output: &mut Vec<u8>) {
+        self.header.encode(output);
+        output.extend_from_slice(&self.level1.value.to_be_bytes());
+        output.extend_from_slice(&self.level2.value.to_be_bytes());
+        output.extend_from_slice(&self.level3.value.to_be_bytes());
+    }
+}
+
 #[cfg(any(test, feature = "bench"))]
 impl GenerateExampleMessage<{ Self::LENGTH }> for MWCBDeclineLevel {
     fn generate_binary_example() -> [u8; Self::LENGTH] {
@@ -338,8 +433,8 @@ impl GenerateExampleMessage<{ Self::LENGTH }> for MWCBDeclineLevel {
 
 #[derive(Debug, PartialEq)]
 pub struct MWCBStatus {
-    header: MessageHeader,
-    breached_level: MWCBLevel,
+    pub header: MessageHeader,
+    pub breached_level: MWCBLevel,
 }
 
 impl Parse for MWCBStatus {
@@ -365,6 +460,17 @@ impl MessageHeaderType for MWCBStatus {
     const MESSAGE_TYPE: u8 = b'W';
 }
 
+impl Encode for MWCBStatus {
+    fn message_type(&self) -> u8 {
+        Self::MESSAGE_TYPE
+    }
+
+    fn encode(&self, output: &mut Vec<u8>) {
+        self.header.encode(output);
+        output.push(u8::from(&self.breached_level));
+    }
+}
+
 #[cfg(any(test, feature = "bench"))]
 impl GenerateExampleMessage<{ Self::LENGTH }> for MWCBStatus {
     fn generate_binary_example() -> [u8; Self::LENGTH] {
@@ -382,11 +488,11 @@ impl GenerateExampleMessage<{ Self::LENGTH }> for MWCBStatus {
 
 #[derive(Debug, PartialEq)]
 pub struct IPOQuotingPeriodUpdate {
-    header: MessageHeader,
-    stock: Stock,
-    ipo_quotation_release_time: u32,
-    ipo_quotation_release_qualifier: IPOReleaseQualifier,
-    ipo_price: u32,
+    pub header: MessageHeader,
+    pub stock: Stock,
+    pub ipo_quotation_release_time: u32,
+    pub ipo_quotation_release_qualifier: IPOReleaseQualifier,
+    pub ipo_price: u32,
 }
 
 impl Parse for IPOQuotingPeriodUpdate {
@@ -415,6 +521,20 @@ impl MessageHeaderType for IPOQuotingPeriodUpdate {
     const MESSAGE_TYPE: u8 = b'K';
 }
 
+impl Encode for IPOQuotingPeriodUpdate {
+    fn message_type(&self) -> u8 {
+        Self::MESSAGE_TYPE
+    }
+
+    fn encode(&self, output: &mut Vec<u8>) {
+        self.header.encode(output);
+        output.extend_from_slice(&self.stock);
+        output.extend_from_slice(&self.ipo_quotation_release_time.to_be_bytes());
+        output.push(u8::from(&self.ipo_quotation_release_qualifier));
+        output.extend_from_slice(&self.ipo_price.to_be_bytes());
+    }
+}
+
 #[cfg(any(test, feature = "bench"))]
 impl GenerateExampleMessage<{ Self::LENGTH }> for IPOQuotingPeriodUpdate {
     fn generate_binary_example() -> [u8; Self::LENGTH] {
@@ -437,3 +557,241 @@ impl GenerateExampleMessage<{ Self::LENGTH }> for IPOQuotingPeriodUpdate {
         message
     }
 }
+
+// Limit Up-Limit Down (LULD) Auction Collar
+#[derive(Debug, PartialEq)]
+pub struct LULDAuctionCollar {
+    pub header: MessageHeader,
+    pub stock: Stock,
+    pub auction_collar_reference_price: Price4,
+    pub upper_auction_collar_price: Price4,
+    pub lower_auction_collar_price: Price4,
+    pub auction_collar_extension: u32,
+}
+
+impl Parse for LULDAuctionCollar {
+    fn parse(input: &[u8]) -> Result<Self, ParseError> {
+        if input.len() != Self::LENGTH {
+            return Err(ParseError::IncompleteMessage {
+                expected: Self::LENGTH,
+            });
+        }
+
+        Ok(LULDAuctionCollar {
+            header: MessageHeader::parse(&input[..10]),
+            stock: input[10..18].try_into().unwrap(),
+            auction_collar_reference_price: Price4::new(BigEndian::read_u32(&input[18..22])),
+            upper_auction_collar_price: Price4::new(BigEndian::read_u32(&input[22..26])),
+            lower_auction_collar_price: Price4::new(BigEndian::read_u32(&input[26..30])),
+            auction_collar_extension: BigEndian::read_u32(&input[30..34]),
+        })
+    }
+}
+
+impl BinaryMessageLength for LULDAuctionCollar {
+    const LENGTH: usize = 34;
+}
+
+impl MessageHeaderType for LULDAuctionCollar {
+    const MESSAGE_TYPE: u8 = b'J';
+}
+
+impl Encode for LULDAuctionCollar {
+    fn message_type(&self) -> u8 {
+        Self::MESSAGE_TYPE
+    }
+
+    fn encode(&self, output: &mut Vec<u8>) {
+        self.header.encode(output);
+        output.extend_from_slice(&self.stock);
+        output.extend_from_slice(&self.auction_collar_reference_price.value.to_be_bytes());
+        output.extend_from_slice(&self.upper_auction_collar_price.value.to_be_bytes());
+        output.extend_from_slice(&self.lower_auction_collar_price.value.to_be_bytes());
+        output.extend_from_slice(&self.auction_collar_extension.to_be_bytes());
+    }
+}
+
+#[cfg(any(test, feature = "bench"))]
+impl GenerateExampleMessage<{ Self::LENGTH }> for LULDAuctionCollar {
+    fn generate_binary_example() -> [u8; Self::LENGTH] {
+        let mut rng = Rng::new();
+
+        let header = MessageHeader::generate_binary_example();
+        let stock = rng.u64(..).to_be_bytes();
+        let auction_collar_reference_price = rng.u32(..).to_be_bytes();
+        let upper_auction_collar_price = rng.u32(..).to_be_bytes();
+        let lower_auction_collar_price = rng.u32(..).to_be_bytes();
+        let auction_collar_extension = rng.u32(..).to_be_bytes();
+
+        // Concatenate the arrays into a final message
+        let mut message = [0u8; Self::LENGTH];
+        message[..10].copy_from_slice(&header);
+        message[10..18].copy_from_slice(&stock);
+        message[18..22].copy_from_slice(&auction_collar_reference_price);
+        message[22..26].copy_from_slice(&upper_auction_collar_price);
+        message[26..30].copy_from_slice(&lower_auction_collar_price);
+        message[30..34].copy_from_slice(&auction_collar_extension);
+
+        message
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct OperationalHalt {
+    pub header: MessageHeader,
+    pub stock: Stock,
+    pub market_code: MarketCode,
+    pub operational_halt_action: OperationalHaltAction,
+}
+
+impl Parse for OperationalHalt {
+    fn parse(input: &[u8]) -> Result<Self, ParseError> {
+        if input.len() != Self::LENGTH {
+            return Err(ParseError::IncompleteMessage {
+                expected: Self::LENGTH,
+            });
+        }
+
+        Ok(OperationalHalt {
+            header: MessageHeader::parse(&input[..10]),
+            stock: input[10..18].try_into().unwrap(),
+            market_code: MarketCode::try_from(input[18])?,
+            operational_halt_action: OperationalHaltAction::try_from(input[19])?,
+        })
+    }
+}
+
+impl BinaryMessageLength for OperationalHalt {
+    const LENGTH: usize = 20;
+}
+
+impl MessageHeaderType for OperationalHalt {
+    const MESSAGE_TYPE: u8 = b'h';
+}
+
+impl Encode for OperationalHalt {
+    fn message_type(&self) -> u8 {
+        Self::MESSAGE_TYPE
+    }
+
+    fn encode(&self, output: &mut Vec<u8>) {
+        self.header.encode(output);
+        output.extend_from_slice(&self.stock);
+        output.push(u8::from(&self.market_code));
+        output.push(u8::from(&self.operational_halt_action));
+    }
+}
+
+#[cfg(any(test, feature = "bench"))]
+impl GenerateExampleMessage<{ Self::LENGTH }> for OperationalHalt {
+    fn generate_binary_example() -> [u8; Self::LENGTH] {
+        let mut rng = Rng::new();
+
+        let header = MessageHeader::generate_binary_example();
+        let stock = rng.u64(..).to_be_bytes();
+        let market_code = MarketCode::generate_example_code();
+        let operational_halt_action = OperationalHaltAction::generate_example_code();
+
+        // Concatenate the arrays into a final message
+        let mut message = [0u8; Self::LENGTH];
+        message[..10].copy_from_slice(&header);
+        message[10..18].copy_from_slice(&stock);
+        message[18] = market_code;
+        message[19] = operational_halt_action;
+
+        message
+    }
+}
+
+// Direct Listing with Capital Raise (DLCR) Price Discovery
+#[derive(Debug, PartialEq)]
+pub struct DLCRPriceDiscovery {
+    pub header: MessageHeader,
+    pub stock: Stock,
+    pub open_eligibility_status: OpenEligibilityStatus,
+    pub minimum_allowable_price: Price4,
+    pub maximum_allowable_price: Price4,
+    pub near_execution_price: Price4,
+    pub near_execution_time: u64,
+    pub lower_price_range_collar: Price4,
+    pub upper_price_range_collar: Price4,
+}
+
+impl Parse for DLCRPriceDiscovery {
+    fn parse(input: &[u8]) -> Result<Self, ParseError> {
+        if input.len() != Self::LENGTH {
+            return Err(ParseError::IncompleteMessage {
+                expected: Self::LENGTH,
+            });
+        }
+
+        Ok(DLCRPriceDiscovery {
+            header: MessageHeader::parse(&input[..10]),
+            stock: input[10..18].try_into().unwrap(),
+            open_eligibility_status: OpenEligibilityStatus::try_from(input[18])?,
+            minimum_allowable_price: Price4::new(BigEndian::read_u32(&input[19..23])),
+            maximum_allowable_price: Price4::new(BigEndian::read_u32(&input[23..27])),
+            near_execution_price: Price4::new(BigEndian::read_u32(&input[27..31])),
+            near_execution_time: BigEndian::read_u64(&input[31..39]),
+            lower_price_range_collar: Price4::new(BigEndian::read_u32(&input[39..43])),
+            upper_price_range_collar: Price4::new(BigEndian::read_u32(&input[43..47])),
+        })
+    }
+}
+
+impl BinaryMessageLength for DLCRPriceDiscovery {
+    const LENGTH: usize = 47;
+}
+
+impl MessageHeaderType for DLCRPriceDiscovery {
+    const MESSAGE_TYPE: u8 = b'O';
+}
+
+impl Encode for DLCRPriceDiscovery {
+    fn message_type(&self) -> u8 {
+        Self::MESSAGE_TYPE
+    }
+
+    fn encode(&self, output: &mut Vec<u8>) {
+        self.header.encode(output);
+        output.extend_from_slice(&self.stock);
+        output.push(u8::from(&self.open_eligibility_status));
+        output.extend_from_slice(&self.minimum_allowable_price.value.to_be_bytes());
+        output.extend_from_slice(&self.maximum_allowable_price.value.to_be_bytes());
+        output.extend_from_slice(&self.near_execution_price.value.to_be_bytes());
+        output.extend_from_slice(&self.near_execution_time.to_be_bytes());
+        output.extend_from_slice(&self.lower_price_range_collar.value.to_be_bytes());
+        output.extend_from_slice(&self.upper_price_range_collar.value.to_be_bytes());
+    }
+}
+
+#[cfg(any(test, feature = "bench"))]
+impl GenerateExampleMessage<{ Self::LENGTH }> for DLCRPriceDiscovery {
+    fn generate_binary_example() -> [u8; Self::LENGTH] {
+        let mut rng = Rng::new();
+
+        let header = MessageHeader::generate_binary_example();
+        let stock = rng.u64(..).to_be_bytes();
+        let open_eligibility_status = OpenEligibilityStatus::generate_example_code();
+        let minimum_allowable_price = rng.u32(..).to_be_bytes();
+        let maximum_allowable_price = rng.u32(..).to_be_bytes();
+        let near_execution_price = rng.u32(..).to_be_bytes();
+        let near_execution_time = rng.u64(..).to_be_bytes();
+        let lower_price_range_collar = rng.u32(..).to_be_bytes();
+        let upper_price_range_collar = rng.u32(..).to_be_bytes();
+
+        // Concatenate the arrays into a final message
+        let mut message = [0u8; Self::LENGTH];
+        message[..10].copy_from_slice(&header);
+        message[10..18].copy_from_slice(&stock);
+        message[18] = open_eligibility_status;
+        message[19..23].copy_from_slice(&minimum_allowable_price);
+        message[23..27].copy_from_slice(&maximum_allowable_price);
+        message[27..31].copy_from_slice(&near_execution_price);
+        message[31..39].copy_from_slice(&near_execution_time);
+        message[39..43].copy_from_slice(&lower_price_range_collar);
+        message[43..47].copy_from_slice(&upper_price_range_collar);
+
+        message
+    }
+}
@@ -1,14 +1,14 @@
 use crate::enums::SystemEventCode;
 use crate::messageheader::MessageHeader;
-use crate::types::{BinaryMessageLength, MessageHeaderType, Parse, ParseError};
+use crate::types::{BinaryMessageLength, Encode, MessageHeaderType, Parse, ParseError};
 
 #[cfg(any(test, feature = "bench"))]
 use crate::types::{EnumTestHelpers, GenerateExampleMessage};
 
 #[derive(Debug, PartialEq)]
 pub struct SystemEventMessage {
-    header: MessageHeader,
-    event_code: SystemEventCode,
+    pub header: MessageHeader,
+    pub event_code: SystemEventCode,
 }
 
 impl Parse for SystemEventMessage {
@@ -32,6 +32,17 @@ impl MessageHeaderType for SystemEventMessage {
     const MESSAGE_TYPE: u8 = b'S';
 }
 
+impl Encode for SystemEventMessage {
+    fn message_type(&self) -> u8 {
+        Self::MESSAGE_TYPE
+    }
+
+    fn encode(&self, output: &mut Vec<u8>) {
+        self.header.encode(output);
+        output.push(u8::from(&self.event_code));
+    }
+}
+
 #[cfg(any(test, feature = "bench"))]
 impl GenerateExampleMessage<{ SystemEventMessage::LENGTH }> for SystemEventMessage {
     fn generate_binary_example() -> [u8; SystemEventMessage::LENGTH] {
@@ -40,7 +51,7 @@ impl GenerateExampleMessage<{ SystemEventMessage::LENGTH }> for SystemEventMessa
 
         let mut message = [0u8; SystemEventMessage::LENGTH];
         message[..10].copy_from_slice(&header);
-        message[10] = event_code as u8;
+        message[10] = event_code;
 
         message
     }
@@ -2,12 +2,13 @@ use modifyordermessages::{
     OrderCancel, OrderDelete, OrderExecuted, OrderExecutedWithPrice, OrderReplace,
 };
 use stockmessages::{
-    IPOQuotingPeriodUpdate, MWCBDeclineLevel, MWCBStatus, MarketParticipantPosition,
-    RegSHOShortSalePriceTestRestriction, StockDirectory, StockTradingAction,
+    DLCRPriceDiscovery, IPOQuotingPeriodUpdate, LULDAuctionCollar, MWCBDeclineLevel, MWCBStatus,
+    MarketParticipantPosition, OperationalHalt, RegSHOShortSalePriceTestRestriction,
+    StockDirectory, StockTradingAction,
 };
 use systemmessages::SystemEventMessage;
 use trademessages::{BrokenTrade, CrossingTrade, NonCrossingTrade};
-use types::GenerateExampleMessage;
+use types::{GenerateExampleMessage, Parse, ParseError};
 
 use super::*;
 
@@ -36,6 +37,100 @@ fn test_byte_to_bool_space() -> Result<(), ParseError> {
     Ok(())
 }
 
+#[test]
+fn test_str_to_stock() {
+    assert_eq!(helpers::str_to_stock("aapl"), Some(*b"AAPL    "));
+    assert_eq!(helpers::str_to_stock("GOOGL.XY"), Some(*b"GOOGL.XY"));
+    assert_eq!(helpers::str_to_stock(""), None);
+    assert_eq!(helpers::str_to_stock("TOOLONGTICKER"), None);
+    assert_eq!(helpers::stock_to_str(b"AAPL    "), "AAPL");
+}
+
+#[test]
+fn test_parse_time_of_day() {
+    assert_eq!(
+        helpers::parse_time_of_day("09:30"),
+        Some(34_200_000_000_000)
+    );
+    assert_eq!(
+        helpers::parse_time_of_day("09:30:00.25"),
+        Some(34_200_250_000_000)
+    );
+    assert_eq!(
+        helpers::parse_time_of_day("16:00:01"),
+        Some(57_601_000_000_000)
+    );
+    assert_eq!(helpers::parse_time_of_day("123"), Some(123));
+    assert_eq!(helpers::parse_time_of_day("09:60"), None);
+    assert_eq!(helpers::parse_time_of_day("25:00"), None);
+    assert_eq!(helpers::parse_time_of_day("09:30:00.1234567890"), None);
+    assert_eq!(
+        helpers::format_time_of_day(34_200_250_000_000),
+        "09:30:00.250000000"
+    );
+}
+
+#[test]
+fn test_trading_reason_codes() {
+    assert!(matches!(
+        enums::TradingReasonCodes::try_from(&b"T1  "[..]),
+        Ok(enums::TradingReasonCodes::Halt(
+            enums::TradingHaltReasonCodes::HaltNewsPending
+        ))
+    ));
+    assert!(matches!(
+        enums::TradingReasonCodes::try_from(&b"C11 "[..]),
+        Ok(enums::TradingReasonCodes::Resumption(
+            enums::TradingResumptionReasonCodes::TradeHaltConcluded
+        ))
+    ));
+    assert!(matches!(
+        enums::TradingReasonCodes::try_from(&b"    "[..]),
+        Ok(enums::TradingReasonCodes::Halt(
+            enums::TradingHaltReasonCodes::NotAvailable
+        ))
+    ));
+    assert!(enums::TradingReasonCodes::try_from(&b"ZZZZ"[..]).is_err());
+}
+
+fn assert_codes_round_trip<T, const N: usize>()
+where
+    T: types::EnumTestHelpers<N> + TryFrom<u8, Error = ParseError>,
+    for<'a> u8: From<&'a T>,
+{
+    for code in T::VALID_CODES {
+        assert_eq!(u8::from(&T::try_from(code).unwrap()), code);
+    }
+}
+
+#[test]
+fn test_enum_codes_round_trip() {
+    assert_codes_round_trip::<enums::SystemEventCode, 6>();
+    assert_codes_round_trip::<enums::MarketCategory, 8>();
+    assert_codes_round_trip::<enums::Authenticity, 2>();
+    assert_codes_round_trip::<enums::ShortSaleThresholdIndicator, 3>();
+    assert_codes_round_trip::<enums::LuldReferencePriceTier, 3>();
+    assert_codes_round_trip::<enums::FinancialStatusIndicator, 11>();
+    assert_codes_round_trip::<enums::TradingState, 4>();
+    assert_codes_round_trip::<enums::RegSHOAction, 3>();
+    assert_codes_round_trip::<enums::IssueClassificationCodes, 16>();
+    assert_codes_round_trip::<enums::MarketMakerMode, 5>();
+    assert_codes_round_trip::<enums::MarketParticipantState, 5>();
+    assert_codes_round_trip::<enums::MWCBLevel, 3>();
+    assert_codes_round_trip::<enums::IPOReleaseQualifier, 2>();
+    assert_codes_round_trip::<enums::MarketCode, 3>();
+    assert_codes_round_trip::<enums::OperationalHaltAction, 2>();
+    assert_codes_round_trip::<enums::OpenEligibilityStatus, 2>();
+    assert_codes_round_trip::<enums::BuySellIndicator, 2>();
+    assert_codes_round_trip::<enums::CrossType, 4>();
+    assert_codes_round_trip::<enums::ImbalanceDirection, 4>();
+
+    for code in [b"T1  ", b"T12 ", b"LUDP", b"C11 ", b"IPOQ", b"    "] {
+        let reason = enums::TradingReasonCodes::try_from(&code[..]).unwrap();
+        assert_eq!(&<[u8; 4]>::from(&reason), code);
+    }
+}
+
 // System Events
 #[test]
 fn test_system_event_message() {
@@ -62,6 +157,18 @@ fn test_stock_trading_action() {
     );
 }
 
+#[test]
+fn test_stock_trading_action_space_padded_reason() {
+    // Reason codes shorter than 4 characters are right padded with spaces
+    let mut example_msg = StockTradingAction::generate_binary_example();
+    example_msg[20..24].copy_from_slice(b"T1  ");
+    let parsed = StockTradingAction::parse(&example_msg).unwrap();
+    assert!(matches!(
+        parsed.reason,
+        enums::TradingReasonCodes::Halt(enums::TradingHaltReasonCodes::HaltNewsPending)
+    ));
+}
+
 #[test]
 fn test_reg_sho_short_sale_price_test_restriction() {
     let example_msg = RegSHOShortSalePriceTestRestriction::generate_binary_example();
@@ -109,6 +216,36 @@ fn test_ipo_quoting_period_update() {
     );
 }
 
+#[test]
+fn test_luld_auction_collar() {
+    let example_msg = LULDAuctionCollar::generate_binary_example();
+    let parsed = LULDAuctionCollar::parse(&example_msg);
+    assert!(
+        parsed.is_ok(),
+        "Parsing the luld auction collar message failed"
+    );
+}
+
+#[test]
+fn test_operational_halt() {
+    let example_msg = OperationalHalt::generate_binary_example();
+    let parsed = OperationalHalt::parse(&example_msg);
+    assert!(
+        parsed.is_ok(),
+        "Parsing the operational halt message failed"
+    );
+}
+
+#[test]
+fn test_dlcr_price_discovery() {
+    let example_msg = DLCRPriceDiscovery::generate_binary_example();
+    let parsed = DLCRPriceDiscovery::parse(&example_msg);
+    assert!(
+        parsed.is_ok(),
+        "Parsing the dlcr price discovery message failed"
+    );
+}
+
 // Trade Messages
 #[test]
 fn test_non_crossing_trade() {
@@ -1,6 +1,6 @@
 use crate::enums::CrossType;
 use crate::messageheader::MessageHeader;
-use crate::types::{BinaryMessageLength, MessageHeaderType, Parse, ParseError, Price4, PriceConversions, Stock};
+use crate::types::{BinaryMessageLength, Encode, MessageHeaderType, Parse, ParseError, Price4, PriceConversions, Stock};
 use byteorder::{BigEndian, ByteOrder};
 
 #[cfg(any(test, feature = "bench"))]
@@ -10,13 +10,13 @@ use fastrand::Rng;
 
 #[derive(Debug, PartialEq)]
 pub struct NonCrossingTrade {
-    header: MessageHeader,
-    order_reference_number: u64,
-    buy_sell_indicator: char,
-    shares: u32,
-    stock: Stock,
-    price: Price4,
-    match_number: u64,
+    pub header: MessageHeader,
+    pub order_reference_number: u64,
+    pub buy_sell_indicator: char,
+    pub shares: u32,
+    pub stock: Stock,
+    pub price: Price4,
+    pub match_number: u64,
 }
 
 impl Parse for NonCrossingTrade {
@@ -54,6 +54,22 @@ impl MessageHeaderType for NonCrossingTrade {
     const MESSAGE_TYPE: u8 = b'P';
 }
 
+impl Encode for NonCrossingTrade {
+    fn message_type(&self) -> u8 {
+        Self::MESSAGE_TYPE
+    }
+
+    fn encode(&self, output: &mut Vec<u8>) {
+        self.header.encode(output);
+        output.extend_from_slice(&self.order_reference_number.to_be_bytes());
+        output.push(self.buy_sell_indicator as u8);
+        output.extend_from_slice(&self.shares.to_be_bytes());
+        output.extend_from_slice(&self.stock);
+        output.extend_from_slice(&self.price.value.to_be_bytes());
+        output.extend_from_slice(&self.match_number.to_be_bytes());
+    }
+}
+
 #[cfg(any(test, feature = "bench"))]
 impl GenerateExampleMessage<{ Self::LENGTH }> for NonCrossingTrade {
     fn generate_binary_example() -> [u8; Self::LENGTH] {
@@ -82,12 +98,12 @@ impl GenerateExampleMessage<{ Self::LENGTH }> for NonCrossingTrade {
 
 #[derive(Debug, PartialEq)]
 pub struct CrossingTrade {
-    header: MessageHeader,
-    shares: u64, // 64 for crossing trades, 32 for non-crossing trades
-    stock: Stock,
-    cross_price: Price4,
-    match_number: u64,
-    cross_type: CrossType,
+    pub header: MessageHeader,
+    pub shares: u64, // 64 for crossing trades, 32 for non-crossing trades
+    pub stock: Stock,
+    pub cross_price: Price4,
+    pub match_number: u64,
+    pub cross_type: CrossType,
 }
 
 impl Parse for CrossingTrade {
@@ -127,6 +143,21 @@ impl MessageHeaderType for CrossingTrade {
     const MESSAGE_TYPE: u8 = b'Q';
 }
 
+impl Encode for CrossingTrade {
+    fn message_type(&self) -> u8 {
+        Self::MESSAGE_TYPE
+    }
+
+    fn encode(&self, output: &mut Vec<u8>) {
+        self.header.encode(output);
+        output.extend_from_slice(&self.shares.to_be_bytes());
+        output.extend_from_slice(&self.stock);
+        output.extend_from_slice(&self.cross_price.value.to_be_bytes());
+        output.extend_from_slice(&self.match_number.to_be_bytes());
+        output.push(u8::from(&self.cross_type));
+    }
+}
+
 #[cfg(any(test, feature = "bench"))]
 impl GenerateExampleMessage<{ Self::LENGTH }> for CrossingTrade {
     fn generate_binary_example() -> [u8; Self::LENGTH] {
@@ -153,8 +184,8 @@ impl GenerateExampleMessage<{ Self::LENGTH }> for CrossingTrade {
 
 #[derive(Debug, PartialEq)]
 pub struct BrokenTrade {
-    header: MessageHeader,
-    match_number: u64,
+    pub header: MessageHeader,
+    pub match_number: u64,
 }
 
 impl Parse for BrokenTrade {
@@ -180,6 +211,17 @@ impl MessageHeaderType for BrokenTrade {
     const MESSAGE_TYPE: u8 = b'B';
 }
 
+impl Encode for BrokenTrade {
+    fn message_type(&self) -> u8 {
+        Self::MESSAGE_TYPE
+    }
+
+    fn encode(&self, output: &mut Vec<u8>) {
+        self.header.encode(output);
+        output.extend_from_slice(&self.match_number.to_be_bytes());
+    }
+}
+
 #[cfg(any(test, feature = "bench"))]
 impl GenerateExampleMessage<{ Self::LENGTH }> for BrokenTrade {
     fn generate_binary_example() -> [u8; Self::LENGTH] {
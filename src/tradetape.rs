@@ -0,0 +1,275 @@
+use crate::enums::{BuySellIndicator, CrossType, MessageTypes};
+use crate::events::{Event, EventSink};
+use crate::orderbook::{Execution, StockLocateCode};
+use crate::trademessages::{BrokenTrade, CrossingTrade, NonCrossingTrade};
+use crate::types::{Price4, PriceConversions};
+use std::collections::{HashMap, VecDeque};
+
+/// A single print on the trade tape.
+///
+/// # Fields
+/// * `timestamp` - Nanoseconds since midnight.
+/// * `match_number` - The match number, referenced by `BrokenTrade` messages.
+/// * `price` - The trade price.
+/// * `shares` - The number of shares traded.
+/// * `aggressor` - The side that removed liquidity, i.e. the opposite side of the resting order. `None` if unknown.
+/// * `cross_type` - The cross the trade was part of, `None` for continuous trading.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Trade {
+    pub timestamp: u64,
+    pub match_number: u64,
+    pub price: Price4,
+    pub shares: u64,
+    pub aggressor: Option<BuySellIndicator>,
+    pub cross_type: Option<CrossType>,
+}
+
+/// Collects the trades of every stock, from 'E', 'C', 'P', 'Q' and 'B' messages.
+///
+/// # Fields
+/// * `tapes` - Stock locate code > trades, in the order they were reported.
+pub struct TradeTapeManager {
+    pub tapes: HashMap<StockLocateCode, Vec<Trade>>,
+}
+
+impl Default for TradeTapeManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TradeTapeManager {
+    pub fn new() -> Self {
+        Self {
+            tapes: HashMap::new(),
+        }
+    }
+
+    /// Record an execution against a displayed order, see `OrderBookManager::execute_order`.
+    /// Non-printable executions are skipped, as their volume is reported by a later cross ('Q') message.
+    ///
+    /// # Arguments
+    /// * `execution` - The execution reported by the order book.
+    ///
+    /// # Returns
+    /// * `Option<Trade>` - The recorded trade, `None` if the execution is not printable.
+    pub fn add_execution(&mut self, execution: &Execution) -> Option<Trade> {
+        if !execution.printable {
+            return None;
+        }
+        let aggressor = match execution.buy_sell_indicator {
+            BuySellIndicator::Buy => BuySellIndicator::Sell,
+            BuySellIndicator::Sell => BuySellIndicator::Buy,
+        };
+        Some(self.add_trade(
+            execution.stock_locate,
+            Trade {
+                timestamp: execution.timestamp,
+                match_number: execution.match_number,
+                price: execution.price,
+                shares: u64::from(execution.executed_shares),
+                aggressor: Some(aggressor),
+                cross_type: None,
+            },
+        ))
+    }
+
+    /// Record an execution against a non-displayed order.
+    /// The aggressor is unknown, as the buy sell indicator of 'P' messages has always been 'B' since 2014.
+    ///
+    /// # Arguments
+    /// * `trade` - The non-crossing trade message.
+    ///
+    /// # Returns
+    /// * `Trade` - The recorded trade.
+    pub fn add_non_crossing_trade(&mut self, trade: &NonCrossingTrade) -> Trade {
+        self.add_trade(
+            trade.header.stock_locate,
+            Trade {
+                timestamp: trade.header.timestamp,
+                match_number: trade.match_number,
+                price: trade.price,
+                shares: u64::from(trade.shares),
+                aggressor: None,
+                cross_type: None,
+            },
+        )
+    }
+
+    /// Record the bulk print of a cross.
+    /// Crosses that matched no shares are skipped, the price they report is not a trade.
+    ///
+    /// # Arguments
+    /// * `trade` - The crossing trade message.
+    ///
+    /// # Returns
+    /// * `Option<Trade>` - The recorded trade, `None` if the cross matched no shares.
+    pub fn add_crossing_trade(&mut self, trade: &CrossingTrade) -> Option<Trade> {
+        if trade.shares == 0 {
+            return None;
+        }
+        Some(self.add_trade(
+            trade.header.stock_locate,
+            Trade {
+                timestamp: trade.header.timestamp,
+                match_number: trade.match_number,
+                price: trade.cross_price,
+                shares: trade.shares,
+                aggressor: None,
+                cross_type: Some(trade.cross_type),
+            },
+        ))
+    }
+
+    /// Retract a broken trade from the tape.
+    ///
+    /// # Arguments
+    /// * `trade` - The broken trade message.
+    ///
+    /// # Returns
+    /// * `Some(Trade)` - The retracted trade.
+    /// * `None` - If no trade with the match number is on the tape of the stock.
+    pub fn break_trade(&mut self, trade: &BrokenTrade) -> Option<Trade> {
+        let tape = self.tapes.get_mut(&trade.header.stock_locate)?;
+        // Breaks are rare and usually refer to a recent trade, so search from the back
+        let index = tape
+            .iter()
+            .rposition(|t| t.match_number == trade.match_number);
+        match index {
+            Some(index) => Some(tape.remove(index)),
+            None => {
+                log::warn!("Attempted to break non-existent trade: {:?}", trade);
+                None
+            }
+        }
+    }
+
+    /// The trades of a stock, in the order they were reported.
+    pub fn get_trades(&self, stock_locate: StockLocateCode) -> &[Trade] {
+        self.tapes
+            .get(&stock_locate)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Total shares traded in a stock.
+    pub fn get_volume(&self, stock_locate: StockLocateCode) -> u64 {
+        self.get_trades(stock_locate).iter().map(|t| t.shares).sum()
+    }
+
+    /// Volume weighted average price of a stock.
+    ///
+    /// # Returns
+    /// * `Option<f64>` - The VWAP, or `None` if nothing has traded.
+    pub fn get_vwap(&self, stock_locate: StockLocateCode) -> Option<f64> {
+        let trades = self.get_trades(stock_locate);
+        let volume: u64 = trades.iter().map(|t| t.shares).sum();
+        if volume == 0 {
+            return None;
+        }
+        let notional: f64 = trades
+            .iter()
+            .map(|t| t.price.to_f64() * t.shares as f64)
+            .sum();
+        Some(notional / volume as f64)
+    }
+
+    fn add_trade(&mut self, stock_locate: StockLocateCode, trade: Trade) -> Trade {
+        log::trace!("Trade {:?}", trade);
+        self.tapes.entry(stock_locate).or_default().push(trade);
+        trade
+    }
+}
+
+/// Builds the trade tape from 'P', 'Q' and 'B' messages, and `Execution` events from the order book.
+/// Emits a `Trade` event for every print, and a `TradeBroken` event for every retraction.
+impl EventSink for TradeTapeManager {
+    fn on_message(&mut self, message: &MessageTypes, events: &mut VecDeque<Event>) {
+        match message {
+            MessageTypes::NonCrossingTrade(trade) => {
+                let recorded = self.add_non_crossing_trade(trade);
+                events.push_back(Event::Trade(trade.header.stock_locate, recorded));
+            }
+            MessageTypes::CrossingTrade(trade) => {
+                if let Some(recorded) = self.add_crossing_trade(trade) {
+                    events.push_back(Event::Trade(trade.header.stock_locate, recorded));
+                }
+            }
+            MessageTypes::BrokenTrade(trade) => {
+                if let Some(broken) = self.break_trade(trade) {
+                    events.push_back(Event::TradeBroken(trade.header.stock_locate, broken));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn on_event(&mut self, event: &Event, events: &mut VecDeque<Event>) {
+        if let Event::Execution(execution) = event {
+            if let Some(recorded) = self.add_execution(execution) {
+                events.push_back(Event::Trade(execution.stock_locate, recorded));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::messageheader::MessageHeader;
+
+    fn execution(match_number: u64, price: u32, shares: u32, printable: bool) -> Execution {
+        Execution {
+            timestamp: match_number,
+            stock_locate: 1,
+            order_reference_number: match_number,
+            buy_sell_indicator: BuySellIndicator::Buy,
+            executed_shares: shares,
+            match_number,
+            price: Price4::new(price),
+            printable,
+        }
+    }
+
+    #[test]
+    fn test_trade_tape() {
+        let mut tape = TradeTapeManager::new();
+        tape.add_execution(&execution(1, 100_0000, 100, true));
+        tape.add_execution(&execution(2, 101_0000, 300, true));
+        tape.add_execution(&execution(3, 200_0000, 500, false)); // Reported by the cross instead
+
+        let header = MessageHeader::example(1, 0);
+        tape.add_crossing_trade(&CrossingTrade {
+            header: header.clone(),
+            shares: 600,
+            stock: *b"AAPL    ",
+            cross_price: Price4::new(102_0000),
+            match_number: 4,
+            cross_type: CrossType::ClosingCross,
+        });
+        let unmatched = tape.add_crossing_trade(&CrossingTrade {
+            header: header.clone(),
+            shares: 0,
+            stock: *b"AAPL    ",
+            cross_price: Price4::new(103_0000),
+            match_number: 5,
+            cross_type: CrossType::IPOCrossOrHaltedSecurity,
+        });
+        assert_eq!(unmatched, None);
+
+        let trades = tape.get_trades(1);
+        assert_eq!(trades.len(), 3);
+        assert_eq!(trades[0].aggressor, Some(BuySellIndicator::Sell)); // Resting buy was hit
+        assert_eq!(trades[2].cross_type, Some(CrossType::ClosingCross));
+        assert_eq!(tape.get_volume(1), 1000);
+        assert_eq!(tape.get_vwap(1), Some(101.5));
+        assert!(tape.get_trades(2).is_empty());
+
+        let broken = tape.break_trade(&BrokenTrade {
+            header,
+            match_number: 2,
+        });
+        assert_eq!(broken.map(|t| t.shares), Some(300));
+        assert_eq!(tape.get_volume(1), 700);
+    }
+}
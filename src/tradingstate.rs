@@ -0,0 +1,395 @@
+use crate::enums::{
+    MWCBLevel, MarketCode, MessageTypes, OperationalHaltAction, RegSHOAction,
+    TradingHaltReasonCodes, TradingReasonCodes, TradingState,
+};
+use crate::events::{Event, EventSink};
+use crate::orderbook::StockLocateCode;
+use crate::types::Price8;
+use std::collections::{HashMap, VecDeque};
+
+/// A change to the trading status of a stock.
+///
+/// # Variants
+/// * `TradingState` - A `StockTradingAction` ('H') message changed the trading state, or the reason for it.
+/// * `RegSHO` - A `RegSHOShortSalePriceTestRestriction` ('Y') message changed the short sale price test.
+/// * `OperationalHalt` - An `OperationalHalt` ('h') message halted or resumed the stock on a single market.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StatusChange {
+    TradingState(TradingState, TradingReasonCodes),
+    RegSHO(RegSHOAction),
+    OperationalHalt(MarketCode, OperationalHaltAction),
+}
+
+/// # Fields
+/// * `timestamp` - Nanoseconds since midnight, of the message that made the change.
+/// * `change` - The new state.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transition {
+    pub timestamp: u64,
+    pub change: StatusChange,
+}
+
+/// The trading status of a single stock, and the transitions that led to it.
+///
+/// # Fields
+/// * `trading_state` - The current trading state. Stocks are assumed to be trading until a message says otherwise.
+/// * `reason` - The halt or resumption reason given for the current trading state.
+/// * `reg_sho_action` - The current Reg SHO short sale price test restriction.
+/// * `operational_halts` - The markets the stock is currently operationally halted on.
+/// * `transitions` - Every change, in file order. Messages that repeat the current state are not recorded.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StockTradingStatus {
+    pub trading_state: TradingState,
+    pub reason: TradingReasonCodes,
+    pub reg_sho_action: RegSHOAction,
+    pub operational_halts: Vec<MarketCode>,
+    pub transitions: Vec<Transition>,
+}
+
+impl Default for StockTradingStatus {
+    fn default() -> Self {
+        Self {
+            trading_state: TradingState::Trading,
+            reason: TradingReasonCodes::Halt(TradingHaltReasonCodes::NotAvailable),
+            reg_sho_action: RegSHOAction::NoPriceTestInEffect,
+            operational_halts: Vec::new(),
+            transitions: Vec::new(),
+        }
+    }
+}
+
+impl StockTradingStatus {
+    /// Returns true if the stock is halted or paused, or operationally halted on any market.
+    pub fn is_halted(&self) -> bool {
+        is_halted(self.trading_state) || !self.operational_halts.is_empty()
+    }
+
+    /// The trading state as of a time of day, once every message up to and including `timestamp` is applied.
+    ///
+    /// # Arguments
+    /// * `timestamp` - Nanoseconds since midnight.
+    pub fn trading_state_at(&self, timestamp: u64) -> TradingState {
+        // Timestamps never decrease within a file, so the transitions are sorted
+        let applied = self
+            .transitions
+            .partition_point(|transition| transition.timestamp <= timestamp);
+        self.transitions[..applied]
+            .iter()
+            .rev()
+            .find_map(|transition| match transition.change {
+                StatusChange::TradingState(trading_state, _) => Some(trading_state),
+                StatusChange::RegSHO(_) | StatusChange::OperationalHalt(..) => None,
+            })
+            .unwrap_or(TradingState::Trading)
+    }
+
+    /// The markets the stock was operationally halted on as of a time of day, once every message up to and including
+    /// `timestamp` is applied.
+    ///
+    /// # Arguments
+    /// * `timestamp` - Nanoseconds since midnight.
+    pub fn operational_halts_at(&self, timestamp: u64) -> Vec<MarketCode> {
+        let mut operational_halts = Vec::new();
+        for transition in self
+            .transitions
+            .iter()
+            .take_while(|transition| transition.timestamp <= timestamp)
+        {
+            if let StatusChange::OperationalHalt(market_code, action) = transition.change {
+                apply_operational_halt(&mut operational_halts, market_code, action);
+            }
+        }
+        operational_halts
+    }
+
+    /// Returns true if the stock was halted or paused, or operationally halted on any market, at a time of day.
+    ///
+    /// # Arguments
+    /// * `timestamp` - Nanoseconds since midnight.
+    pub fn was_halted_at(&self, timestamp: u64) -> bool {
+        is_halted(self.trading_state_at(timestamp))
+            || !self.operational_halts_at(timestamp).is_empty()
+    }
+
+    /// The number of times the stock was halted or paused, including opening halted, e.g. ahead of an IPO.
+    /// An operational halt counts as a halt unless the stock was already halted.
+    pub fn halts(&self) -> usize {
+        let mut trading_halted = false;
+        let mut operational_halts = Vec::new();
+        let mut halts = 0;
+        for transition in &self.transitions {
+            let halted = trading_halted || !operational_halts.is_empty();
+            match transition.change {
+                StatusChange::TradingState(trading_state, _) => {
+                    trading_halted = is_halted(trading_state);
+                }
+                StatusChange::OperationalHalt(market_code, action) => {
+                    apply_operational_halt(&mut operational_halts, market_code, action);
+                }
+                StatusChange::RegSHO(_) => {}
+            }
+            if !halted && (trading_halted || !operational_halts.is_empty()) {
+                halts += 1;
+            }
+        }
+        halts
+    }
+
+    fn record(&mut self, timestamp: u64, change: StatusChange) {
+        self.transitions.push(Transition { timestamp, change });
+    }
+}
+
+pub(crate) fn is_halted(trading_state: TradingState) -> bool {
+    matches!(trading_state, TradingState::Halted | TradingState::Paused)
+}
+
+/// Add or remove a market from the markets a stock is operationally halted on.
+///
+/// # Returns
+/// * `bool` - False if the stock was already halted or trading on the market.
+fn apply_operational_halt(
+    operational_halts: &mut Vec<MarketCode>,
+    market_code: MarketCode,
+    action: OperationalHaltAction,
+) -> bool {
+    let halted = operational_halts.contains(&market_code);
+    match action {
+        OperationalHaltAction::Halted if !halted => operational_halts.push(market_code),
+        OperationalHaltAction::Resumed if halted => {
+            operational_halts.retain(|halted_on| *halted_on != market_code)
+        }
+        _ => return false,
+    }
+    true
+}
+
+/// Tracks the trading state of every stock from 'H' (trading action), 'h' (operational halt) and 'Y' (Reg SHO)
+/// messages, and the market wide circuit breaker (MWCB) from 'V' (decline level) and 'W' (status) messages.
+///
+/// # Fields
+/// * `stocks` - The status of every stock that has had a trading action, operational halt or Reg SHO message.
+/// * `mwcb_decline_levels` - The level 1, 2 and 3 decline levels of the S&P 500, from the 'V' message.
+/// * `mwcb_breaches` - (timestamp, level) of every breach, in file order.
+#[derive(Debug, Default)]
+pub struct TradingStateManager {
+    pub stocks: HashMap<StockLocateCode, StockTradingStatus>,
+    pub mwcb_decline_levels: Option<[Price8; 3]>,
+    pub mwcb_breaches: Vec<(u64, MWCBLevel)>,
+}
+
+impl TradingStateManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get_status(&self, stock_locate: StockLocateCode) -> Option<&StockTradingStatus> {
+        self.stocks.get(&stock_locate)
+    }
+
+    /// Returns true if a stock is currently halted or paused, or operationally halted on any market.
+    pub fn is_halted(&self, stock_locate: StockLocateCode) -> bool {
+        self.get_status(stock_locate)
+            .is_some_and(StockTradingStatus::is_halted)
+    }
+
+    /// Returns true if a stock was halted or paused, or operationally halted on any market, at a time of day.
+    ///
+    /// # Arguments
+    /// * `stock_locate` - The stock locate code of the stock.
+    /// * `timestamp` - Nanoseconds since midnight.
+    pub fn was_halted_at(&self, stock_locate: StockLocateCode, timestamp: u64) -> bool {
+        self.get_status(stock_locate)
+            .is_some_and(|status| status.was_halted_at(timestamp))
+    }
+
+    /// The highest circuit breaker level breached as of a time of day, `None` if none was.
+    /// Levels only escalate during a day, so this is the last breach up to `timestamp`.
+    ///
+    /// # Arguments
+    /// * `timestamp` - Nanoseconds since midnight.
+    pub fn mwcb_level_at(&self, timestamp: u64) -> Option<MWCBLevel> {
+        self.mwcb_breaches
+            .iter()
+            .take_while(|(breached_at, _)| *breached_at <= timestamp)
+            .last()
+            .map(|(_, level)| *level)
+    }
+}
+
+impl EventSink for TradingStateManager {
+    fn on_message(&mut self, message: &MessageTypes, _events: &mut VecDeque<Event>) {
+        match message {
+            MessageTypes::StockTradingAction(action) => {
+                let status = self.stocks.entry(action.header.stock_locate).or_default();
+                if (status.trading_state, status.reason) != (action.trading_state, action.reason) {
+                    status.trading_state = action.trading_state;
+                    status.reason = action.reason;
+                    status.record(
+                        action.header.timestamp,
+                        StatusChange::TradingState(action.trading_state, action.reason),
+                    );
+                }
+            }
+            MessageTypes::OperationalHalt(halt) => {
+                let status = self.stocks.entry(halt.header.stock_locate).or_default();
+                if apply_operational_halt(
+                    &mut status.operational_halts,
+                    halt.market_code,
+                    halt.operational_halt_action,
+                ) {
+                    status.record(
+                        halt.header.timestamp,
+                        StatusChange::OperationalHalt(
+                            halt.market_code,
+                            halt.operational_halt_action,
+                        ),
+                    );
+                }
+            }
+            MessageTypes::RegSHO(reg_sho) => {
+                let status = self.stocks.entry(reg_sho.header.stock_locate).or_default();
+                if status.reg_sho_action != reg_sho.reg_sho_action {
+                    status.reg_sho_action = reg_sho.reg_sho_action;
+                    status.record(
+                        reg_sho.header.timestamp,
+                        StatusChange::RegSHO(reg_sho.reg_sho_action),
+                    );
+                }
+            }
+            MessageTypes::MWCBDeclineLevel(decline_level) => {
+                self.mwcb_decline_levels = Some([
+                    decline_level.level1,
+                    decline_level.level2,
+                    decline_level.level3,
+                ]);
+            }
+            MessageTypes::MWCBStatus(status) => {
+                self.mwcb_breaches
+                    .push((status.header.timestamp, status.breached_level));
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enums::TradingResumptionReasonCodes;
+    use crate::messageheader::MessageHeader;
+    use crate::stockmessages::{
+        MWCBStatus, OperationalHalt, RegSHOShortSalePriceTestRestriction, StockTradingAction,
+    };
+
+    fn trading_action(timestamp: u64, trading_state: TradingState, reason: &[u8]) -> MessageTypes {
+        MessageTypes::StockTradingAction(StockTradingAction {
+            header: MessageHeader::example(7, timestamp),
+            stock: *b"AAPL    ",
+            trading_state,
+            reserved: b' ',
+            reason: TradingReasonCodes::try_from(reason).unwrap(),
+        })
+    }
+
+    #[test]
+    fn test_trading_state_manager() {
+        let mut manager = TradingStateManager::new();
+        let mut events = VecDeque::new();
+        let messages = [
+            trading_action(1, TradingState::Trading, b"    "),
+            trading_action(10, TradingState::Halted, b"T1  "),
+            trading_action(20, TradingState::Halted, b"T1  "),
+            trading_action(30, TradingState::QuotationOnly, b"T3  "),
+            MessageTypes::RegSHO(RegSHOShortSalePriceTestRestriction {
+                header: MessageHeader::example(7, 35),
+                stock: *b"AAPL    ",
+                reg_sho_action: RegSHOAction::RegSHOShortSalePriceTestRestriction,
+            }),
+            trading_action(40, TradingState::Trading, b"T3  "),
+            MessageTypes::MWCBStatus(MWCBStatus {
+                header: MessageHeader::example(0, 50),
+                breached_level: MWCBLevel::Level1,
+            }),
+        ];
+        for message in &messages {
+            manager.on_message(message, &mut events);
+        }
+        assert!(events.is_empty());
+
+        // Repeated and start of day trading actions are not transitions
+        let status = manager.get_status(7).unwrap();
+        assert_eq!(status.transitions.len(), 4);
+        assert_eq!(
+            status.transitions[1].change,
+            StatusChange::TradingState(
+                TradingState::QuotationOnly,
+                TradingReasonCodes::Resumption(TradingResumptionReasonCodes::NewsAndResumptionTime)
+            )
+        );
+        assert_eq!(
+            status.reg_sho_action,
+            RegSHOAction::RegSHOShortSalePriceTestRestriction
+        );
+        assert_eq!(status.halts(), 1);
+        assert!(!manager.is_halted(7));
+
+        assert!(!manager.was_halted_at(7, 9));
+        assert!(manager.was_halted_at(7, 10));
+        assert!(manager.was_halted_at(7, 29));
+        assert!(!manager.was_halted_at(7, 30));
+        assert_eq!(status.trading_state_at(35), TradingState::QuotationOnly);
+        assert_eq!(status.trading_state_at(u64::MAX), TradingState::Trading);
+        assert!(!manager.was_halted_at(8, 10));
+
+        assert_eq!(manager.mwcb_level_at(49), None);
+        assert_eq!(manager.mwcb_level_at(50), Some(MWCBLevel::Level1));
+    }
+
+    fn operational_halt(
+        timestamp: u64,
+        market_code: MarketCode,
+        operational_halt_action: OperationalHaltAction,
+    ) -> MessageTypes {
+        MessageTypes::OperationalHalt(OperationalHalt {
+            header: MessageHeader::example(7, timestamp),
+            stock: *b"AAPL    ",
+            market_code,
+            operational_halt_action,
+        })
+    }
+
+    #[test]
+    fn test_operational_halt() {
+        let mut manager = TradingStateManager::new();
+        let mut events = VecDeque::new();
+        let messages = [
+            operational_halt(10, MarketCode::Nasdaq, OperationalHaltAction::Halted),
+            operational_halt(15, MarketCode::Nasdaq, OperationalHaltAction::Halted),
+            trading_action(20, TradingState::Halted, b"T1  "),
+            operational_halt(30, MarketCode::Nasdaq, OperationalHaltAction::Resumed),
+            trading_action(40, TradingState::Trading, b"T1  "),
+            operational_halt(50, MarketCode::Bx, OperationalHaltAction::Halted),
+        ];
+        for message in &messages {
+            manager.on_message(message, &mut events);
+        }
+
+        // The trading state is unaffected, but the stock is halted on a market
+        let status = manager.get_status(7).unwrap();
+        assert_eq!(status.transitions.len(), 5);
+        assert_eq!(status.trading_state, TradingState::Trading);
+        assert_eq!(status.operational_halts, [MarketCode::Bx]);
+        assert!(manager.is_halted(7));
+
+        assert!(!manager.was_halted_at(7, 9));
+        assert!(manager.was_halted_at(7, 10));
+        assert_eq!(status.operational_halts_at(25), [MarketCode::Nasdaq]);
+        assert!(manager.was_halted_at(7, 35));
+        assert!(!manager.was_halted_at(7, 45));
+        assert!(manager.was_halted_at(7, 50));
+
+        // The trading halt during the operational halt is not a separate halt
+        assert_eq!(status.halts(), 2);
+    }
+}
@@ -1,20 +1,20 @@
-use crate::orderbook::{OrderBookManager, StockLocateCode};
-use crate::stockdirectory::StockDirectoryManager;
-use crate::types::PriceConversions;
-use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
+use crossterm::event::{self, KeyCode, KeyEventKind};
+use fastasx::bookview::PublishedBook;
+use fastasx::helpers::stock_to_str;
+use fastasx::types::{PriceConversions, Stock};
 use ratatui::{
     style::Stylize,
     widgets::{BarChart, Paragraph},
     DefaultTerminal,
 };
 use std::io;
-use std::sync::{Arc, RwLock};
+use std::sync::Arc;
 use std::time::Duration;
 
 pub fn run(
     mut terminal: DefaultTerminal,
-    order_book_manager: Arc<RwLock<OrderBookManager>>,
-    stock_directory_manager: Arc<RwLock<StockDirectoryManager>>,
+    published_book: Arc<PublishedBook>,
+    stock: Stock,
 ) -> io::Result<()> {
     loop {
         terminal.draw(|frame| {
@@ -36,43 +36,36 @@ pub fn run(
                 .on_blue();
             frame.render_widget(greeting, chunks[0]);
 
-            // Acquire read lock within the draw closure
-            let order_manager = order_book_manager.read().unwrap();
-            let stock_directory_manager = stock_directory_manager.read().unwrap();
-
-            let stock = b"AAPL    ";
-            let stock_str = std::str::from_utf8(stock).unwrap().trim();
-
-            let stock_locate_code = match stock_directory_manager.stock_to_locate(*stock) {
-                Some(stock_locate_code) => stock_locate_code,
-                None => {
-                    // Render a stock not found popup
-                    let popup = ratatui::widgets::Paragraph::new("Stock not found")
-                        .block(
-                            ratatui::widgets::Block::default()
-                                .title("Error")
-                                .borders(ratatui::widgets::Borders::ALL),
-                        )
-                        .style(ratatui::style::Style::default().fg(ratatui::style::Color::Red))
-                        .alignment(ratatui::layout::Alignment::Center);
-
-                    let area = ratatui::layout::Rect::new(0, 0, size.width, size.height);
-                    frame.render_widget(ratatui::widgets::Clear, area); // Clear the area first
-                    frame.render_widget(popup, area);
-
-                    return; // Exit the draw closure early
-                }
-            };
+            // The replay publishes copies of the book, so drawing never holds up the order book manager
+            let book = published_book.load();
+
+            let stock_str = stock_to_str(&stock);
+
+            if book.stock_locate.is_none() {
+                // Render a stock not found popup
+                let popup = ratatui::widgets::Paragraph::new("Stock not found")
+                    .block(
+                        ratatui::widgets::Block::default()
+                            .title("Error")
+                            .borders(ratatui::widgets::Borders::ALL),
+                    )
+                    .style(ratatui::style::Style::default().fg(ratatui::style::Color::Red))
+                    .alignment(ratatui::layout::Alignment::Center);
+
+                let area = ratatui::layout::Rect::new(0, 0, size.width, size.height);
+                frame.render_widget(ratatui::widgets::Clear, area); // Clear the area first
+                frame.render_widget(popup, area);
+
+                return; // Exit the draw closure early
+            }
 
             let num_bins = 25;
-            let price_quantities = match order_manager.order_books.get(&stock_locate_code) {
-                Some(book) => book
-                    .bid_book
-                    .iter()
-                    .map(|(price, bucket)| (price.0.to_f64(), bucket.share_quantity as u64))
-                    .collect(),
-                None => Vec::new(),
-            };
+            let price_quantities: Vec<_> = book
+                .depth
+                .bids
+                .iter()
+                .map(|level| (level.price.to_f64(), level.share_quantity as u64))
+                .collect();
             let binned_bid_book_data = bin_orderbook_data(
                 &price_quantities,
                 num_bins,
@@ -128,6 +121,7 @@ pub fn run(
 use std::collections::HashMap;
 
 /// Binning strategy for order book visualization.
+#[allow(dead_code)]
 enum BinningStrategy {
     Uniform,
     Logarithmic,
@@ -7,7 +7,7 @@ pub type Stock = [u8; 8];
 #[cfg(any(test, feature = "bench"))]
 impl GenerateExampleMessage<8> for Stock {
     fn generate_binary_example() -> [u8; 8] {
-        [b'T', b'E', b'S', b'T', b' ', b' ', b' ', b' ']
+        *b"TEST    "
     }
 }
 
@@ -46,14 +46,14 @@ impl PriceConversions<u32> for Price4 {
     fn convert_to_string(&self) -> String {
         let integer_part = self.value / 10_000;
         let fractional_part = self.value % 10_000;
-        format!("{}.{}", integer_part, format!("{:04}", fractional_part))
+        format!("{}.{:04}", integer_part, fractional_part)
     }
 }
 
 /// `Price8` uses `u64` for value and has a fixed precision of 8 decimal places.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub struct Price8 {
-    value: u64, // Price value in fixed-point format
+    pub value: u64, // Price value in fixed-point format
 }
 
 impl PriceConversions<u64> for Price8 {
@@ -76,7 +76,7 @@ impl PriceConversions<u64> for Price8 {
     fn convert_to_string(&self) -> String {
         let integer_part = self.value / 100_000_000;
         let fractional_part = self.value % 100_000_000;
-        format!("{}.{}", integer_part, format!("{:08}", fractional_part))
+        format!("{}.{:08}", integer_part, fractional_part)
     }
 }
 
@@ -107,6 +107,15 @@ pub enum ParseError {
     #[error("Failed to parse message, slice may be incomplete. Expected {expected} bytes.")]
     IncompleteMessage { expected: usize },
 
+    #[error("Unknown message type encountered: {message_type}")]
+    UnknownMessageType { message_type: u8 },
+
+    #[error("Invalid message length encountered: {length}")]
+    InvalidMessageLength { length: u16 },
+
+    #[error("Failed to read message: {0}")]
+    Io(#[from] std::io::Error),
+
     //System Messages
     #[error("Invalid SystemEventCode encountered: {invalid_byte}")]
     InvalidSystemEventCode { invalid_byte: u8 },
@@ -155,6 +164,15 @@ pub enum ParseError {
     #[error("Invalid IPOReleaseQualifier encountered: {invalid_byte}")]
     InvalidIPOReleaseQualifier { invalid_byte: u8 },
 
+    #[error("Invalid MarketCode encountered: {invalid_byte}")]
+    InvalidMarketCode { invalid_byte: u8 },
+
+    #[error("Invalid OperationalHaltAction encountered: {invalid_byte}")]
+    InvalidOperationalHaltAction { invalid_byte: u8 },
+
+    #[error("Invalid OpenEligibilityStatus encountered: {invalid_byte}")]
+    InvalidOpenEligibilityStatus { invalid_byte: u8 },
+
     // Add Order Messages
     #[error("Invalid BuySellType encountered: {invalid_byte}")]
     InvalidBuySellIndicator { invalid_byte: u8 },
@@ -179,6 +197,30 @@ pub enum OrderBookError {
     InvalidCancellation,
 }
 
+#[derive(Debug, Error)]
+pub enum SnapshotError {
+    #[error("Failed to read snapshot: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Not an order book snapshot")]
+    InvalidMagic,
+
+    #[error("Unsupported snapshot version: {version}")]
+    UnsupportedVersion { version: u16 },
+
+    #[error("Invalid order in snapshot: {0}")]
+    Parse(#[from] ParseError),
+
+    #[error("Snapshot contains a message other than an add order")]
+    UnexpectedMessage,
+
+    #[error("Failed to restore order: {0}")]
+    OrderBook(#[from] OrderBookError),
+
+    #[error("Snapshot has {read} orders, expected {expected}")]
+    OrderCount { read: u64, expected: u64 },
+}
+
 pub trait BinaryMessageLength {
     const LENGTH: usize;
 }
@@ -200,6 +242,15 @@ pub trait Parse: Sized {
     fn parse(input: &[u8]) -> Result<Self, ParseError>;
 }
 
+/// The inverse of `Parse`, for writing messages back out in the ITCH 5.0 wire format.
+pub trait Encode {
+    /// The message type byte the body is framed with, e.g. 'F' rather than 'A' for an add order with an MPID.
+    fn message_type(&self) -> u8;
+
+    /// Append the message body (everything after the message type byte), as accepted by `Parse::parse`.
+    fn encode(&self, output: &mut Vec<u8>);
+}
+
 pub trait GenerateExampleMessage<const N: usize> {
     fn generate_binary_example() -> [u8; N];
 }